@@ -2,11 +2,16 @@ use std::time::Duration;
 
 use criterion::{Criterion, criterion_group, criterion_main};
 use diesel::{Connection, SqliteConnection, result::Error};
-use evmnodetooling::dieselsqlite::{models::*, *};
+use evmnodetooling::dieselsqlite::{
+    applyblueprint::*,
+    models::*,
+    types::{self, BlockHash, Bytes, L2Level, TxHash},
+    *,
+};
 
 fn criterion_insert_blueprint(c: &mut Criterion) {
     let connection = &mut establish_connection().unwrap();
-    let block_number = load_block_number();
+    let block_number = load_block_number().unwrap();
 
     let select_id = block_number;
     let clear_id = Blueprint::top_level(connection).unwrap();
@@ -27,8 +32,8 @@ fn criterion_insert_blueprint(c: &mut Criterion) {
 
 fn run_insert_blueprint(
     connection: &mut SqliteConnection,
-    id: &mut i32,
-    payload: &Vec<u8>,
+    id: &mut L2Level,
+    payload: &Bytes,
     timestamp: i64,
 ) {
     let blueprint = Blueprint {
@@ -38,12 +43,12 @@ fn run_insert_blueprint(
     };
     let _ = blueprint.insert(connection);
 
-    *id = *id + 1;
+    *id += 1;
 }
 
 fn criterion_insert_block(c: &mut Criterion) {
     let connection = &mut establish_connection().unwrap();
-    let block_number = load_block_number();
+    let block_number = load_block_number().unwrap();
 
     let select_id = block_number;
     let clear_id = Block::top_level(connection).unwrap();
@@ -65,13 +70,13 @@ fn criterion_insert_block(c: &mut Criterion) {
 
 fn run_insert_block(
     connection: &mut SqliteConnection,
-    level: &mut i32,
+    level: &mut L2Level,
     bytes: &mut [u8; 32],
-    block: &Vec<u8>,
+    block: &Bytes,
 ) {
     let block = Block {
         level: *level,
-        hash: rand_32_bytes_vec(bytes),
+        hash: BlockHash(rand_32_bytes(bytes)),
         block: block.clone(),
     };
     block.insert(connection).unwrap();
@@ -80,7 +85,7 @@ fn run_insert_block(
 
 fn criterion_insert_transactions(c: &mut Criterion) {
     let connection = &mut establish_connection().unwrap();
-    let block_number = load_block_number();
+    let block_number = load_block_number().unwrap();
 
     let select_id = block_number;
     let clear_id = Block::top_level(connection).unwrap();
@@ -102,11 +107,12 @@ fn criterion_insert_transactions(c: &mut Criterion) {
 
 fn run_insert_transactions(
     connection: &mut SqliteConnection,
-    transactions: &Vec<(Vec<u8>, i32, Vec<u8>, Option<Vec<u8>>, Vec<u8>, Vec<u8>)>,
-    insert_id: &mut i32,
+    transactions: &[Transaction],
+    insert_id: &mut L2Level,
     bytes: &mut [u8; 32],
 ) {
-    let transactions = generate_transactions_with_hash(transactions, *insert_id, bytes);
+    let block_hash = BlockHash(rand_32_bytes(bytes));
+    let transactions = generate_transactions_with_hash(transactions, *insert_id, block_hash, bytes);
 
     let _ = Transaction::batch_insert(connection, &transactions);
 
@@ -115,20 +121,20 @@ fn run_insert_transactions(
 
 fn criterion_insert_context_hash(c: &mut Criterion) {
     let connection = &mut establish_connection().unwrap();
-    let block_number = load_block_number();
+    let block_number = load_block_number().unwrap();
 
     let select_id = block_number;
     let clear_id = Block::top_level(connection).unwrap();
     let mut insert_id = clear_id + 1;
 
-    let context_hash = ContextHash::select(connection, select_id).unwrap();
+    let context_hash = ContextHashRow::select(connection, select_id).unwrap();
 
     connection
         .transaction::<_, Error, _>(|conn| {
             c.bench_function("step Insert Context Hash", |b| {
                 b.iter(|| run_insert_context_hash(conn, &mut insert_id, &context_hash))
             });
-            let _ = ContextHash::clear_after(conn, clear_id);
+            let _ = ContextHashRow::clear_after(conn, clear_id);
             Ok(())
         })
         .unwrap();
@@ -136,12 +142,12 @@ fn criterion_insert_context_hash(c: &mut Criterion) {
 
 fn run_insert_context_hash(
     connection: &mut SqliteConnection,
-    insert_id: &mut i32,
-    context_hash: &Vec<u8>,
+    insert_id: &mut L2Level,
+    context_hash: &types::ContextHash,
 ) {
-    let context_hash = ContextHash {
+    let context_hash = ContextHashRow {
         id: *insert_id,
-        context_hash: context_hash.clone(),
+        context_hash: *context_hash,
     };
     context_hash.insert(connection).unwrap();
     *insert_id += 1;
@@ -167,84 +173,38 @@ fn run_select_history_mode(connection: &mut SqliteConnection) {
 fn criterion_apply_blueprint(c: &mut Criterion) {
     let mut group = c.benchmark_group("Apply blueprint group");
 
-    let block_number = load_block_number();
+    let block_number = load_block_number().unwrap();
 
     let connection = &mut establish_connection().unwrap();
     let select_id = block_number;
     let base_insert_id = Blueprint::top_level(connection).unwrap();
-    let (payload, timestamp) = Blueprint::select(connection, select_id).unwrap();
-    let block = Block::select_with_level(connection, select_id).unwrap();
+    let template = BlueprintTemplate::select(connection, select_id);
     let bytes = &mut [0u8; 32];
 
     let mut insert_id = base_insert_id + 1;
-    let transactions = select_transactions(connection, select_id);
-    let context_hash = ContextHash::select(connection, select_id).unwrap();
 
     group.warm_up_time(Duration::from_millis(500));
     group.measurement_time(Duration::from_secs(3));
     group.sample_size(50);
 
     group.bench_function("Apply blueprint", |b| {
-        b.iter(|| {
-            run_apply_blueprint(
-                connection,
-                &mut insert_id,
-                &payload,
-                timestamp,
-                &block,
-                &transactions,
-                &context_hash,
-                bytes,
-            )
-        })
+        b.iter(|| run_apply_blueprint(connection, &mut insert_id, &template, bytes))
     });
 
-    let _ = clear_after_apply_blueprint(connection, base_insert_id);
+    clear_after_apply_blueprint(connection, base_insert_id);
 
     group.finish();
 }
 
 fn run_apply_blueprint(
     connection: &mut SqliteConnection,
-    insert_id: &mut i32,
-    payload: &Vec<u8>,
-    timestamp: i64,
-    block: &Vec<u8>,
-    transactions: &Vec<(Vec<u8>, i32, Vec<u8>, Option<Vec<u8>>, Vec<u8>, Vec<u8>)>,
-    context_hash: &Vec<u8>,
+    insert_id: &mut L2Level,
+    template: &BlueprintTemplate,
     bytes: &mut [u8; 32],
 ) {
-    connection
-        .transaction::<_, Error, _>(|conn| {
-            let _ = PendingConfirmation::select_with_level(conn, *insert_id);
-
-            let blueprint = Blueprint {
-                id: *insert_id,
-                payload: payload.clone(),
-                timestamp,
-            };
-            blueprint.insert(conn)?;
-            let block = Block {
-                level: *insert_id,
-                hash: rand_32_bytes_vec(bytes),
-                block: block.clone(),
-            };
-            block.insert(conn)?;
-            let transactions = generate_transactions_with_hash(&transactions, *insert_id, bytes);
-            Transaction::batch_insert(conn, &transactions)?;
-            // for tx in transactions{
-            //     tx.insert(conn)?;
-            // }
-            let context_hash = ContextHash {
-                id: *insert_id,
-                context_hash: context_hash.clone(),
-            };
-            context_hash.insert(conn)?;
-            let _history_mode = Metadata::get_history_mode(conn)?;
-            *insert_id += 1;
-            Ok(())
-        })
-        .unwrap();
+    let input = template.input(*insert_id, bytes);
+    apply_blueprint(connection, input).unwrap();
+    *insert_id += 1;
 }
 
 criterion_group!(
@@ -260,63 +220,94 @@ criterion_main!(benches);
 
 //Helper functions
 
-fn select_transactions(
-    connection: &mut SqliteConnection,
-    select_id: i32,
-) -> Vec<(Vec<u8>, i32, Vec<u8>, Option<Vec<u8>>, Vec<u8>, Vec<u8>)> {
+//Content of an existing level, replayed at new levels with fresh hashes
+struct BlueprintTemplate {
+    payload: Bytes,
+    timestamp: i64,
+    block: Bytes,
+    transactions: Vec<Transaction>,
+    context_hash: types::ContextHash,
+}
+
+impl BlueprintTemplate {
+    fn select(connection: &mut SqliteConnection, select_id: L2Level) -> BlueprintTemplate {
+        let (payload, timestamp) = Blueprint::select(connection, select_id).unwrap();
+        BlueprintTemplate {
+            payload,
+            timestamp,
+            block: Block::select_with_level(connection, select_id).unwrap(),
+            transactions: select_transactions(connection, select_id),
+            context_hash: ContextHashRow::select(connection, select_id).unwrap(),
+        }
+    }
+
+    fn input(&self, insert_id: L2Level, bytes: &mut [u8; 32]) -> ApplyBlueprint {
+        let block_hash = BlockHash(rand_32_bytes(bytes));
+        ApplyBlueprint {
+            blueprint: Blueprint {
+                id: insert_id,
+                payload: self.payload.clone(),
+                timestamp: self.timestamp,
+            },
+            block: Block {
+                level: insert_id,
+                hash: block_hash,
+                block: self.block.clone(),
+            },
+            transactions: generate_transactions_with_hash(
+                &self.transactions,
+                insert_id,
+                block_hash,
+                bytes,
+            ),
+            context_hash: ContextHashRow {
+                id: insert_id,
+                context_hash: self.context_hash,
+            },
+        }
+    }
+}
+
+fn select_transactions(connection: &mut SqliteConnection, select_id: L2Level) -> Vec<Transaction> {
     let transactions_receipts =
         Transaction::select_receipts_from_block_number(connection, select_id).unwrap();
     let transaction_objects =
         Transaction::select_objects_from_block_number(connection, select_id).unwrap();
 
     transactions_receipts
-        .into_iter()
-        .zip(transaction_objects.into_iter())
-        .map(
-            |((block_hash, index_, _, from_, to_, receipt_fields), (_, _, _, _, object_fields))| {
-                (
-                    block_hash,
-                    index_,
-                    from_,
-                    to_,
-                    receipt_fields,
-                    object_fields,
-                )
-            },
-        )
-        .collect::<Vec<(Vec<u8>, i32, Vec<u8>, Option<Vec<u8>>, Vec<u8>, Vec<u8>)>>()
+        .iter()
+        .zip(&transaction_objects)
+        .map(|(receipt, object)| Transaction::from_decoded(receipt, object))
+        .collect::<Vec<Transaction>>()
 }
 
 fn generate_transactions_with_hash(
-    transactions: &Vec<(Vec<u8>, i32, Vec<u8>, Option<Vec<u8>>, Vec<u8>, Vec<u8>)>,
-    block_number: i32,
+    transactions: &[Transaction],
+    block_number: L2Level,
+    block_hash: BlockHash,
     bytes: &mut [u8; 32],
 ) -> Vec<Transaction> {
     transactions
-        .into_iter()
-        .map(
-            |(block_hash, index_, from_, to_, receipt_fields, object_fields)| Transaction {
-                block_hash: block_hash.clone(),
-                block_number,
-                index_: index_.clone(),
-                hash: rand_32_bytes_vec(bytes),
-                from_: from_.clone(),
-                to_: to_.clone(),
-                receipt_fields: receipt_fields.clone(),
-                object_fields: object_fields.clone(),
-            },
-        )
+        .iter()
+        .map(|transaction| Transaction {
+            block_hash,
+            block_number,
+            hash: TxHash(rand_32_bytes(bytes)),
+            receipt_fields: transaction.receipt_fields.clone(),
+            object_fields: transaction.object_fields.clone(),
+            ..*transaction
+        })
         .collect::<Vec<Transaction>>()
 }
 
-fn clear_after_apply_blueprint(connection: &mut SqliteConnection, clear_id: i32) {
+fn clear_after_apply_blueprint(connection: &mut SqliteConnection, clear_id: L2Level) {
     let _ = Blueprint::clear_after(connection, clear_id);
     let _ = Block::clear_after(connection, clear_id);
-    let _ = ContextHash::clear_after(connection, clear_id);
+    let _ = ContextHashRow::clear_after(connection, clear_id);
     let _ = Transaction::clear_after(connection, clear_id);
 }
 
-fn rand_32_bytes_vec(bytes: &mut [u8; 32]) -> Vec<u8> {
+fn rand_32_bytes(bytes: &mut [u8; 32]) -> [u8; 32] {
     rand::fill(bytes);
-    Vec::from(bytes)
+    *bytes
 }
@@ -0,0 +1,56 @@
+use std::{env, fs, time::Duration};
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use evmnodetooling::dieselsqlite::{
+    applyblueprint::apply_blueprint,
+    bootstrap::create_store,
+    fixture::{FixtureConfig, FixtureGenerator},
+    models::Block,
+};
+
+const TRANSACTIONS_PER_BLOCK: [i32; 3] = [0, 10, 500];
+const PREFILLED_LEVELS: i32 = 100;
+
+fn criterion_fixture_apply_blueprint(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Fixture apply blueprint group");
+
+    group.warm_up_time(Duration::from_millis(500));
+    group.measurement_time(Duration::from_secs(3));
+    group.sample_size(50);
+
+    for transactions_per_block in TRANSACTIONS_PER_BLOCK {
+        let path = env::temp_dir().join(format!(
+            "evmnodetooling-fixture-{transactions_per_block}.sqlite"
+        ));
+        let _ = fs::remove_file(&path);
+        let mut connection = create_store(path.to_str().unwrap()).unwrap();
+
+        let mut generator = FixtureGenerator::new(FixtureConfig {
+            levels: PREFILLED_LEVELS,
+            transactions_per_block,
+            ..FixtureConfig::default()
+        });
+        generator.generate(&mut connection).unwrap();
+        let mut insert_id = Block::top_level(&mut connection).unwrap() + 1;
+
+        group.bench_with_input(
+            BenchmarkId::new("Fixture apply blueprint", transactions_per_block),
+            &transactions_per_block,
+            |b, _| {
+                b.iter(|| {
+                    let input = generator.apply_blueprint_input(insert_id);
+                    apply_blueprint(&mut connection, input).unwrap();
+                    insert_id += 1;
+                })
+            },
+        );
+
+        drop(connection);
+        let _ = fs::remove_file(&path);
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, criterion_fixture_apply_blueprint);
+criterion_main!(benches);
@@ -0,0 +1,34 @@
+use std::env;
+
+use evmnodetooling::dieselsqlite::{
+    accountindex::{ACCOUNT_INDEX_URL_KEY, create_account_index, update_account_index},
+    establish_read_only_connection, parse_argument,
+    types::L2Level,
+};
+
+//Usage: accountindex [index path] [upper level]
+fn main() {
+    dotenvy::dotenv().ok();
+    let mut args = env::args().skip(1);
+    let index_url = args
+        .next()
+        .or_else(|| env::var(ACCOUNT_INDEX_URL_KEY).ok())
+        .expect("Provide the path of the account index");
+    let upper_level: Option<L2Level> = parse_argument(args.next(), "upper level");
+
+    let store = &mut establish_read_only_connection().unwrap();
+    let index = &mut create_account_index(&index_url).unwrap();
+
+    let update = update_account_index(index, store, upper_level).unwrap();
+
+    if update.rebuilt {
+        println!("The index didn't match the store anymore, it was rebuilt");
+    }
+    match update.indexed_level {
+        Some(level) => println!(
+            "Indexed {} transactions, up to level {}",
+            update.indexed, level
+        ),
+        None => println!("Nothing to index"),
+    }
+}
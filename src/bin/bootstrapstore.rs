@@ -0,0 +1,51 @@
+use std::env;
+
+use evmnodetooling::dieselsqlite::{
+    DATABASE_URL_KEY,
+    bootstrap::{create_store, create_store_like},
+    fixture::{FixtureConfig, generate},
+    parse_argument,
+    store::{Store, StoreConfig},
+};
+
+//Usage: bootstrapstore [--like template path] [path] [levels] [transactions per block] [seed]
+fn main() {
+    dotenvy::dotenv().ok();
+    let mut args = env::args().skip(1).peekable();
+    let template = args
+        .next_if(|argument| argument == "--like")
+        .map(|_| args.next().expect("Provide the path of the template store"));
+    let database_url = args
+        .next()
+        .or_else(|| env::var(DATABASE_URL_KEY).ok())
+        .expect("Provide the path of the store to create");
+    let levels: Option<i32> = parse_argument(args.next(), "number of levels");
+    let transactions_per_block: Option<i32> =
+        parse_argument(args.next(), "number of transactions per block");
+    let seed: Option<u64> = parse_argument(args.next(), "seed");
+
+    let connection = &mut match template {
+        Some(template) => {
+            let template = &mut Store::open(StoreConfig::new(&template).into_read_only())
+                .expect("Can't open the template store")
+                .into_connection();
+            create_store_like(&database_url, template).unwrap()
+        }
+        None => create_store(&database_url).unwrap(),
+    };
+
+    println!("Store created:{}", database_url);
+
+    if let Some(levels) = levels {
+        let default_config = FixtureConfig::default();
+        let config = FixtureConfig {
+            levels,
+            transactions_per_block: transactions_per_block
+                .unwrap_or(default_config.transactions_per_block),
+            seed: seed.unwrap_or(default_config.seed),
+            ..default_config
+        };
+        let report = generate(connection, &config).unwrap();
+        println!("Fixture generated:{:?}", report);
+    }
+}
@@ -0,0 +1,22 @@
+use evmnodetooling::dieselsqlite::{
+    ethrpc, parse_argument,
+    pool::{PoolConfig, build_pool},
+    server::{self, Pools},
+    store::StoreConfig,
+};
+use std::env;
+
+const DEFAULT_PORT: u16 = 8545;
+
+//Usage: ethrpc [port]
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    let port = parse_argument(env::args().nth(1), "port").unwrap_or(DEFAULT_PORT);
+    let store = StoreConfig::from_env()
+        .expect("Provide a valid store configuration")
+        .into_read_only();
+    let pool_config = PoolConfig::from_env().expect("Provide a valid pool size");
+    let pools = Pools::new(build_pool(&store, &pool_config).expect("Can't open the store"));
+
+    server::run(ethrpc::methods(), pools, ("127.0.0.1", port)).await
+}
@@ -0,0 +1,28 @@
+use std::env;
+
+use evmnodetooling::dieselsqlite::{
+    establish_read_only_connection, fsck::check, models::Block, parse_argument, types::L2Level,
+};
+
+//Usage: fsck [--json] [lower level] [upper level]
+fn main() {
+    let mut args = env::args().skip(1).peekable();
+    let json = args.next_if(|argument| argument == "--json").is_some();
+    let lower_level: Option<L2Level> = parse_argument(args.next(), "lower level");
+    let upper_level: Option<L2Level> = parse_argument(args.next(), "upper level");
+
+    let connection = &mut establish_read_only_connection().unwrap();
+    let lower_level = lower_level.unwrap_or_else(|| Block::base_level(connection).unwrap());
+    let upper_level = upper_level.unwrap_or_else(|| Block::top_level(connection).unwrap());
+
+    let report = check(connection, lower_level, upper_level).unwrap();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    } else {
+        println!("{}", report);
+    }
+    if !report.is_clean() {
+        std::process::exit(1);
+    }
+}
@@ -1,11 +1,15 @@
 use std::time::Instant;
 
-use diesel::{Connection, result::Error};
-use evmnodetooling::dieselsqlite::{models::*, *};
+use evmnodetooling::dieselsqlite::{
+    applyblueprint::*,
+    models::*,
+    types::{BlockHash, TxHash},
+    *,
+};
 
 fn main() {
     let connection = &mut establish_connection().unwrap();
-    let select_index = load_block_number();
+    let select_index = load_block_number().unwrap();
     let clear_index = Blueprint::top_level(connection).unwrap();
     let insert_index = clear_index + 1;
     // println!("Insert_index:{}",insert_index);
@@ -18,9 +22,10 @@ fn main() {
     let block = Block::select_with_level(connection, select_index).unwrap();
     let mut bytes = [0u8; 32];
     rand::fill(&mut bytes);
+    let block_hash = BlockHash(bytes);
     let block = Block {
         level: insert_index,
-        hash: Vec::from(bytes),
+        hash: block_hash,
         block,
     };
     let transactions_receipts =
@@ -30,48 +35,34 @@ fn main() {
 
     let transactions = transactions_receipts
         .into_iter()
-        .zip(transaction_objects.into_iter())
-        .map(
-            |((block_hash, index_, _, from_, to_, receipt_fields), (_, _, _, _, object_fields))| {
-                Transaction {
-                    block_hash,
-                    block_number: insert_index,
-                    index_,
-                    hash: {
-                        rand::fill(&mut bytes);
-                        Vec::from(bytes)
-                    },
-                    from_,
-                    to_,
-                    receipt_fields,
-                    object_fields,
-                }
+        .zip(transaction_objects)
+        .map(|(receipt, object)| Transaction {
+            block_hash,
+            block_number: insert_index,
+            hash: {
+                rand::fill(&mut bytes);
+                TxHash(bytes)
             },
-        )
+            ..Transaction::from_decoded(&receipt, &object)
+        })
         .collect::<Vec<Transaction>>();
-    let context_hash = ContextHash {
+    let context_hash = ContextHashRow {
         id: insert_index,
-        context_hash: ContextHash::select(connection, select_index).unwrap(),
+        context_hash: ContextHashRow::select(connection, select_index).unwrap(),
+    };
+    let input = ApplyBlueprint {
+        blueprint,
+        block,
+        transactions,
+        context_hash,
     };
     let start = Instant::now();
-    connection
-        .transaction::<_, Error, _>(|conn| {
-            let _ = PendingConfirmation::select_with_level(conn, blueprint.id);
-            // println!("blueprint_id:{}",blueprint.id);
-            blueprint.insert(conn)?;
-            block.insert(conn)?;
-            Transaction::batch_insert(conn, &transactions)?;
-            context_hash.insert(conn)?;
-            let _history_mode = Metadata::get_history_mode(conn)?;
-
-            Ok(())
-        })
-        .unwrap();
+    apply_blueprint(connection, input).unwrap();
     let elapsed = start.elapsed();
 
     let _ = Blueprint::clear_after(connection, clear_index).unwrap();
     let _ = Block::clear_after(connection, clear_index).unwrap();
-    let _ = ContextHash::clear_after(connection, clear_index).unwrap();
+    let _ = ContextHashRow::clear_after(connection, clear_index).unwrap();
     let _ = Transaction::clear_after(connection, clear_index).unwrap();
     println!("Single iteration apply_blueprint:{:?}", elapsed);
 }
@@ -1,7 +1,10 @@
 use diesel::{ExpressionMethods, debug_query, dsl::*, sqlite::Sqlite};
 use evmnodetooling::dieselsqlite::{
-    establish_connection, models::*, schema::kernel_upgrades::dsl::*,
+    establish_connection,
+    models::*,
+    schema::kernel_upgrades::dsl::*,
     schema::transactions::dsl::transactions,
+    types::{Address, BlockHash, Bytes, L2Level, RootHash, TxHash},
 };
 
 fn main() {
@@ -52,8 +55,8 @@ fn main() {
 
     let block = Block {
         level: top_level + 1,
-        hash: "Random hash".as_bytes().to_vec(),
-        block: block,
+        hash: BlockHash([1u8; 32]),
+        block,
     };
 
     let _ = block.insert(connection).unwrap();
@@ -74,42 +77,31 @@ fn main() {
 
     println!("Transaction objects top level block:{:?}", objects);
 
-    let (vec_block_hash, vec_index_, vec_hash, vec_from_, vec_to_, vec_receipt_fields) =
-        (&receipts[0]).clone();
-    let (_, _, _, _, vec_object_fields) = (&objects[0]).clone();
+    let receipt = Transaction::select_receipt(connection, &receipts[0].hash).unwrap();
+    let object = Transaction::select_object(connection, &receipts[0].hash).unwrap();
 
-    let (block_hash, block_number, index_, hash, from_, to_, receipt_fields) =
-        Transaction::select_receipt(connection, &vec_hash).unwrap();
-    let (_, _, _, _, _, _, object_fields) =
-        Transaction::select_object(connection, &vec_hash).unwrap();
-
-    assert_eq!(block_hash, vec_block_hash);
-    assert_eq!(block_number, top_level);
-    assert_eq!(index_, vec_index_);
-    assert_eq!(hash, vec_hash);
-    assert_eq!(from_, vec_from_);
-    assert_eq!(to_, vec_to_);
-    assert_eq!(receipt_fields, vec_receipt_fields);
-    assert_eq!(object_fields, vec_object_fields);
+    assert_eq!(receipt, receipts[0]);
+    assert_eq!(receipt.block_number, top_level);
+    assert_eq!(object, objects[0]);
 
     let kernel_upgrade = KernelUpgrade {
-        injected_before: 1000,
-        root_hash: "hash".as_bytes().to_vec(),
+        injected_before: L2Level(1000),
+        root_hash: RootHash([1u8; 33]),
         activation_timestamp: 2000,
         applied_before: None,
     };
 
     let binding1 = replace_into(kernel_upgrades).values((
         injected_before.eq(kernel_upgrade.injected_before),
-        root_hash.eq(kernel_upgrade.root_hash.clone()),
+        root_hash.eq(kernel_upgrade.root_hash),
         activation_timestamp.eq(kernel_upgrade.activation_timestamp),
     ));
 
     let binding2 = update(diesel::QueryDsl::filter(
         kernel_upgrades,
-        applied_before.gt(1000),
+        applied_before.gt(L2Level(1000)),
     ))
-    .set(applied_before.eq::<Option<i32>>(None));
+    .set(applied_before.eq::<Option<L2Level>>(None));
 
     let sql = debug_query::<Sqlite, _>(&binding1);
     println!("SQL:{:?}", sql);
@@ -117,32 +109,32 @@ fn main() {
     println!("SQL:{:?}", sql);
 
     let _ = kernel_upgrade.insert(connection).unwrap();
-    let _ = KernelUpgrade::record_apply(connection, 1004);
-    let _ = KernelUpgrade::nullify_after(connection, 1003).unwrap();
+    let _ = KernelUpgrade::record_apply(connection, L2Level(1004));
+    let _ = KernelUpgrade::nullify_after(connection, L2Level(1003)).unwrap();
     let latest_unapplied = KernelUpgrade::get_latest_unapplied(connection).unwrap();
     println!("Latest unapplied:{:?}", latest_unapplied);
-    let _ = KernelUpgrade::clear_after(connection, 999);
+    let _ = KernelUpgrade::clear_after(connection, L2Level(999));
 
     let iter = 5;
-    let inserted_block_hash: Vec<u8> = "block_hash".as_bytes().to_vec();
+    let inserted_block_hash = BlockHash([1u8; 32]);
     let inserted_block_number = Block::top_level(connection).unwrap() + 1;
     let inserted_index_ = 0;
-    let inserted_from_ = "from_".as_bytes().to_vec();
-    let inserted_to_ = Some("to_".as_bytes().to_vec());
-    let inserted_receipt_fields = "receipt_fields".as_bytes().to_vec();
-    let inserted_object_fields = "object_fields".as_bytes().to_vec();
+    let inserted_from_ = Address([1u8; 20]);
+    let inserted_to_ = Some(Address([2u8; 20]));
+    let inserted_receipt_fields = Bytes::from("receipt_fields".as_bytes());
+    let inserted_object_fields = Bytes::from("object_fields".as_bytes());
 
     let mut batch = Vec::new();
     for i in 0..iter {
-        let inserted_hash: Vec<u8> = format!("transactionHash:{i}").as_bytes().to_vec();
+        let inserted_hash = TxHash([i as u8; 32]);
 
         let transaction = Transaction {
-            block_hash: inserted_block_hash.clone(),
+            block_hash: inserted_block_hash,
             block_number: inserted_block_number,
             index_: inserted_index_,
-            hash: inserted_hash.clone(),
-            from_: inserted_from_.clone(),
-            to_: inserted_to_.clone(),
+            hash: inserted_hash,
+            from_: inserted_from_,
+            to_: inserted_to_,
             receipt_fields: inserted_receipt_fields.clone(),
             object_fields: inserted_object_fields.clone(),
         };
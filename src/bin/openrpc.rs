@@ -0,0 +1,8 @@
+use evmnodetooling::dieselsqlite::server::store_methods;
+
+//Usage: openrpc
+fn main() {
+    let document = store_methods().discover();
+
+    println!("{}", serde_json::to_string_pretty(&document).unwrap());
+}
@@ -0,0 +1,31 @@
+use std::env;
+
+use evmnodetooling::dieselsqlite::{
+    establish_read_only_connection, parse_argument,
+    topicindex::{TOPIC_INDEX_URL_KEY, create_topic_index, update_topic_index},
+    types::L2Level,
+};
+
+//Usage: topicindex [index path] [upper level]
+fn main() {
+    dotenvy::dotenv().ok();
+    let mut args = env::args().skip(1);
+    let index_url = args
+        .next()
+        .or_else(|| env::var(TOPIC_INDEX_URL_KEY).ok())
+        .expect("Provide the path of the topic index");
+    let upper_level: Option<L2Level> = parse_argument(args.next(), "upper level");
+
+    let store = &mut establish_read_only_connection().unwrap();
+    let index = &mut create_topic_index(&index_url).unwrap();
+
+    let update = update_topic_index(index, store, upper_level).unwrap();
+
+    if update.rebuilt {
+        println!("The index didn't match the store anymore, it was rebuilt");
+    }
+    match update.indexed_level {
+        Some(level) => println!("Indexed {} logs, up to level {}", update.indexed, level),
+        None => println!("Nothing to index"),
+    }
+}
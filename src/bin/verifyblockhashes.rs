@@ -0,0 +1,34 @@
+use std::env;
+
+use evmnodetooling::dieselsqlite::{
+    establish_read_only_connection, models::Block, parse_argument, types::L2Level, verify::*,
+};
+
+//Usage: verifyblockhashes [--all] [lower level] [upper level]
+fn main() {
+    let mut args = env::args().skip(1).peekable();
+    let mode = match args.next_if(|argument| argument == "--all") {
+        Some(_) => VerifyMode::ReportAll,
+        None => VerifyMode::StopAtFirst,
+    };
+    let lower_level: Option<L2Level> = parse_argument(args.next(), "lower level");
+    let upper_level: Option<L2Level> = parse_argument(args.next(), "upper level");
+
+    let connection = &mut establish_read_only_connection().unwrap();
+    let lower_level = lower_level.unwrap_or_else(|| Block::base_level(connection).unwrap());
+    let upper_level = upper_level.unwrap_or_else(|| Block::top_level(connection).unwrap());
+
+    let report = verify_block_hashes(connection, lower_level, upper_level, mode).unwrap();
+
+    for issue in &report.issues {
+        println!("{}", issue);
+    }
+    println!(
+        "Verified:{}, issues:{}",
+        report.verified,
+        report.issues.len()
+    );
+    if !report.is_valid() {
+        std::process::exit(1);
+    }
+}
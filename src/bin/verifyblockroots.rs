@@ -0,0 +1,52 @@
+use std::env;
+
+use evmnodetooling::dieselsqlite::{
+    establish_read_only_connection, models::Block, parse_argument, types::L2Level, verify::*,
+};
+
+//Usage: verifyblockroots [--all] [lower level] [upper level]
+fn main() {
+    let mut args = env::args().skip(1).peekable();
+    let mode = match args.next_if(|argument| argument == "--all") {
+        Some(_) => VerifyMode::ReportAll,
+        None => VerifyMode::StopAtFirst,
+    };
+    let lower_level: Option<L2Level> = parse_argument(args.next(), "lower level");
+    let upper_level: Option<L2Level> = parse_argument(args.next(), "upper level");
+
+    let connection = &mut establish_read_only_connection().unwrap();
+    let lower_level = lower_level.unwrap_or_else(|| Block::base_level(connection).unwrap());
+    let upper_level = upper_level.unwrap_or_else(|| Block::top_level(connection).unwrap());
+
+    let report = verify_block_roots(connection, lower_level, upper_level, mode).unwrap();
+
+    for issue in &report.issues {
+        println!("{}", issue);
+    }
+    for level in &report.unchecked_transactions_roots {
+        println!("Level {}:transactions root unchecked", level);
+    }
+    println!(
+        "Verified:{}, unchecked transactions roots:{}, issues:{}",
+        report.verified,
+        report.unchecked_transactions_roots.len(),
+        report.issues.len()
+    );
+    if !report.unchecked_transactions_roots.is_empty() {
+        println!(
+            "The node doesn't store the chain id, fee caps and access list of typed transactions: \
+             the transactions root of the blocks holding some can't be rebuilt, only their \
+             receipts root and logs bloom are checked."
+        );
+    }
+    if report.roots_never_matched() {
+        println!(
+            "No block matched its transactions and receipts roots, rebuilt as Ethereum's \
+             Merkle Patricia tries: the node may not build its roots this way, so these \
+             mismatches don't show the store is corrupted by themselves."
+        );
+    }
+    if !report.is_valid() {
+        std::process::exit(1);
+    }
+}
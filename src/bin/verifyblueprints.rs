@@ -0,0 +1,39 @@
+use std::env;
+
+use evmnodetooling::dieselsqlite::{
+    establish_read_only_connection,
+    models::Blueprint,
+    parse_argument,
+    types::{Bytes, L2Level},
+    verify::*,
+};
+
+//Usage: verifyblueprints [lower level] [upper level] [initial sequencer key]
+fn main() {
+    let mut args = env::args().skip(1);
+    let lower_level: Option<L2Level> = parse_argument(args.next(), "lower level");
+    let upper_level: Option<L2Level> = parse_argument(args.next(), "upper level");
+    let initial_sequencer: Option<Bytes> = parse_argument(args.next(), "sequencer key");
+    let initial_sequencer = initial_sequencer
+        .map(|key| SequencerKey::decode(key.as_bytes()).expect("Provide a valid sequencer key"));
+
+    let connection = &mut establish_read_only_connection().unwrap();
+    let lower_level = lower_level.unwrap_or_else(|| Blueprint::base_level(connection).unwrap());
+    let upper_level = upper_level.unwrap_or_else(|| Blueprint::top_level(connection).unwrap());
+
+    let report =
+        verify_blueprints(connection, initial_sequencer, lower_level, upper_level).unwrap();
+
+    for invalid in &report.invalid {
+        println!("Level {}:{}", invalid.level, invalid.issue);
+    }
+    println!(
+        "Verified:{}, unchecked:{}, invalid:{}",
+        report.verified,
+        report.unchecked,
+        report.invalid.len()
+    );
+    if !report.is_valid() {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,303 @@
+use crate::dieselsqlite::{
+    bootstrap::BootstrapError,
+    error::StoreResult,
+    models::{Transaction, TransactionAccountsRow},
+    sidecar::{INSERT_CHUNK_SIZE, IndexUpdate, SidecarIndex, create_index, update_index},
+    types::{Address, L2Level, TxHash},
+};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Environment variable giving the path of the account index served by the server.
+pub const ACCOUNT_INDEX_URL_KEY: &str = "ACCOUNT_INDEX_URL";
+
+/// Table of the account index. The index lives in its own database so the
+/// node's store is only ever read.
+pub const ACCOUNT_INDEX_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS account_transactions (
+        address BLOB NOT NULL,
+        block_number INTEGER NOT NULL,
+        index_ INTEGER NOT NULL,
+        hash BLOB NOT NULL,
+        sent INTEGER NOT NULL,
+        received INTEGER NOT NULL,
+        PRIMARY KEY (address, block_number, index_)
+    ) WITHOUT ROWID;";
+
+diesel::table! {
+    account_transactions (address, block_number, index_) {
+        address -> Binary,
+        block_number -> Integer,
+        index_ -> Integer,
+        hash -> Binary,
+        sent -> Bool,
+        received -> Bool,
+    }
+}
+
+crate::rpc_object! {
+    /// Transaction sent or received by an account, one row per account and
+    /// transaction.
+    #[derive(Debug, Clone, PartialEq, Queryable, Selectable, Insertable, Serialize)]
+    #[diesel(table_name = account_transactions)]
+    #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+    pub struct AccountTransaction {
+        pub address: Address,
+        pub block_number: L2Level,
+        pub index_: i32,
+        pub hash: TxHash,
+        pub sent: bool,
+        pub received: bool,
+    }
+}
+
+/// Transactions of an account to select.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountDirection {
+    Sent,
+    Received,
+    Any,
+}
+
+impl AccountTransaction {
+    /// Rows of the sender and recipient of a transaction, a single one when
+    /// the transaction is sent to its sender. The contract created by a
+    /// transaction without recipient is indexed as its recipient.
+    fn from_row(row: TransactionAccountsRow) -> Vec<AccountTransaction> {
+        let (block_number, index_, hash, from, to, contract_address) = row;
+        let row = |address, sent, received| AccountTransaction {
+            address,
+            block_number,
+            index_,
+            hash,
+            sent,
+            received,
+        };
+        match to.or(contract_address) {
+            Some(to) if to == from => vec![row(from, true, true)],
+            Some(to) => vec![row(from, true, false), row(to, false, true)],
+            None => vec![row(from, true, false)],
+        }
+    }
+
+    /// Page of the transactions of `address` between the levels, in block
+    /// order, skipping the first `offset` ones.
+    pub fn select(
+        connection: &mut SqliteConnection,
+        address: &Address,
+        direction: AccountDirection,
+        lowerlevel: L2Level,
+        upperlevel: L2Level,
+        offset: i64,
+        limit: i64,
+    ) -> StoreResult<Vec<AccountTransaction>> {
+        use self::account_transactions::dsl;
+
+        let mut query = dsl::account_transactions
+            .filter(dsl::address.eq(address))
+            .filter(dsl::block_number.between(lowerlevel, upperlevel))
+            .into_boxed();
+        query = match direction {
+            AccountDirection::Sent => query.filter(dsl::sent.eq(true)),
+            AccountDirection::Received => query.filter(dsl::received.eq(true)),
+            AccountDirection::Any => query,
+        };
+        let rows = query
+            .order((dsl::block_number.asc(), dsl::index_.asc()))
+            .offset(offset)
+            .limit(limit)
+            .select(AccountTransaction::as_select())
+            .load(connection)?;
+        Ok(rows)
+    }
+}
+
+/// The account index, see `SidecarIndex`.
+pub struct AccountIndex;
+
+impl SidecarIndex for AccountIndex {
+    type Row = AccountTransaction;
+
+    fn load(
+        store: &mut SqliteConnection,
+        lowerlevel: L2Level,
+        upperlevel: L2Level,
+    ) -> StoreResult<(Vec<AccountTransaction>, usize)> {
+        let transactions = Transaction::select_accounts_range(store, lowerlevel, upperlevel)?;
+        let transaction_count = transactions.len();
+        let rows = transactions
+            .into_iter()
+            .flat_map(AccountTransaction::from_row)
+            .collect::<Vec<AccountTransaction>>();
+        Ok((rows, transaction_count))
+    }
+
+    fn insert(index: &mut SqliteConnection, rows: &[AccountTransaction]) -> StoreResult<()> {
+        for chunk in rows.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(account_transactions::table)
+                .values(chunk)
+                .execute(index)?;
+        }
+        Ok(())
+    }
+
+    fn clear(index: &mut SqliteConnection) -> StoreResult<()> {
+        diesel::delete(account_transactions::table).execute(index)?;
+        Ok(())
+    }
+}
+
+/// Opens (creating it if needed) the account index at `database_url`.
+pub fn create_account_index(database_url: &str) -> Result<SqliteConnection, BootstrapError> {
+    create_index(database_url, ACCOUNT_INDEX_SCHEMA)
+}
+
+/// Indexes the transactions of `store`, see `update_index`.
+pub fn update_account_index(
+    index: &mut SqliteConnection,
+    store: &mut SqliteConnection,
+    upper_level: Option<L2Level>,
+) -> StoreResult<IndexUpdate> {
+    update_index::<AccountIndex>(index, store, upper_level)
+}
+
+#[cfg(test)]
+mod account_index_test {
+    use super::*;
+    use crate::dieselsqlite::{fixture::fixture_store, schema::transactions};
+
+    #[test]
+    fn test_account_index() {
+        let (mut store, config) = fixture_store(20, 3);
+        let store = &mut store;
+        let start_level = config.start_level;
+        let top_level = config.top_level();
+        let account = Address([7u8; 20]);
+        diesel::update(
+            transactions::table
+                .filter(transactions::block_number.between(start_level + 2, start_level + 11))
+                .filter(transactions::index_.eq(0)),
+        )
+        .set(transactions::from_.eq(account))
+        .execute(store)
+        .unwrap();
+        diesel::update(
+            transactions::table.filter(
+                transactions::block_number
+                    .eq(start_level + 5)
+                    .and(transactions::index_.eq(0))
+                    .or(transactions::block_number
+                        .eq(start_level + 15)
+                        .and(transactions::index_.eq(1))),
+            ),
+        )
+        .set(transactions::to_.eq(account))
+        .execute(store)
+        .unwrap();
+        let contract = Address([8u8; 20]);
+        let mut creation = Transaction::select_receipts_from_block_number(store, start_level + 3)
+            .unwrap()[2]
+            .clone();
+        creation.fields.contract_address = Some(contract);
+        diesel::update(transactions::table.filter(transactions::hash.eq(creation.hash)))
+            .set((
+                transactions::to_.eq(None::<Address>),
+                transactions::receipt_fields.eq(creation.fields.encode()),
+            ))
+            .execute(store)
+            .unwrap();
+
+        let index = &mut create_account_index(":memory:").unwrap();
+        let update = update_account_index(index, store, Some(start_level + 9)).unwrap();
+
+        assert_eq!(
+            update,
+            IndexUpdate {
+                rebuilt: false,
+                indexed: 30,
+                indexed_level: Some(start_level + 9),
+            }
+        );
+
+        let update = update_account_index(index, store, None).unwrap();
+
+        assert_eq!(update.indexed, 30);
+        assert_eq!(update.indexed_level, Some(top_level));
+
+        let select = |index: &mut SqliteConnection, direction, lowerlevel, offset, limit| {
+            AccountTransaction::select(
+                index, &account, direction, lowerlevel, top_level, offset, limit,
+            )
+            .unwrap()
+            .into_iter()
+            .map(|row| (row.block_number, row.sent, row.received))
+            .collect::<Vec<(L2Level, bool, bool)>>()
+        };
+
+        assert_eq!(
+            select(index, AccountDirection::Any, start_level, 0, 100).len(),
+            11
+        );
+        assert_eq!(
+            select(index, AccountDirection::Any, start_level, 4, 3),
+            vec![
+                (start_level + 6, true, false),
+                (start_level + 7, true, false),
+                (start_level + 8, true, false),
+            ]
+        );
+        assert_eq!(
+            select(index, AccountDirection::Sent, start_level, 0, 100).len(),
+            10
+        );
+        assert_eq!(
+            select(index, AccountDirection::Received, start_level, 0, 100),
+            vec![
+                (start_level + 5, true, true),
+                (start_level + 15, false, true)
+            ]
+        );
+        assert_eq!(
+            select(index, AccountDirection::Any, start_level + 12, 0, 100),
+            vec![(start_level + 15, false, true)]
+        );
+        assert_eq!(
+            AccountTransaction::select(
+                index,
+                &contract,
+                AccountDirection::Received,
+                start_level,
+                top_level,
+                0,
+                100,
+            )
+            .unwrap(),
+            vec![AccountTransaction {
+                address: contract,
+                block_number: start_level + 3,
+                index_: 2,
+                hash: creation.hash,
+                sent: false,
+                received: true,
+            }]
+        );
+
+        let receipt = Transaction::select_receipt(
+            store,
+            &AccountTransaction::select(
+                index,
+                &account,
+                AccountDirection::Any,
+                start_level,
+                top_level,
+                0,
+                1,
+            )
+            .unwrap()[0]
+                .hash,
+        )
+        .unwrap();
+
+        assert_eq!(receipt.from, account);
+    }
+}
@@ -0,0 +1,325 @@
+use crate::dieselsqlite::{
+    error::StoreError,
+    models::*,
+    types::{BlockHash, L2Level, TxHash},
+};
+use diesel::{prelude::*, result::Error as dieselError};
+use std::fmt::{self, Display, Formatter};
+
+/// Everything the node writes to the store when it applies the blueprint of a level.
+pub struct ApplyBlueprint {
+    pub blueprint: Blueprint,
+    pub block: Block,
+    pub transactions: Vec<Transaction>,
+    pub context_hash: ContextHashRow,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AppliedBlueprint {
+    pub level: L2Level,
+    pub inserted_transactions: usize,
+    pub pending_confirmation: Option<BlockHash>,
+    pub history_mode: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ApplyBlueprintError {
+    LevelMismatch {
+        table: &'static str,
+        expected: L2Level,
+        found: L2Level,
+    },
+    BlockHashMismatch {
+        transaction_hash: TxHash,
+    },
+    StoreError {
+        error: StoreError,
+    },
+}
+
+impl Display for ApplyBlueprintError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let string = match self {
+            ApplyBlueprintError::LevelMismatch {
+                table,
+                expected,
+                found,
+            } => format!(
+                "Level mismatch in {}:expected {}, found {}",
+                table, expected, found
+            ),
+            ApplyBlueprintError::BlockHashMismatch { transaction_hash } => {
+                format!("Block hash mismatch for transaction:{}", transaction_hash)
+            }
+            ApplyBlueprintError::StoreError { error } => format!("Store error:{}", error),
+        };
+        write!(f, "{}", string)
+    }
+}
+
+impl std::error::Error for ApplyBlueprintError {}
+
+impl From<StoreError> for ApplyBlueprintError {
+    fn from(error: StoreError) -> ApplyBlueprintError {
+        ApplyBlueprintError::StoreError { error }
+    }
+}
+
+impl From<dieselError> for ApplyBlueprintError {
+    fn from(error: dieselError) -> ApplyBlueprintError {
+        ApplyBlueprintError::StoreError {
+            error: error.into(),
+        }
+    }
+}
+
+impl ApplyBlueprint {
+    pub fn level(&self) -> L2Level {
+        self.blueprint.id
+    }
+
+    fn check(&self) -> Result<(), ApplyBlueprintError> {
+        let expected = self.level();
+        let levels = [
+            ("blocks", self.block.level),
+            ("context_hashes", self.context_hash.id),
+        ]
+        .into_iter()
+        .chain(
+            self.transactions
+                .iter()
+                .map(|transaction| ("transactions", transaction.block_number)),
+        );
+        for (table, found) in levels {
+            if found != expected {
+                return Err(ApplyBlueprintError::LevelMismatch {
+                    table,
+                    expected,
+                    found,
+                });
+            }
+        }
+        if let Some(transaction) = self
+            .transactions
+            .iter()
+            .find(|transaction| transaction.block_hash != self.block.hash)
+        {
+            return Err(ApplyBlueprintError::BlockHashMismatch {
+                transaction_hash: transaction.hash,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Applies a blueprint the way the node does, in a single SQL transaction:
+/// pending confirmation lookup, blueprint, block, transactions and context
+/// hash inserts, then the history mode read.
+pub fn apply_blueprint(
+    connection: &mut SqliteConnection,
+    input: ApplyBlueprint,
+) -> Result<AppliedBlueprint, ApplyBlueprintError> {
+    input.check()?;
+
+    let ApplyBlueprint {
+        blueprint,
+        block,
+        transactions,
+        context_hash,
+    } = input;
+    let level = blueprint.id;
+
+    connection.transaction(|conn| {
+        let pending_confirmation = match PendingConfirmation::select_with_level(conn, level) {
+            Ok(hash) => Some(hash),
+            Err(StoreError::NotFound) => None,
+            Err(error) => return Err(error.into()),
+        };
+        blueprint.insert(conn)?;
+        block.insert(conn)?;
+        let inserted_transactions = Transaction::batch_insert(conn, &transactions)?;
+        context_hash.insert(conn)?;
+        let history_mode = Metadata::get_history_mode(conn)?;
+
+        Ok(AppliedBlueprint {
+            level,
+            inserted_transactions,
+            pending_confirmation,
+            history_mode,
+        })
+    })
+}
+
+#[cfg(test)]
+mod apply_blueprint_test {
+    use super::*;
+    use crate::dieselsqlite::{
+        decoder::{ObjectFields, ReceiptFields},
+        establish_connection,
+        types::{self, Address, Bloom, Bytes, H256, Quantity},
+    };
+
+    fn transaction_hash(level: L2Level, index: i32) -> TxHash {
+        let mut hash = [0u8; 32];
+        hash[..4].copy_from_slice(&level.0.to_be_bytes());
+        hash[4..8].copy_from_slice(&index.to_be_bytes());
+        TxHash(hash)
+    }
+
+    fn receipt_fields(index: i32) -> Bytes {
+        ReceiptFields {
+            cumulative_gas_used: Quantity::from(21_000 * (index as u64 + 1)),
+            effective_gas_price: Quantity(1_000_000_000),
+            gas_used: Quantity(21_000),
+            logs: Vec::new(),
+            logs_bloom: Bloom([0u8; 256]),
+            transaction_type: 2,
+            status: 1,
+            contract_address: None,
+        }
+        .encode()
+    }
+
+    fn object_fields(index: i32) -> Bytes {
+        ObjectFields {
+            gas: Quantity(21_000),
+            gas_price: Quantity(1_000_000_000),
+            input: Bytes::default(),
+            nonce: Quantity::from(index as u64),
+            value: Quantity(1),
+            v: Quantity(0),
+            r: H256([4u8; 32]),
+            s: H256([5u8; 32]),
+        }
+        .encode()
+    }
+
+    fn blueprint_input(level: L2Level, block_hash: BlockHash, transactions: i32) -> ApplyBlueprint {
+        ApplyBlueprint {
+            blueprint: Blueprint {
+                id: level,
+                payload: Bytes::from("payload".as_bytes()),
+                timestamp: 1000,
+            },
+            block: Block {
+                level,
+                hash: block_hash,
+                block: Bytes::from("block".as_bytes()),
+            },
+            transactions: (0..transactions)
+                .map(|i| Transaction {
+                    block_hash,
+                    block_number: level,
+                    index_: i,
+                    hash: transaction_hash(level, i),
+                    from_: Address([1u8; 20]),
+                    to_: Some(Address([2u8; 20])),
+                    receipt_fields: receipt_fields(i),
+                    object_fields: object_fields(i),
+                })
+                .collect(),
+            context_hash: ContextHashRow {
+                id: level,
+                context_hash: types::ContextHash([3u8; 32]),
+            },
+        }
+    }
+
+    #[test]
+    fn test_apply_blueprint() {
+        let connection = &mut establish_connection().unwrap();
+
+        connection.test_transaction::<_, ApplyBlueprintError, _>(|conn| {
+            let insert_level = Blueprint::top_level(conn)? + 1;
+            let inserted_hash = BlockHash([1u8; 32]);
+            let iter = 3;
+
+            PendingConfirmation {
+                level: insert_level,
+                hash: inserted_hash,
+            }
+            .insert(conn)?;
+
+            let applied =
+                apply_blueprint(conn, blueprint_input(insert_level, inserted_hash, iter))?;
+
+            assert_eq!(applied.level, insert_level);
+            assert_eq!(applied.inserted_transactions, iter as usize);
+            assert_eq!(applied.pending_confirmation, Some(inserted_hash));
+            assert_eq!(applied.history_mode, Metadata::get_history_mode(conn)?);
+
+            assert_eq!(Blueprint::top_level(conn)?, insert_level);
+            assert_eq!(
+                Block::select_hash_of_number(conn, insert_level)?,
+                inserted_hash
+            );
+            assert_eq!(
+                Transaction::select_receipts_from_block_number(conn, insert_level)?
+                    .iter()
+                    .map(|receipt| receipt.hash)
+                    .collect::<Vec<TxHash>>(),
+                (0..iter)
+                    .map(|i| transaction_hash(insert_level, i))
+                    .collect::<Vec<TxHash>>()
+            );
+            assert_eq!(
+                ContextHashRow::select(conn, insert_level)?,
+                types::ContextHash([3u8; 32])
+            );
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_apply_blueprint_rejects_inconsistent_input() {
+        let connection = &mut establish_connection().unwrap();
+
+        connection.test_transaction::<_, ApplyBlueprintError, _>(|conn| {
+            let insert_level = Blueprint::top_level(conn)? + 1;
+            let inserted_hash = BlockHash([1u8; 32]);
+
+            let mut input = blueprint_input(insert_level, inserted_hash, 1);
+            input.context_hash.id = insert_level + 1;
+
+            let result = apply_blueprint(conn, input);
+
+            assert_eq!(
+                result,
+                Err(ApplyBlueprintError::LevelMismatch {
+                    table: "context_hashes",
+                    expected: insert_level,
+                    found: insert_level + 1,
+                })
+            );
+
+            let mut input = blueprint_input(insert_level, inserted_hash, 1);
+            input.transactions[0].block_hash = BlockHash([2u8; 32]);
+
+            let result = apply_blueprint(conn, input);
+
+            assert_eq!(
+                result,
+                Err(ApplyBlueprintError::BlockHashMismatch {
+                    transaction_hash: transaction_hash(insert_level, 0),
+                })
+            );
+
+            let input = blueprint_input(insert_level, inserted_hash, 1);
+            apply_blueprint(conn, input)?;
+
+            let input = blueprint_input(insert_level, inserted_hash, 1);
+            let result = apply_blueprint(conn, input);
+
+            assert!(matches!(
+                result,
+                Err(ApplyBlueprintError::StoreError {
+                    error: StoreError::ConstraintViolation { .. }
+                })
+            ));
+            assert_eq!(Blueprint::top_level(conn)?, insert_level);
+
+            Ok(())
+        })
+    }
+}
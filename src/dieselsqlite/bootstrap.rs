@@ -0,0 +1,481 @@
+use crate::dieselsqlite::{
+    error::StoreError,
+    models::{BlockStorageMode, Migration, Schema},
+};
+use diesel::{
+    ConnectionError, connection::SimpleConnection, prelude::*, result::Error as dieselError,
+};
+use std::fmt::{self, Display, Formatter};
+
+/// Migrations creating a store, in the order they are applied. Each one is
+/// recorded in the `migrations` table with its position as id.
+///
+/// The resulting tables match the ones the diesel CLI generated from a
+/// node's store (`schema.rs`). The names of the migrations and their SQL
+/// aren't the node's own though: they are a reconstruction, compared with a
+/// real store only by the ignored tests of `tests/test_node_store.rs`. Use
+/// `create_store_like` with a copy of a node's store to get its exact
+/// schema and migrations history.
+pub const MIGRATIONS: &[(&str, &str)] = &[
+    (
+        "000_init",
+        "CREATE TABLE context_hashes (
+            id SERIAL PRIMARY KEY,
+            context_hash VARCHAR(52) NOT NULL
+        );
+        CREATE TABLE kernel_upgrades (
+            injected_before INT NOT NULL UNIQUE,
+            root_hash BLOB NOT NULL,
+            activation_timestamp BIGINT NOT NULL,
+            applied_before INT
+        );
+        CREATE TABLE delayed_transactions (
+            injected_before INT NOT NULL,
+            hash BLOB NOT NULL,
+            payload BLOB NOT NULL
+        );
+        CREATE INDEX delayed_transactions_injected_before ON delayed_transactions (injected_before);",
+    ),
+    (
+        "001_blueprints",
+        "CREATE TABLE blueprints (
+            id SERIAL PRIMARY KEY,
+            payload BLOB NOT NULL,
+            timestamp DATETIME NOT NULL
+        );",
+    ),
+    (
+        "002_l1_l2_levels_relationships",
+        "CREATE TABLE l1_l2_levels_relationships (
+            latest_l2_level INTEGER PRIMARY KEY ON CONFLICT REPLACE,
+            l1_level INTEGER NOT NULL
+        );",
+    ),
+    (
+        "003_metadata",
+        "CREATE TABLE metadata (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );",
+    ),
+    (
+        "004_blocks_and_transactions",
+        "CREATE TABLE blocks (
+            level SERIAL PRIMARY KEY,
+            hash VARCHAR(32) NOT NULL,
+            block BLOB NOT NULL
+        );
+        CREATE INDEX block_hash_index ON blocks (hash);
+        CREATE TABLE transactions (
+            block_hash VARCHAR(32) NOT NULL,
+            block_number SERIAL NOT NULL,
+            index_ SERIAL NOT NULL,
+            hash VARCHAR(32) PRIMARY KEY NOT NULL,
+            from_ VARCHAR(20) NOT NULL,
+            to_ VARCHAR(20),
+            receipt_fields BLOB NOT NULL,
+            object_fields BLOB NOT NULL
+        );
+        CREATE INDEX transaction_block_number ON transactions (block_number);",
+    ),
+    (
+        "005_irmin_chunks",
+        "CREATE TABLE irmin_chunks (
+            level INTEGER NOT NULL,
+            timestamp BIGINT NOT NULL
+        );",
+    ),
+    (
+        "006_sequencer_upgrades",
+        "CREATE TABLE sequencer_upgrades (
+            injected_before INT NOT NULL UNIQUE,
+            sequencer BLOB NOT NULL,
+            pool_address BLOB NOT NULL,
+            activation_timestamp BIGINT NOT NULL,
+            applied_before INT
+        );",
+    ),
+    (
+        "007_pending_confirmations",
+        "CREATE TABLE pending_confirmations (
+            level INTEGER PRIMARY KEY,
+            hash VARCHAR(32) NOT NULL
+        );",
+    ),
+    (
+        "008_l1_l2_finalized_levels",
+        "CREATE TABLE l1_l2_finalized_levels (
+            l1_level INTEGER PRIMARY KEY,
+            start_l2_level INTEGER NOT NULL,
+            end_l2_level INTEGER NOT NULL
+        );",
+    ),
+    (
+        "009_block_storage_mode",
+        "CREATE TABLE block_storage_mode (
+            legacy INTEGER NOT NULL
+        );
+        INSERT INTO block_storage_mode (legacy) VALUES (0);",
+    ),
+];
+
+#[derive(Debug)]
+pub enum BootstrapError {
+    AlreadyInitialized,
+    ConnectionError { error: ConnectionError },
+    StoreError { error: StoreError },
+}
+
+impl Display for BootstrapError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let string = match self {
+            BootstrapError::AlreadyInitialized => "The store is already initialized".to_string(),
+            BootstrapError::ConnectionError { error } => {
+                format!("Error connecting to the database:{}", error)
+            }
+            BootstrapError::StoreError { error } => format!("Store error:{}", error),
+        };
+        write!(f, "{}", string)
+    }
+}
+
+impl std::error::Error for BootstrapError {}
+
+impl From<ConnectionError> for BootstrapError {
+    fn from(error: ConnectionError) -> BootstrapError {
+        BootstrapError::ConnectionError { error }
+    }
+}
+
+impl From<StoreError> for BootstrapError {
+    fn from(error: StoreError) -> BootstrapError {
+        BootstrapError::StoreError { error }
+    }
+}
+
+impl From<dieselError> for BootstrapError {
+    fn from(error: dieselError) -> BootstrapError {
+        BootstrapError::StoreError {
+            error: error.into(),
+        }
+    }
+}
+
+/// Opens (creating it if needed) the database at `database_url` and
+/// initializes the schema of `MIGRATIONS` in it.
+pub fn create_store(database_url: &str) -> Result<SqliteConnection, BootstrapError> {
+    let mut connection = SqliteConnection::establish(database_url)?;
+    initialize_schema(&mut connection)?;
+    Ok(connection)
+}
+
+/// Creates the tables and indexes of `MIGRATIONS` and records them as the
+/// migrations history, on a database that doesn't have a store yet.
+pub fn initialize_schema(connection: &mut SqliteConnection) -> Result<(), BootstrapError> {
+    if Schema::table_exists(connection, "migrations")? {
+        return Err(BootstrapError::AlreadyInitialized);
+    }
+
+    connection.transaction(|conn| {
+        Migration::create_table(conn)?;
+        for (id, (name, sql)) in MIGRATIONS.iter().enumerate() {
+            conn.batch_execute(sql)?;
+            let migration = Migration {
+                id: id as i32,
+                name: Some(name.to_string()),
+            };
+            migration.register_migration(conn)?;
+        }
+        Ok(())
+    })
+}
+
+/// Opens (creating it if needed) the database at `database_url` and creates
+/// in it the tables and indexes of the store `template`, with its migrations
+/// history and block storage mode but none of its other rows. With a copy
+/// of a store written by the node, the new store has the node's exact schema.
+pub fn create_store_like(
+    database_url: &str,
+    template: &mut SqliteConnection,
+) -> Result<SqliteConnection, BootstrapError> {
+    let statements = Schema::create_statements(template)?;
+    let migrations = Migration::select_all(template)?;
+    let legacy = match BlockStorageMode::legacy(template) {
+        Ok(legacy) => Some(legacy),
+        Err(StoreError::NotFound) => None,
+        Err(error) => return Err(error.into()),
+    };
+
+    let mut connection = SqliteConnection::establish(database_url)?;
+    if Schema::table_exists(&mut connection, "migrations")? {
+        return Err(BootstrapError::AlreadyInitialized);
+    }
+    connection.transaction(|conn| {
+        for statement in statements {
+            conn.batch_execute(&statement)?;
+        }
+        for migration in migrations {
+            migration.register_migration(conn)?;
+        }
+        if let Some(legacy) = legacy {
+            BlockStorageMode { legacy }.insert(conn)?;
+        }
+        Ok::<_, BootstrapError>(())
+    })?;
+    Ok(connection)
+}
+
+#[cfg(test)]
+mod bootstrap_test {
+    use super::*;
+    use crate::dieselsqlite::{
+        applyblueprint::{ApplyBlueprint, apply_blueprint},
+        fixture::{TempStoreFile, fixture_store},
+        models::*,
+        types::{self, BlockHash, Bytes, L2Level},
+    };
+
+    #[test]
+    fn test_create_store() {
+        let connection = &mut create_store(":memory:").unwrap();
+
+        let tables = [
+            "blocks",
+            "blueprints",
+            "transactions",
+            "context_hashes",
+            "pending_confirmations",
+            "migrations",
+            "metadata",
+            "kernel_upgrades",
+            "sequencer_upgrades",
+            "delayed_transactions",
+            "l1_l2_levels_relationships",
+            "l1_l2_finalized_levels",
+            "irmin_chunks",
+            "block_storage_mode",
+        ];
+        for table in tables {
+            assert!(Schema::table_exists(connection, table).unwrap(), "{table}");
+        }
+
+        let schema = Schema::get_all(connection).unwrap();
+
+        assert!(schema.iter().any(|sql| sql.contains("block_hash_index")));
+
+        let expected_migration = (MIGRATIONS.len() - 1) as i32;
+
+        assert_eq!(
+            Migration::current_migration(connection).unwrap(),
+            expected_migration
+        );
+        assert_eq!(BlockStorageMode::legacy(connection).unwrap(), 0);
+
+        let result = initialize_schema(connection);
+
+        assert!(matches!(result, Err(BootstrapError::AlreadyInitialized)));
+    }
+
+    #[test]
+    fn test_create_store_like() {
+        let (mut template, _) = fixture_store(3, 1);
+        let template = &mut template;
+        template
+            .batch_execute(
+                "UPDATE migrations SET name = 'node_' || name;
+                CREATE INDEX blocks_block ON blocks (block);",
+            )
+            .unwrap();
+        BlockStorageMode::force_legacy(template).unwrap();
+        let store_file = TempStoreFile::new("bootstrap");
+        let connection = &mut create_store_like(store_file.path(), template).unwrap();
+
+        assert_eq!(
+            Schema::create_statements(connection).unwrap(),
+            Schema::create_statements(template).unwrap()
+        );
+
+        let tables = Schema::table_names(template).unwrap();
+
+        assert_eq!(Schema::table_names(connection).unwrap(), tables);
+        for table in tables {
+            assert_eq!(
+                SchemaColumn::of_table(connection, &table).unwrap(),
+                SchemaColumn::of_table(template, &table).unwrap(),
+                "{table}"
+            );
+        }
+
+        let migrations = |connection: &mut SqliteConnection| {
+            Migration::select_all(connection)
+                .unwrap()
+                .into_iter()
+                .map(|migration| (migration.id, migration.name))
+                .collect::<Vec<(i32, Option<String>)>>()
+        };
+
+        assert_eq!(migrations(connection), migrations(template));
+        assert_eq!(
+            migrations(connection)[0],
+            (0, Some(format!("node_{}", MIGRATIONS[0].0)))
+        );
+        assert_eq!(BlockStorageMode::legacy(connection).unwrap(), 1);
+        assert!(matches!(
+            Block::top_level(connection),
+            Err(StoreError::NotFound)
+        ));
+        assert!(matches!(
+            create_store_like(store_file.path(), template),
+            Err(BootstrapError::AlreadyInitialized)
+        ));
+    }
+
+    /// Diesel type of a column declared with `column_type`, following the
+    /// mapping of `diesel print-schema` for SQLite.
+    fn diesel_type(column_type: &str) -> &'static str {
+        let column_type = column_type.to_uppercase();
+        if column_type.contains("BIGINT") {
+            "BigInt"
+        } else if column_type.contains("INT") {
+            "Integer"
+        } else if column_type.contains("BLOB") {
+            "Binary"
+        } else if column_type.contains("CHAR") || column_type.contains("TEXT") {
+            "Text"
+        } else {
+            "Unknown"
+        }
+    }
+
+    #[test]
+    fn test_create_store_generated_schema() {
+        // Tables of `schema.rs` generated by the diesel CLI from a node's
+        // store: name, type, nullable and primary key of their columns.
+        let generated = [
+            (
+                "block_storage_mode",
+                vec![("legacy", "Integer", false, false)],
+            ),
+            (
+                "delayed_transactions",
+                vec![
+                    ("injected_before", "Integer", false, false),
+                    ("hash", "Binary", false, false),
+                    ("payload", "Binary", false, false),
+                ],
+            ),
+            (
+                "irmin_chunks",
+                vec![
+                    ("level", "Integer", false, false),
+                    ("timestamp", "BigInt", false, false),
+                ],
+            ),
+            (
+                "kernel_upgrades",
+                vec![
+                    ("injected_before", "Integer", false, false),
+                    ("root_hash", "Binary", false, false),
+                    ("activation_timestamp", "BigInt", false, false),
+                    ("applied_before", "Integer", true, false),
+                ],
+            ),
+            (
+                "l1_l2_finalized_levels",
+                vec![
+                    ("l1_level", "Integer", false, true),
+                    ("start_l2_level", "Integer", false, false),
+                    ("end_l2_level", "Integer", false, false),
+                ],
+            ),
+            (
+                "l1_l2_levels_relationships",
+                vec![
+                    ("latest_l2_level", "Integer", false, true),
+                    ("l1_level", "Integer", false, false),
+                ],
+            ),
+            (
+                "metadata",
+                vec![
+                    ("key", "Text", false, true),
+                    ("value", "Text", false, false),
+                ],
+            ),
+            (
+                "sequencer_upgrades",
+                vec![
+                    ("injected_before", "Integer", false, false),
+                    ("sequencer", "Binary", false, false),
+                    ("pool_address", "Binary", false, false),
+                    ("activation_timestamp", "BigInt", false, false),
+                    ("applied_before", "Integer", true, false),
+                ],
+            ),
+        ];
+        let connection = &mut create_store(":memory:").unwrap();
+
+        for (table, expected) in generated {
+            let columns = SchemaColumn::of_table(connection, table)
+                .unwrap()
+                .into_iter()
+                .map(|column| {
+                    let primary_key = column.primary_key > 0;
+                    (
+                        column.name,
+                        diesel_type(&column.column_type),
+                        !column.not_null && !primary_key,
+                        primary_key,
+                    )
+                })
+                .collect::<Vec<_>>();
+            let expected = expected
+                .into_iter()
+                .map(|(name, diesel_type, nullable, primary_key)| {
+                    (name.to_string(), diesel_type, nullable, primary_key)
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(columns, expected, "{table}");
+        }
+    }
+
+    #[test]
+    fn test_create_store_apply_blueprint() {
+        let connection = &mut create_store(":memory:").unwrap();
+
+        Metadata::insert_history_mode(connection, "rolling:1").unwrap();
+
+        let level = L2Level(0);
+        let block_hash = BlockHash([1u8; 32]);
+        let input = ApplyBlueprint {
+            blueprint: Blueprint {
+                id: level,
+                payload: Bytes::from("payload".as_bytes()),
+                timestamp: 0,
+            },
+            block: Block {
+                level,
+                hash: block_hash,
+                block: Bytes::from("block".as_bytes()),
+            },
+            transactions: Vec::new(),
+            context_hash: ContextHashRow {
+                id: level,
+                context_hash: types::ContextHash([2u8; 32]),
+            },
+        };
+
+        apply_blueprint(connection, input).unwrap();
+
+        assert_eq!(Block::top_level(connection).unwrap(), level);
+        assert_eq!(
+            Block::select_number_of_hash(connection, &block_hash).unwrap(),
+            level
+        );
+        assert_eq!(
+            context_hash_of_block_hash(connection, &block_hash).unwrap(),
+            types::ContextHash([2u8; 32])
+        );
+    }
+}
@@ -0,0 +1,294 @@
+use crate::dieselsqlite::error::{StoreError, StoreResult};
+use diesel::{connection::SimpleConnection, prelude::*, sql_types::Text};
+use serde::Deserialize;
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+    time::Duration,
+};
+
+/// Busy timeout used when reading a store the node keeps writing to.
+pub const DEFAULT_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Journal mode of a database, as reported by `PRAGMA journal_mode`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JournalMode {
+    Delete,
+    Truncate,
+    Persist,
+    Memory,
+    Wal,
+    Off,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct InvalidJournalMode {
+    pub journal_mode: String,
+}
+
+impl Display for InvalidJournalMode {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Invalid journal mode:{}", self.journal_mode)
+    }
+}
+
+impl std::error::Error for InvalidJournalMode {}
+
+impl FromStr for JournalMode {
+    type Err = InvalidJournalMode;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "delete" => Ok(JournalMode::Delete),
+            "truncate" => Ok(JournalMode::Truncate),
+            "persist" => Ok(JournalMode::Persist),
+            "memory" => Ok(JournalMode::Memory),
+            "wal" => Ok(JournalMode::Wal),
+            "off" => Ok(JournalMode::Off),
+            _ => Err(InvalidJournalMode {
+                journal_mode: s.to_string(),
+            }),
+        }
+    }
+}
+
+impl Display for JournalMode {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let journal_mode = match self {
+            JournalMode::Delete => "delete",
+            JournalMode::Truncate => "truncate",
+            JournalMode::Persist => "persist",
+            JournalMode::Memory => "memory",
+            JournalMode::Wal => "wal",
+            JournalMode::Off => "off",
+        };
+        write!(f, "{}", journal_mode)
+    }
+}
+
+#[derive(QueryableByName)]
+struct JournalModeRow {
+    #[diesel(sql_type = Text)]
+    journal_mode: String,
+}
+
+/// Reads the journal mode of the database without changing it.
+pub fn journal_mode(connection: &mut SqliteConnection) -> StoreResult<JournalMode> {
+    let row: JournalModeRow = diesel::sql_query("PRAGMA journal_mode").get_result(connection)?;
+    row.journal_mode
+        .parse()
+        .map_err(|error: InvalidJournalMode| StoreError::Decode {
+            message: error.to_string(),
+        })
+}
+
+/// `PRAGMA synchronous` setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Synchronous {
+    Off,
+    Normal,
+    Full,
+    Extra,
+}
+
+impl Display for Synchronous {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let synchronous = match self {
+            Synchronous::Off => "off",
+            Synchronous::Normal => "normal",
+            Synchronous::Full => "full",
+            Synchronous::Extra => "extra",
+        };
+        write!(f, "{}", synchronous)
+    }
+}
+
+/// `PRAGMA temp_store` setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TempStore {
+    Default,
+    File,
+    Memory,
+}
+
+impl Display for TempStore {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let temp_store = match self {
+            TempStore::Default => "default",
+            TempStore::File => "file",
+            TempStore::Memory => "memory",
+        };
+        write!(f, "{}", temp_store)
+    }
+}
+
+/// Options used to open the store.
+///
+/// A read-only connection never writes to the database file nor changes its
+/// journal mode, so it can be used on the store of a running node: in WAL
+/// mode, readers and the node's writer don't block each other, and in the
+/// other journal modes the busy timeout makes the reader wait for the node's
+/// writes to be committed instead of failing. `immutable` disables locking
+/// entirely and must only be used on a copy of the store that nothing writes to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionBuilder {
+    database_url: String,
+    read_only: bool,
+    immutable: bool,
+    busy_timeout: Option<Duration>,
+}
+
+impl ConnectionBuilder {
+    pub fn new(database_url: &str) -> ConnectionBuilder {
+        ConnectionBuilder {
+            database_url: database_url.to_string(),
+            read_only: false,
+            immutable: false,
+            busy_timeout: None,
+        }
+    }
+
+    pub fn read_only(mut self, read_only: bool) -> ConnectionBuilder {
+        self.read_only = read_only;
+        self
+    }
+
+    pub fn immutable(mut self, immutable: bool) -> ConnectionBuilder {
+        self.immutable = immutable;
+        self
+    }
+
+    pub fn busy_timeout(mut self, busy_timeout: Duration) -> ConnectionBuilder {
+        self.busy_timeout = Some(busy_timeout);
+        self
+    }
+
+    pub fn database_url(&self) -> &str {
+        &self.database_url
+    }
+
+    pub fn get_busy_timeout(&self) -> Option<Duration> {
+        self.busy_timeout
+    }
+
+    /// URI given to SQLite, carrying the open flags as query parameters.
+    pub fn uri(&self) -> String {
+        let mut parameters = Vec::new();
+        if self.read_only {
+            parameters.push("mode=ro");
+        }
+        if self.immutable {
+            parameters.push("immutable=1");
+        }
+        if parameters.is_empty() {
+            return self.database_url.clone();
+        }
+        let parameters = parameters.join("&");
+        match self.database_url.strip_prefix("file:") {
+            Some(uri) if uri.contains('?') => format!("file:{}&{}", uri, parameters),
+            Some(uri) => format!("file:{}?{}", uri, parameters),
+            None => {
+                let path = self
+                    .database_url
+                    .replace('%', "%25")
+                    .replace('?', "%3f")
+                    .replace('#', "%23");
+                format!("file:{}?{}", path, parameters)
+            }
+        }
+    }
+
+    pub fn establish(&self) -> ConnectionResult<SqliteConnection> {
+        let mut connection = SqliteConnection::establish(&self.uri())?;
+        if let Some(busy_timeout) = self.busy_timeout {
+            connection
+                .batch_execute(&format!(
+                    "PRAGMA busy_timeout = {};",
+                    busy_timeout.as_millis()
+                ))
+                .map_err(ConnectionError::CouldntSetupConfiguration)?;
+        }
+        Ok(connection)
+    }
+}
+
+#[cfg(test)]
+mod connection_test {
+    use super::*;
+    use crate::dieselsqlite::{
+        bootstrap::create_store,
+        fixture::TempStoreFile,
+        models::{Block, Metadata},
+        types::{BlockHash, Bytes, L2Level},
+    };
+
+    #[test]
+    fn test_connection_builder_uri() {
+        let builder = ConnectionBuilder::new("/data/store.sqlite");
+
+        assert_eq!(builder.uri(), "/data/store.sqlite");
+
+        let read_only = builder.clone().read_only(true);
+
+        assert_eq!(read_only.uri(), "file:/data/store.sqlite?mode=ro");
+
+        let immutable = read_only.immutable(true);
+
+        assert_eq!(
+            immutable.uri(),
+            "file:/data/store.sqlite?mode=ro&immutable=1"
+        );
+
+        let escaped = ConnectionBuilder::new("/data/100%?#.sqlite").read_only(true);
+
+        assert_eq!(escaped.uri(), "file:/data/100%25%3f%23.sqlite?mode=ro");
+
+        let uri = ConnectionBuilder::new("file:/data/store.sqlite?cache=shared").read_only(true);
+
+        assert_eq!(uri.uri(), "file:/data/store.sqlite?cache=shared&mode=ro");
+    }
+
+    #[test]
+    fn test_read_only_connection_alongside_writer() {
+        let store_file = TempStoreFile::new("connection");
+        let path = store_file.path();
+
+        let writer = &mut create_store(path).unwrap();
+        writer.batch_execute("PRAGMA journal_mode = WAL;").unwrap();
+        Metadata::insert_history_mode(writer, "rolling:1").unwrap();
+
+        let reader = &mut ConnectionBuilder::new(path)
+            .read_only(true)
+            .busy_timeout(DEFAULT_BUSY_TIMEOUT)
+            .establish()
+            .unwrap();
+
+        assert_eq!(journal_mode(reader).unwrap(), JournalMode::Wal);
+        assert_eq!(Metadata::get_history_mode(reader).unwrap(), "rolling:1");
+
+        let write = Metadata::insert_history_mode(reader, "archive");
+
+        assert!(write.is_err());
+
+        writer
+            .transaction::<_, StoreError, _>(|conn| {
+                Block {
+                    level: L2Level(0),
+                    hash: BlockHash([0u8; 32]),
+                    block: Bytes::from("block".as_bytes()),
+                }
+                .insert(conn)?;
+
+                assert!(Block::top_level(reader).is_err());
+
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(Block::top_level(reader).unwrap(), L2Level(0));
+        assert_eq!(journal_mode(writer).unwrap(), JournalMode::Wal);
+    }
+}
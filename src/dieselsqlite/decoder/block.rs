@@ -0,0 +1,224 @@
+use crate::dieselsqlite::{
+    decoder::{
+        DecodeError, DecodeResult, append_i64_le, append_u256_le, decode_bytes, decode_fixed,
+        decode_i64_le, decode_list, decode_option, decode_u64_le, field, keccak256,
+    },
+    types::{Address, BlockHash, Bloom, Bytes, H256, TxHash},
+};
+use rlp::RlpStream;
+
+const FIELDS: usize = 15;
+const HEADER_FIELDS: usize = 16;
+
+/// Ethereum block as stored by the node in `blocks.block`.
+///
+/// The blob is the kernel's RLP encoding of its blocks: a list of the number,
+/// hash, parent hash, logs bloom, transactions, state and receipts roots,
+/// miner, extra data, gas limit, list of transaction hashes, gas used,
+/// timestamp, base fee per gas and mix hash. Numbers are little endian (32
+/// bytes for `U256`s, 8 bytes for the timestamp). Fields appended by later
+/// kernels are ignored.
+///
+/// The layout follows the kernel's encoder; no block written by the node is
+/// checked in, so the tests only decode blocks encoded by `encode`.
+/// `tests/test_node_store.rs` decodes and hashes the blocks of a real store.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EthBlock {
+    pub number: u64,
+    pub hash: BlockHash,
+    pub parent_hash: BlockHash,
+    pub logs_bloom: Bloom,
+    pub transactions_root: H256,
+    pub state_root: H256,
+    pub receipts_root: H256,
+    pub miner: Option<Address>,
+    pub extra_data: Bytes,
+    pub gas_limit: u64,
+    pub transactions: Vec<TxHash>,
+    pub gas_used: u64,
+    pub timestamp: i64,
+    pub base_fee_per_gas: u64,
+    pub mix_hash: H256,
+}
+
+impl EthBlock {
+    pub fn decode(bytes: &[u8]) -> DecodeResult<EthBlock> {
+        let rlp = decode_list(bytes)?;
+        let transactions = field(&rlp, 10, "transactions")?
+            .iter()
+            .map(|transaction| {
+                TxHash::try_from(transaction.data()?).map_err(|error| DecodeError::InvalidField {
+                    field: "transactions",
+                    error,
+                })
+            })
+            .collect::<DecodeResult<Vec<TxHash>>>()?;
+
+        Ok(EthBlock {
+            number: decode_u64_le(&rlp, 0, "number")?,
+            hash: decode_fixed(&rlp, 1, "hash")?,
+            parent_hash: decode_fixed(&rlp, 2, "parent_hash")?,
+            logs_bloom: decode_fixed(&rlp, 3, "logs_bloom")?,
+            transactions_root: decode_fixed(&rlp, 4, "transactions_root")?,
+            state_root: decode_fixed(&rlp, 5, "state_root")?,
+            receipts_root: decode_fixed(&rlp, 6, "receipts_root")?,
+            miner: decode_option(&rlp, 7, "miner")?,
+            extra_data: Bytes::from(decode_bytes(&rlp, 8, "extra_data")?),
+            gas_limit: decode_u64_le(&rlp, 9, "gas_limit")?,
+            transactions,
+            gas_used: decode_u64_le(&rlp, 11, "gas_used")?,
+            timestamp: decode_i64_le(&rlp, 12, "timestamp")?,
+            base_fee_per_gas: decode_u64_le(&rlp, 13, "base_fee_per_gas")?,
+            mix_hash: decode_fixed(&rlp, 14, "mix_hash")?,
+        })
+    }
+
+    pub fn encode(&self) -> Bytes {
+        let mut stream = RlpStream::new_list(FIELDS);
+        append_u256_le(&mut stream, self.number);
+        stream.append(&self.hash);
+        stream.append(&self.parent_hash);
+        stream.append(&self.logs_bloom);
+        stream.append(&self.transactions_root);
+        stream.append(&self.state_root);
+        stream.append(&self.receipts_root);
+        match &self.miner {
+            Some(miner) => stream.append(miner),
+            None => stream.append_empty_data(),
+        };
+        stream.append(&self.extra_data);
+        append_u256_le(&mut stream, self.gas_limit);
+        stream.append_list(&self.transactions);
+        append_u256_le(&mut stream, self.gas_used);
+        append_i64_le(&mut stream, self.timestamp);
+        append_u256_le(&mut stream, self.base_fee_per_gas);
+        stream.append(&self.mix_hash);
+        Bytes(stream.out().to_vec())
+    }
+
+    /// Hash of the block: the Keccak hash of its Ethereum (London) header,
+    /// RLP encoded with big endian numbers. Etherlink blocks have no ommers,
+    /// a zero difficulty and nonce, and the zero address as default miner.
+    /// This is the kernel's header as far as the crate knows: a store whose
+    /// blocks never hash to their stored hash more likely has another header
+    /// than corrupted blocks.
+    pub fn compute_hash(&self) -> BlockHash {
+        let mut stream = RlpStream::new_list(HEADER_FIELDS);
+        stream.append(&self.parent_hash);
+        stream.append(&keccak256(&rlp::EMPTY_LIST_RLP).as_slice());
+        stream.append(&self.miner.unwrap_or(Address([0u8; 20])));
+        stream.append(&self.state_root);
+        stream.append(&self.transactions_root);
+        stream.append(&self.receipts_root);
+        stream.append(&self.logs_bloom);
+        stream.append(&0u64);
+        stream.append(&self.number);
+        stream.append(&self.gas_limit);
+        stream.append(&self.gas_used);
+        stream.append(&(self.timestamp as u64));
+        stream.append(&self.extra_data);
+        stream.append(&self.mix_hash);
+        stream.append(&[0u8; 8].as_slice());
+        stream.append(&self.base_fee_per_gas);
+        BlockHash(keccak256(&stream.out()))
+    }
+}
+
+#[cfg(test)]
+mod block_test {
+    use super::*;
+
+    fn block() -> EthBlock {
+        EthBlock {
+            number: 1000,
+            hash: BlockHash([1u8; 32]),
+            parent_hash: BlockHash([2u8; 32]),
+            logs_bloom: Bloom([0u8; 256]),
+            transactions_root: H256([3u8; 32]),
+            state_root: H256([4u8; 32]),
+            receipts_root: H256([5u8; 32]),
+            miner: None,
+            extra_data: Bytes::default(),
+            gas_limit: 30_000_000,
+            transactions: vec![TxHash([6u8; 32]), TxHash([7u8; 32])],
+            gas_used: 42_000,
+            timestamp: 1_700_000_000,
+            base_fee_per_gas: 1_000_000_000,
+            mix_hash: H256([8u8; 32]),
+        }
+    }
+
+    #[test]
+    fn test_eth_block_round_trip() {
+        let block = block();
+
+        assert_eq!(
+            EthBlock::decode(block.encode().as_bytes()),
+            Ok(block.clone())
+        );
+
+        let block = EthBlock {
+            miner: Some(Address([9u8; 20])),
+            transactions: Vec::new(),
+            ..block
+        };
+
+        assert_eq!(EthBlock::decode(block.encode().as_bytes()), Ok(block));
+    }
+
+    #[test]
+    fn test_eth_block_compute_hash() {
+        // The ommers hash of every post-merge Ethereum header.
+        assert_eq!(
+            H256(keccak256(&rlp::EMPTY_LIST_RLP)).to_string(),
+            "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347"
+        );
+
+        let block = block();
+        let hash = block.compute_hash();
+
+        assert_eq!(
+            hash.to_string(),
+            "0x038332f270d8881836e67d9c57637cd259c4d0549f241aefff3d9d3e22e4fdc5"
+        );
+        assert_ne!(
+            EthBlock {
+                gas_used: 21_000,
+                ..block.clone()
+            }
+            .compute_hash(),
+            hash
+        );
+        assert_eq!(
+            EthBlock {
+                hash: BlockHash([0u8; 32]),
+                transactions: Vec::new(),
+                ..block
+            }
+            .compute_hash(),
+            hash
+        );
+    }
+
+    #[test]
+    fn test_eth_block_invalid() {
+        let mut bytes = block().encode().to_vec();
+        bytes.truncate(100);
+
+        assert!(matches!(
+            EthBlock::decode(&bytes),
+            Err(DecodeError::Rlp { .. })
+        ));
+
+        let mut stream = RlpStream::new_list(2);
+        append_u256_le(&mut stream, 1u64);
+        stream.append(&vec![1u8; 31]);
+
+        assert!(matches!(
+            EthBlock::decode(&stream.out()),
+            Err(DecodeError::MissingField {
+                field: "transactions"
+            })
+        ));
+    }
+}
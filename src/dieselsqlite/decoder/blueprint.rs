@@ -0,0 +1,399 @@
+use crate::dieselsqlite::{
+    decoder::{
+        DecodeError, DecodeResult, EthBlock, append_i64_le, append_u16_le, append_u256_le,
+        decode_bytes, decode_fixed, decode_i64_le, decode_list, decode_u16_le, decode_u64_le,
+        field,
+    },
+    types::{BlockHash, Bytes, Signature, TxHash},
+};
+use rlp::RlpStream;
+
+const CHUNK_FIELDS: usize = 4;
+const CONTENT_FIELDS: usize = 4;
+/// Tag of an L1 external message targetting a smart rollup.
+const TARGETTED_MESSAGE_TAG: u8 = 0x00;
+/// Length of the address of the rollup following the tag.
+const ROLLUP_ADDRESS_LENGTH: usize = 20;
+/// Tag of a blueprint chunk among the messages of the kernel's framing protocol.
+const BLUEPRINT_CHUNK_TAG: u8 = 0x03;
+const FRAMING_LENGTH: usize = 1 + ROLLUP_ADDRESS_LENGTH + 1;
+
+/// Chunk in `bytes`, without the framing of the external message carrying it
+/// when there is one. A bare chunk starts with an RLP list prefix, never with
+/// the targetted message tag.
+fn unframe(bytes: &[u8]) -> DecodeResult<&[u8]> {
+    if bytes.first() != Some(&TARGETTED_MESSAGE_TAG) {
+        return Ok(bytes);
+    }
+    match bytes.get(FRAMING_LENGTH - 1) {
+        Some(&BLUEPRINT_CHUNK_TAG) => Ok(&bytes[FRAMING_LENGTH..]),
+        Some(tag) => Err(DecodeError::InvalidChunks {
+            message: format!("external message of tag {} instead of a chunk", tag),
+        }),
+        None => Err(DecodeError::InvalidChunks {
+            message: format!("external message of {} bytes", bytes.len()),
+        }),
+    }
+}
+
+/// Signed chunk of a blueprint, as published by the sequencer.
+///
+/// A chunk is the RLP list of the unsigned chunk and the signature. The
+/// unsigned chunk is the list of the data, the blueprint number (32 bytes
+/// little endian), the chunk count and the chunk index (2 bytes little
+/// endian). The signature is the 64 bytes of a Tezos signature: `r` and `s`
+/// for secp256k1 and P-256 keys, with no recovery id.
+///
+/// The chunk may come framed as the L1 external message that published it:
+/// the targetted message tag (`0x00`), the 20 bytes address of the rollup and
+/// the blueprint chunk tag (`0x03`) before the RLP.
+///
+/// Both forms are taken from the kernel's framing protocol; no chunk published
+/// by a sequencer is checked in, so the tests only parse chunks built by
+/// `encode`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlueprintChunk {
+    pub data: Bytes,
+    pub number: u64,
+    pub chunk_count: u16,
+    pub chunk_index: u16,
+    pub signature: Signature,
+}
+
+impl BlueprintChunk {
+    pub fn decode(bytes: &[u8]) -> DecodeResult<BlueprintChunk> {
+        let rlp = decode_list(unframe(bytes)?)?;
+        let unsigned = field(&rlp, 0, "chunk")?;
+
+        Ok(BlueprintChunk {
+            data: Bytes::from(decode_bytes(&unsigned, 0, "data")?),
+            number: decode_u64_le(&unsigned, 1, "number")?,
+            chunk_count: decode_u16_le(&unsigned, 2, "chunk_count")?,
+            chunk_index: decode_u16_le(&unsigned, 3, "chunk_index")?,
+            signature: decode_fixed(&rlp, 1, "signature")?,
+        })
+    }
+
+    /// Encoding of the unsigned chunk, the message signed by the sequencer.
+    pub fn unsigned(&self) -> Bytes {
+        let mut stream = RlpStream::new_list(CHUNK_FIELDS);
+        stream.append(&self.data);
+        append_u256_le(&mut stream, self.number);
+        append_u16_le(&mut stream, self.chunk_count);
+        append_u16_le(&mut stream, self.chunk_index);
+        Bytes(stream.out().to_vec())
+    }
+
+    pub fn encode(&self) -> Bytes {
+        let mut stream = RlpStream::new_list(2);
+        stream.append_raw(self.unsigned().as_bytes(), 1);
+        stream.append(&self.signature);
+        Bytes(stream.out().to_vec())
+    }
+
+    /// Unsigned chunks of `content` of at most `chunk_size` bytes of data.
+    pub fn split(
+        number: u64,
+        content: &BlueprintContent,
+        chunk_size: usize,
+    ) -> Vec<BlueprintChunk> {
+        let data = content.encode();
+        let chunks = data
+            .as_bytes()
+            .chunks(chunk_size.max(1))
+            .collect::<Vec<&[u8]>>();
+        chunks
+            .iter()
+            .enumerate()
+            .map(|(chunk_index, chunk)| BlueprintChunk {
+                data: Bytes::from(*chunk),
+                number,
+                chunk_count: chunks.len() as u16,
+                chunk_index: chunk_index as u16,
+                signature: Signature([0u8; 64]),
+            })
+            .collect()
+    }
+}
+
+/// Content of a blueprint, split over the data of its chunks.
+///
+/// The concatenated data is the RLP list of the parent hash, the hashes of
+/// the delayed transactions, the raw transactions and the timestamp (8 bytes
+/// little endian).
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlueprintContent {
+    pub parent_hash: BlockHash,
+    pub delayed_transactions: Vec<TxHash>,
+    pub transactions: Vec<Bytes>,
+    pub timestamp: i64,
+}
+
+impl BlueprintContent {
+    pub fn decode(bytes: &[u8]) -> DecodeResult<BlueprintContent> {
+        let rlp = decode_list(bytes)?;
+        let delayed_transactions = field(&rlp, 1, "delayed_transactions")?
+            .iter()
+            .map(|hash| {
+                TxHash::try_from(hash.data()?).map_err(|error| DecodeError::InvalidField {
+                    field: "delayed_transactions",
+                    error,
+                })
+            })
+            .collect::<DecodeResult<Vec<TxHash>>>()?;
+        let transactions = field(&rlp, 2, "transactions")?
+            .iter()
+            .map(|transaction| Ok(Bytes::from(transaction.data()?)))
+            .collect::<DecodeResult<Vec<Bytes>>>()?;
+
+        Ok(BlueprintContent {
+            parent_hash: decode_fixed(&rlp, 0, "parent_hash")?,
+            delayed_transactions,
+            transactions,
+            timestamp: decode_i64_le(&rlp, 3, "timestamp")?,
+        })
+    }
+
+    pub fn encode(&self) -> Bytes {
+        let mut stream = RlpStream::new_list(CONTENT_FIELDS);
+        stream.append(&self.parent_hash);
+        stream.append_list(&self.delayed_transactions);
+        stream.append_list(&self.transactions);
+        append_i64_le(&mut stream, self.timestamp);
+        Bytes(stream.out().to_vec())
+    }
+}
+
+/// Blueprint as stored by the node in `blueprints.payload`: the RLP list of
+/// its chunks, in order. Each chunk is either embedded as an RLP list or
+/// given as the bytes of its external message, framing included.
+///
+/// Which of the two forms the node stores is unknown: both are accepted.
+/// `tests/test_node_store.rs` decodes the blueprints of a real store.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SequencerBlueprint {
+    pub number: u64,
+    pub chunks: Vec<BlueprintChunk>,
+    pub content: BlueprintContent,
+}
+
+impl SequencerBlueprint {
+    pub fn decode(bytes: &[u8]) -> DecodeResult<SequencerBlueprint> {
+        let rlp = decode_list(bytes)?;
+        let chunks = rlp
+            .iter()
+            .map(|chunk| {
+                if chunk.is_list() {
+                    BlueprintChunk::decode(chunk.as_raw())
+                } else {
+                    BlueprintChunk::decode(chunk.data()?)
+                }
+            })
+            .collect::<DecodeResult<Vec<BlueprintChunk>>>()?;
+
+        let number = match chunks.first() {
+            Some(chunk) => chunk.number,
+            None => Err(DecodeError::InvalidChunks {
+                message: String::from("no chunk"),
+            })?,
+        };
+        for (position, chunk) in chunks.iter().enumerate() {
+            if chunk.number != number
+                || usize::from(chunk.chunk_count) != chunks.len()
+                || usize::from(chunk.chunk_index) != position
+            {
+                Err(DecodeError::InvalidChunks {
+                    message: format!(
+                        "chunk {} of {} for blueprint {} at position {} of {}",
+                        chunk.chunk_index,
+                        chunk.chunk_count,
+                        chunk.number,
+                        position,
+                        chunks.len()
+                    ),
+                })?
+            }
+        }
+
+        let data = chunks
+            .iter()
+            .flat_map(|chunk| chunk.data.as_bytes())
+            .copied()
+            .collect::<Vec<u8>>();
+        let content = BlueprintContent::decode(&data)?;
+
+        Ok(SequencerBlueprint {
+            number,
+            chunks,
+            content,
+        })
+    }
+
+    pub fn encode(&self) -> Bytes {
+        let mut stream = RlpStream::new_list(self.chunks.len());
+        for chunk in &self.chunks {
+            stream.append_raw(chunk.encode().as_bytes(), 1);
+        }
+        Bytes(stream.out().to_vec())
+    }
+
+    /// Names of the fields on which the blueprint and the block executed from it disagree.
+    /// The block includes the delayed transactions along with the sequencer's.
+    pub fn mismatches(&self, block: &EthBlock) -> Vec<&'static str> {
+        let mut mismatches = Vec::new();
+        if self.number != block.number {
+            mismatches.push("number");
+        }
+        if self.content.parent_hash != block.parent_hash {
+            mismatches.push("parent_hash");
+        }
+        if self.content.timestamp != block.timestamp {
+            mismatches.push("timestamp");
+        }
+        if self.content.transactions.len() + self.content.delayed_transactions.len()
+            != block.transactions.len()
+        {
+            mismatches.push("transactions");
+        }
+        mismatches
+    }
+}
+
+#[cfg(test)]
+mod blueprint_test {
+    use super::*;
+    use crate::dieselsqlite::types::{Bloom, H256};
+
+    fn content() -> BlueprintContent {
+        BlueprintContent {
+            parent_hash: BlockHash([1u8; 32]),
+            delayed_transactions: vec![TxHash([2u8; 32])],
+            transactions: vec![Bytes(vec![3u8; 120]), Bytes(vec![4u8; 200])],
+            timestamp: 1_700_000_000,
+        }
+    }
+
+    fn blueprint(chunk_size: usize) -> SequencerBlueprint {
+        let content = content();
+        SequencerBlueprint {
+            number: 1000,
+            chunks: BlueprintChunk::split(1000, &content, chunk_size),
+            content,
+        }
+    }
+
+    #[test]
+    fn test_sequencer_blueprint_round_trip() {
+        let blueprint = blueprint(100);
+
+        assert_eq!(blueprint.chunks.len(), 5);
+        assert_eq!(
+            SequencerBlueprint::decode(blueprint.encode().as_bytes()),
+            Ok(blueprint)
+        );
+
+        let blueprint = self::blueprint(4096);
+
+        assert_eq!(blueprint.chunks.len(), 1);
+        assert_eq!(
+            SequencerBlueprint::decode(blueprint.encode().as_bytes()),
+            Ok(blueprint)
+        );
+    }
+
+    /// `chunk` framed as an external message to the rollup `[9u8; 20]`, with `tag`.
+    fn framed(chunk: &BlueprintChunk, tag: u8) -> Vec<u8> {
+        let mut message = vec![TARGETTED_MESSAGE_TAG];
+        message.extend_from_slice(&[9u8; ROLLUP_ADDRESS_LENGTH]);
+        message.push(tag);
+        message.extend_from_slice(chunk.encode().as_bytes());
+        message
+    }
+
+    #[test]
+    fn test_sequencer_blueprint_framed_chunks() {
+        let blueprint = blueprint(100);
+        let chunk = &blueprint.chunks[0];
+
+        assert_eq!(
+            BlueprintChunk::decode(&framed(chunk, BLUEPRINT_CHUNK_TAG)),
+            Ok(chunk.clone())
+        );
+
+        let mut stream = RlpStream::new_list(blueprint.chunks.len());
+        for chunk in &blueprint.chunks {
+            stream.append(&framed(chunk, BLUEPRINT_CHUNK_TAG));
+        }
+
+        assert_eq!(
+            SequencerBlueprint::decode(&stream.out()),
+            Ok(blueprint.clone())
+        );
+        assert!(matches!(
+            BlueprintChunk::decode(&framed(chunk, 0x01)),
+            Err(DecodeError::InvalidChunks { .. })
+        ));
+        assert!(matches!(
+            BlueprintChunk::decode(&framed(chunk, BLUEPRINT_CHUNK_TAG)[..10]),
+            Err(DecodeError::InvalidChunks { .. })
+        ));
+    }
+
+    #[test]
+    fn test_sequencer_blueprint_invalid_chunks() {
+        let mut blueprint = blueprint(100);
+        blueprint.chunks.swap(1, 2);
+
+        assert!(matches!(
+            SequencerBlueprint::decode(blueprint.encode().as_bytes()),
+            Err(DecodeError::InvalidChunks { .. })
+        ));
+
+        blueprint.chunks.clear();
+
+        assert!(matches!(
+            SequencerBlueprint::decode(blueprint.encode().as_bytes()),
+            Err(DecodeError::InvalidChunks { .. })
+        ));
+
+        assert!(matches!(
+            SequencerBlueprint::decode("payload".as_bytes()),
+            Err(DecodeError::Rlp { .. })
+        ));
+    }
+
+    #[test]
+    fn test_sequencer_blueprint_mismatches() {
+        let blueprint = blueprint(100);
+        let block = EthBlock {
+            number: 1000,
+            hash: BlockHash([5u8; 32]),
+            parent_hash: BlockHash([1u8; 32]),
+            logs_bloom: Bloom([0u8; 256]),
+            transactions_root: H256([0u8; 32]),
+            state_root: H256([0u8; 32]),
+            receipts_root: H256([0u8; 32]),
+            miner: None,
+            extra_data: Bytes::default(),
+            gas_limit: 30_000_000,
+            transactions: vec![TxHash([6u8; 32]); 3],
+            gas_used: 63_000,
+            timestamp: 1_700_000_000,
+            base_fee_per_gas: 1_000_000_000,
+            mix_hash: H256([0u8; 32]),
+        };
+
+        assert!(blueprint.mismatches(&block).is_empty());
+
+        let block = EthBlock {
+            parent_hash: BlockHash([7u8; 32]),
+            transactions: Vec::new(),
+            ..block
+        };
+
+        assert_eq!(
+            blueprint.mismatches(&block),
+            vec!["parent_hash", "transactions"]
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use crate::dieselsqlite::{
+    error::StoreError,
+    types::{InvalidBytes, Quantity},
+};
+use rlp::{DecoderError, Rlp, RlpStream};
+use sha3::{Digest, Keccak256};
+use std::fmt::{self, Display, Formatter};
+
+pub mod block;
+pub mod blueprint;
+pub mod transaction;
+pub mod trie;
+
+pub use block::EthBlock;
+pub use blueprint::{BlueprintChunk, BlueprintContent, SequencerBlueprint};
+pub use transaction::{
+    Log, ObjectFields, ReceiptFields, TransactionObject, TransactionReceipt, bloom_contains,
+};
+pub use trie::ordered_trie_root;
+
+/// Errors raised while decoding the node's encodings of the stored blobs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeError {
+    Rlp {
+        error: DecoderError,
+    },
+    MissingField {
+        field: &'static str,
+    },
+    InvalidField {
+        field: &'static str,
+        error: InvalidBytes,
+    },
+    NumberOverflow {
+        field: &'static str,
+    },
+    InvalidChunks {
+        message: String,
+    },
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let string = match self {
+            DecodeError::Rlp { error } => format!("Invalid RLP:{}", error),
+            DecodeError::MissingField { field } => format!("Missing field {}", field),
+            DecodeError::InvalidField { field, error } => {
+                format!("Invalid field {}:{}", field, error)
+            }
+            DecodeError::NumberOverflow { field } => format!("Number overflow in field {}", field),
+            DecodeError::InvalidChunks { message } => format!("Invalid chunks:{}", message),
+        };
+        write!(f, "{}", string)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<DecoderError> for DecodeError {
+    fn from(error: DecoderError) -> DecodeError {
+        DecodeError::Rlp { error }
+    }
+}
+
+impl From<DecodeError> for StoreError {
+    fn from(error: DecodeError) -> StoreError {
+        StoreError::Decode {
+            message: error.to_string(),
+        }
+    }
+}
+
+pub type DecodeResult<T> = Result<T, DecodeError>;
+
+pub(crate) fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    Keccak256::digest(bytes).into()
+}
+
+/// RLP list spanning exactly `bytes`.
+pub(crate) fn decode_list(bytes: &[u8]) -> DecodeResult<Rlp<'_>> {
+    let rlp = Rlp::new(bytes);
+    let total = rlp.payload_info()?.total();
+    if !rlp.is_list() {
+        Err(DecoderError::RlpExpectedToBeList)?
+    } else if total > bytes.len() {
+        Err(DecoderError::RlpIsTooShort)?
+    } else if total < bytes.len() {
+        Err(DecoderError::RlpIsTooBig)?
+    }
+    Ok(rlp)
+}
+
+/// Payload of the `index`-th item of the list `rlp`.
+pub(crate) fn field<'a>(rlp: &Rlp<'a>, index: usize, name: &'static str) -> DecodeResult<Rlp<'a>> {
+    if index >= rlp.item_count()? {
+        return Err(DecodeError::MissingField { field: name });
+    }
+    Ok(rlp.at(index)?)
+}
+
+pub(crate) fn decode_bytes<'a>(
+    rlp: &Rlp<'a>,
+    index: usize,
+    name: &'static str,
+) -> DecodeResult<&'a [u8]> {
+    Ok(field(rlp, index, name)?.data()?)
+}
+
+pub(crate) fn decode_fixed<T>(rlp: &Rlp, index: usize, name: &'static str) -> DecodeResult<T>
+where
+    T: for<'b> TryFrom<&'b [u8], Error = InvalidBytes>,
+{
+    let bytes = decode_bytes(rlp, index, name)?;
+    T::try_from(bytes).map_err(|error| DecodeError::InvalidField { field: name, error })
+}
+
+/// Optional fixed length value, encoded as empty bytes when absent.
+pub(crate) fn decode_option<T>(
+    rlp: &Rlp,
+    index: usize,
+    name: &'static str,
+) -> DecodeResult<Option<T>>
+where
+    T: for<'b> TryFrom<&'b [u8], Error = InvalidBytes>,
+{
+    if decode_bytes(rlp, index, name)?.is_empty() {
+        Ok(None)
+    } else {
+        decode_fixed(rlp, index, name).map(Some)
+    }
+}
+
+/// Little endian number, of any length as long as it fits in a `u128`.
+pub(crate) fn decode_u128_le(rlp: &Rlp, index: usize, name: &'static str) -> DecodeResult<u128> {
+    let bytes = decode_bytes(rlp, index, name)?;
+    le_to_u128(bytes).ok_or(DecodeError::NumberOverflow { field: name })
+}
+
+pub(crate) fn decode_quantity_le(
+    rlp: &Rlp,
+    index: usize,
+    name: &'static str,
+) -> DecodeResult<Quantity> {
+    decode_u128_le(rlp, index, name).map(Quantity)
+}
+
+pub(crate) fn decode_u64_le(rlp: &Rlp, index: usize, name: &'static str) -> DecodeResult<u64> {
+    u64::try_from(decode_u128_le(rlp, index, name)?)
+        .map_err(|_| DecodeError::NumberOverflow { field: name })
+}
+
+pub(crate) fn decode_u8(rlp: &Rlp, index: usize, name: &'static str) -> DecodeResult<u8> {
+    u8::try_from(decode_u128_le(rlp, index, name)?)
+        .map_err(|_| DecodeError::NumberOverflow { field: name })
+}
+
+pub(crate) fn decode_u16_le(rlp: &Rlp, index: usize, name: &'static str) -> DecodeResult<u16> {
+    u16::try_from(decode_u128_le(rlp, index, name)?)
+        .map_err(|_| DecodeError::NumberOverflow { field: name })
+}
+
+pub(crate) fn decode_i64_le(rlp: &Rlp, index: usize, name: &'static str) -> DecodeResult<i64> {
+    let bytes = decode_bytes(rlp, index, name)?;
+    let array: [u8; 8] = bytes.try_into().map_err(|_| DecodeError::InvalidField {
+        field: name,
+        error: InvalidBytes::InvalidLength {
+            type_name: "i64",
+            expected: 8,
+            found: bytes.len(),
+        },
+    })?;
+    Ok(i64::from_le_bytes(array))
+}
+
+fn le_to_u128(bytes: &[u8]) -> Option<u128> {
+    let (low, high) = bytes.split_at(bytes.len().min(16));
+    if high.iter().any(|byte| *byte != 0) {
+        return None;
+    }
+    let mut array = [0u8; 16];
+    array[..low.len()].copy_from_slice(low);
+    Some(u128::from_le_bytes(array))
+}
+
+/// Appends `value` as a 32 bytes little endian number, like the kernel's `U256`s.
+pub(crate) fn append_u256_le(stream: &mut RlpStream, value: impl Into<u128>) {
+    let mut bytes = [0u8; 32];
+    bytes[..16].copy_from_slice(&value.into().to_le_bytes());
+    stream.append(&bytes.as_slice());
+}
+
+pub(crate) fn append_u16_le(stream: &mut RlpStream, value: u16) {
+    stream.append(&value.to_le_bytes().as_slice());
+}
+
+pub(crate) fn append_u8(stream: &mut RlpStream, value: u8) {
+    stream.append(&[value].as_slice());
+}
+
+pub(crate) fn append_i64_le(stream: &mut RlpStream, value: i64) {
+    stream.append(&value.to_le_bytes().as_slice());
+}
+
+#[cfg(test)]
+mod decoder_test {
+    use super::*;
+
+    #[test]
+    fn test_decode_numbers() {
+        let mut stream = RlpStream::new_list(5);
+        append_u256_le(&mut stream, 0x0102u64);
+        append_i64_le(&mut stream, -5);
+        stream.append(&[0u8; 8].iter().chain(&[1u8]).copied().collect::<Vec<u8>>());
+        append_u256_le(&mut stream, u128::MAX);
+        append_u8(&mut stream, 2);
+        let bytes = stream.out();
+        let rlp = Rlp::new(&bytes);
+
+        assert_eq!(decode_u64_le(&rlp, 0, "a"), Ok(0x0102));
+        assert_eq!(decode_i64_le(&rlp, 1, "b"), Ok(-5));
+        assert_eq!(
+            decode_u64_le(&rlp, 2, "c"),
+            Err(DecodeError::NumberOverflow { field: "c" })
+        );
+        assert_eq!(decode_u128_le(&rlp, 2, "c"), Ok(1 << 64));
+        assert_eq!(decode_u128_le(&rlp, 3, "d"), Ok(u128::MAX));
+        assert_eq!(decode_u8(&rlp, 4, "e"), Ok(2));
+        assert_eq!(
+            decode_u8(&rlp, 0, "a"),
+            Err(DecodeError::NumberOverflow { field: "a" })
+        );
+        assert_eq!(
+            decode_u64_le(&rlp, 5, "f"),
+            Err(DecodeError::MissingField { field: "f" })
+        );
+    }
+}
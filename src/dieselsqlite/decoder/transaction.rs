@@ -0,0 +1,519 @@
+use crate::dieselsqlite::{
+    decoder::{
+        DecodeError, DecodeResult, append_u8, append_u256_le, decode_bytes, decode_fixed,
+        decode_list, decode_option, decode_quantity_le, decode_u8, field, keccak256,
+    },
+    types::{Address, BlockHash, Bloom, Bytes, H256, L2Level, Quantity, TxHash},
+};
+use rlp::{Rlp, RlpStream};
+use serde::Serialize;
+
+const RECEIPT_FIELDS: usize = 8;
+const OBJECT_FIELDS: usize = 8;
+const LOG_FIELDS: usize = 3;
+const RECEIPT_ENVELOPE_FIELDS: usize = 4;
+const LEGACY_TRANSACTION_FIELDS: usize = 9;
+const LEGACY_TRANSACTION_TYPE: u8 = 0;
+
+/// Byte and bit of the three bits of a bloom selected by the Keccak hash of `input`.
+fn bloom_bits(input: &[u8]) -> [(usize, u8); 3] {
+    let hash = keccak256(input);
+    [0, 2, 4].map(|start| {
+        let bit = usize::from(u16::from_be_bytes([hash[start], hash[start + 1]]) & 0x07ff);
+        (255 - bit / 8, 1 << (bit % 8))
+    })
+}
+
+fn accrue(bloom: &mut Bloom, input: &[u8]) {
+    for (byte, bit) in bloom_bits(input) {
+        bloom.0[byte] |= bit;
+    }
+}
+
+/// Whether `input` may have been accrued in `bloom`.
+pub fn bloom_contains(bloom: &Bloom, input: &[u8]) -> bool {
+    bloom_bits(input)
+        .iter()
+        .all(|(byte, bit)| bloom.0[*byte] & bit != 0)
+}
+
+/// Big endian number, without its leading zeros.
+fn trim_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes
+        .iter()
+        .position(|byte| *byte != 0)
+        .unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+crate::rpc_object! {
+    /// Log emitted by a transaction: the address, topics and data.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct Log {
+        pub address: Address,
+        pub topics: Vec<H256>,
+        pub data: Bytes,
+    }
+}
+
+impl Log {
+    fn decode(rlp: &Rlp) -> DecodeResult<Log> {
+        let topics = field(rlp, 1, "topics")?
+            .iter()
+            .map(|topic| {
+                H256::try_from(topic.data()?).map_err(|error| DecodeError::InvalidField {
+                    field: "topics",
+                    error,
+                })
+            })
+            .collect::<DecodeResult<Vec<H256>>>()?;
+
+        Ok(Log {
+            address: decode_fixed(rlp, 0, "address")?,
+            topics,
+            data: Bytes::from(decode_bytes(rlp, 2, "data")?),
+        })
+    }
+
+    fn append(&self, stream: &mut RlpStream) {
+        stream.begin_list(LOG_FIELDS);
+        stream.append(&self.address);
+        stream.append_list(&self.topics);
+        stream.append(&self.data);
+    }
+}
+
+crate::rpc_object! {
+    /// Receipt of a transaction as stored by the node in `transactions.receipt_fields`.
+    ///
+    /// The blob is an RLP list of the cumulative gas used, effective gas price,
+    /// gas used, logs, logs bloom, type, status and contract address (empty when
+    /// the transaction doesn't create a contract). Gas amounts and prices are 32
+    /// bytes little endian numbers; the type and status are single bytes.
+    ///
+    /// The layout follows the kernel's encoder and hasn't been checked against
+    /// receipts written by the node, typed (EIP-2930, EIP-1559) receipts
+    /// included: `tests/test_node_store.rs` decodes those of a real store.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct ReceiptFields {
+        pub cumulative_gas_used: Quantity,
+        pub effective_gas_price: Quantity,
+        pub gas_used: Quantity,
+        pub logs: Vec<Log>,
+        pub logs_bloom: Bloom,
+        pub transaction_type: u8,
+        pub status: u8,
+        pub contract_address: Option<Address>,
+    }
+}
+
+impl ReceiptFields {
+    pub fn decode(bytes: &[u8]) -> DecodeResult<ReceiptFields> {
+        let rlp = decode_list(bytes)?;
+        let logs = field(&rlp, 3, "logs")?
+            .iter()
+            .map(|log| Log::decode(&log))
+            .collect::<DecodeResult<Vec<Log>>>()?;
+
+        Ok(ReceiptFields {
+            cumulative_gas_used: decode_quantity_le(&rlp, 0, "cumulative_gas_used")?,
+            effective_gas_price: decode_quantity_le(&rlp, 1, "effective_gas_price")?,
+            gas_used: decode_quantity_le(&rlp, 2, "gas_used")?,
+            logs,
+            logs_bloom: decode_fixed(&rlp, 4, "logs_bloom")?,
+            transaction_type: decode_u8(&rlp, 5, "transaction_type")?,
+            status: decode_u8(&rlp, 6, "status")?,
+            contract_address: decode_option(&rlp, 7, "contract_address")?,
+        })
+    }
+
+    pub fn encode(&self) -> Bytes {
+        let mut stream = RlpStream::new_list(RECEIPT_FIELDS);
+        append_u256_le(&mut stream, self.cumulative_gas_used);
+        append_u256_le(&mut stream, self.effective_gas_price);
+        append_u256_le(&mut stream, self.gas_used);
+        stream.begin_list(self.logs.len());
+        for log in &self.logs {
+            log.append(&mut stream);
+        }
+        stream.append(&self.logs_bloom);
+        append_u8(&mut stream, self.transaction_type);
+        append_u8(&mut stream, self.status);
+        match &self.contract_address {
+            Some(contract_address) => stream.append(contract_address),
+            None => stream.append_empty_data(),
+        };
+        Bytes(stream.out().to_vec())
+    }
+
+    /// Bloom of the addresses and topics of the logs.
+    pub fn compute_logs_bloom(&self) -> Bloom {
+        let mut bloom = Bloom([0u8; 256]);
+        for log in &self.logs {
+            accrue(&mut bloom, log.address.as_bytes());
+            for topic in &log.topics {
+                accrue(&mut bloom, topic.as_bytes());
+            }
+        }
+        bloom
+    }
+
+    /// Ethereum encoding of the receipt, the value of the receipts trie: the
+    /// RLP list of the status, cumulative gas used, logs bloom and logs,
+    /// prefixed with the type for typed transactions.
+    pub fn encode_envelope(&self) -> Bytes {
+        let mut stream = RlpStream::new_list(RECEIPT_ENVELOPE_FIELDS);
+        stream.append(&self.status);
+        stream.append(&self.cumulative_gas_used);
+        stream.append(&self.logs_bloom);
+        stream.begin_list(self.logs.len());
+        for log in &self.logs {
+            log.append(&mut stream);
+        }
+        let mut envelope = Vec::new();
+        if self.transaction_type != LEGACY_TRANSACTION_TYPE {
+            envelope.push(self.transaction_type);
+        }
+        envelope.extend_from_slice(&stream.out());
+        Bytes(envelope)
+    }
+
+    pub fn is_legacy(&self) -> bool {
+        self.transaction_type == LEGACY_TRANSACTION_TYPE
+    }
+}
+
+crate::rpc_object! {
+    /// Transaction object as stored by the node in `transactions.object_fields`.
+    ///
+    /// The blob is an RLP list of the gas limit, gas price, input, nonce, value
+    /// and the `v`, `r` and `s` of the signature. Numbers are 32 bytes little
+    /// endian.
+    ///
+    /// Like the receipt, the layout is the kernel encoder's, untested against
+    /// objects written by the node. It has no access list nor priority fee:
+    /// fields a node would append for typed transactions are ignored.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct ObjectFields {
+        pub gas: Quantity,
+        pub gas_price: Quantity,
+        pub input: Bytes,
+        pub nonce: Quantity,
+        pub value: Quantity,
+        pub v: Quantity,
+        pub r: H256,
+        pub s: H256,
+    }
+}
+
+impl ObjectFields {
+    pub fn decode(bytes: &[u8]) -> DecodeResult<ObjectFields> {
+        let rlp = decode_list(bytes)?;
+
+        Ok(ObjectFields {
+            gas: decode_quantity_le(&rlp, 0, "gas")?,
+            gas_price: decode_quantity_le(&rlp, 1, "gas_price")?,
+            input: Bytes::from(decode_bytes(&rlp, 2, "input")?),
+            nonce: decode_quantity_le(&rlp, 3, "nonce")?,
+            value: decode_quantity_le(&rlp, 4, "value")?,
+            v: decode_quantity_le(&rlp, 5, "v")?,
+            r: decode_fixed(&rlp, 6, "r")?,
+            s: decode_fixed(&rlp, 7, "s")?,
+        })
+    }
+
+    pub fn encode(&self) -> Bytes {
+        let mut stream = RlpStream::new_list(OBJECT_FIELDS);
+        append_u256_le(&mut stream, self.gas);
+        append_u256_le(&mut stream, self.gas_price);
+        stream.append(&self.input);
+        append_u256_le(&mut stream, self.nonce);
+        append_u256_le(&mut stream, self.value);
+        append_u256_le(&mut stream, self.v);
+        stream.append(&self.r);
+        stream.append(&self.s);
+        Bytes(stream.out().to_vec())
+    }
+
+    /// Ethereum encoding of the transaction sent to `to`, the value of the
+    /// transactions trie, when it is a legacy one. Typed transactions can't be
+    /// encoded back: the node doesn't store their chain id, fee caps and
+    /// access list.
+    pub fn encode_legacy(&self, to: Option<Address>) -> Bytes {
+        let mut stream = RlpStream::new_list(LEGACY_TRANSACTION_FIELDS);
+        stream.append(&self.nonce);
+        stream.append(&self.gas_price);
+        stream.append(&self.gas);
+        match &to {
+            Some(to) => stream.append(to),
+            None => stream.append_empty_data(),
+        };
+        stream.append(&self.value);
+        stream.append(&self.input);
+        stream.append(&self.v);
+        stream.append(&trim_leading_zeros(self.r.as_bytes()));
+        stream.append(&trim_leading_zeros(self.s.as_bytes()));
+        Bytes(stream.out().to_vec())
+    }
+}
+
+crate::rpc_object! {
+    /// Receipt of a transaction: the columns of its row and its decoded `receipt_fields`.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct TransactionReceipt {
+        pub block_hash: BlockHash,
+        pub block_number: L2Level,
+        pub transaction_index: i32,
+        pub hash: TxHash,
+        pub from: Address,
+        pub to: Option<Address>,
+        #[serde(flatten)]
+        pub fields: ReceiptFields,
+    }
+}
+
+crate::rpc_object! {
+    /// Transaction object: the columns of its row and its decoded `object_fields`.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct TransactionObject {
+        pub block_hash: BlockHash,
+        pub block_number: L2Level,
+        pub transaction_index: i32,
+        pub hash: TxHash,
+        pub from: Address,
+        pub to: Option<Address>,
+        #[serde(flatten)]
+        pub fields: ObjectFields,
+    }
+}
+
+#[cfg(test)]
+mod transaction_test {
+    use super::*;
+
+    fn receipt_fields() -> ReceiptFields {
+        ReceiptFields {
+            cumulative_gas_used: Quantity(42_000),
+            effective_gas_price: Quantity(1_000_000_000),
+            gas_used: Quantity(21_000),
+            logs: vec![
+                Log {
+                    address: Address([1u8; 20]),
+                    topics: vec![H256([2u8; 32]), H256([3u8; 32])],
+                    data: Bytes::from("data".as_bytes()),
+                },
+                Log {
+                    address: Address([4u8; 20]),
+                    topics: Vec::new(),
+                    data: Bytes::default(),
+                },
+            ],
+            logs_bloom: Bloom([0u8; 256]),
+            transaction_type: 2,
+            status: 1,
+            contract_address: None,
+        }
+    }
+
+    #[test]
+    fn test_receipt_fields_round_trip() {
+        let fields = receipt_fields();
+
+        assert_eq!(
+            ReceiptFields::decode(fields.encode().as_bytes()),
+            Ok(fields.clone())
+        );
+
+        let fields = ReceiptFields {
+            logs: Vec::new(),
+            status: 0,
+            contract_address: Some(Address([5u8; 20])),
+            ..fields
+        };
+
+        assert_eq!(
+            ReceiptFields::decode(fields.encode().as_bytes()),
+            Ok(fields)
+        );
+    }
+
+    #[test]
+    fn test_object_fields_round_trip() {
+        let fields = ObjectFields {
+            gas: Quantity(21_000),
+            gas_price: Quantity(1_000_000_000),
+            input: Bytes::from("input".as_bytes()),
+            nonce: Quantity(7),
+            value: Quantity(10u128.pow(20)),
+            v: Quantity(27),
+            r: H256([1u8; 32]),
+            s: H256([2u8; 32]),
+        };
+
+        assert_eq!(ObjectFields::decode(fields.encode().as_bytes()), Ok(fields));
+    }
+
+    #[test]
+    fn test_object_fields_decode_blob() {
+        // The layout of the node's object_fields column: the numbers are
+        // 32-byte little-endian words, written out by hand.
+        let blob: Bytes = concat!(
+            "0xf8e8",
+            "a00852000000000000000000000000000000000000000000000000000000000000",
+            "a000ca9a3b00000000000000000000000000000000000000000000000000000000",
+            "80",
+            "a00700000000000000000000000000000000000000000000000000000000000000",
+            "a00100000000000000010000000000000000000000000000000000000000000000",
+            "a02500000000000000000000000000000000000000000000000000000000000000",
+            "a00101010101010101010101010101010101010101010101010101010101010101",
+            "a00202020202020202020202020202020202020202020202020202020202020202",
+        )
+        .parse()
+        .unwrap();
+        let fields = ObjectFields::decode(blob.as_bytes()).unwrap();
+
+        assert_eq!(fields.gas, Quantity(21_000));
+        assert_eq!(fields.gas_price, Quantity(1_000_000_000));
+        assert_eq!(fields.input, Bytes::default());
+        assert_eq!(fields.nonce, Quantity(7));
+        assert_eq!(fields.value, Quantity((1u128 << 64) + 1));
+        assert_eq!(fields.v, Quantity(37));
+        assert_eq!(fields.r, H256([1u8; 32]));
+        assert_eq!(fields.s, H256([2u8; 32]));
+        assert_eq!(fields.encode(), blob);
+    }
+
+    #[test]
+    fn test_object_fields_serialize() {
+        let fields = ObjectFields {
+            gas: Quantity(21_000),
+            gas_price: Quantity(1_000_000_000),
+            input: Bytes::default(),
+            nonce: Quantity(0),
+            value: Quantity((1u128 << 64) + 1),
+            v: Quantity(27),
+            r: H256([1u8; 32]),
+            s: H256([2u8; 32]),
+        };
+        let value = serde_json::to_value(&fields).unwrap();
+
+        assert_eq!(value["value"], "0x10000000000000001");
+        assert_eq!(value["gas"], "0x5208");
+        assert_eq!(value["nonce"], "0x0");
+    }
+
+    #[test]
+    fn test_receipt_fields_envelope() {
+        let bloom = receipt_fields().compute_logs_bloom();
+        let fields = ReceiptFields {
+            logs_bloom: bloom,
+            ..receipt_fields()
+        };
+
+        assert_eq!(
+            H256(keccak256(bloom.as_bytes())).to_string(),
+            "0xb95e09cf8ab7dc271ddbebaf3f086c995e98a4671f005779f17b85ecf6035e56"
+        );
+        assert!(bloom_contains(&bloom, Address([4u8; 20]).as_bytes()));
+        assert!(bloom_contains(&bloom, H256([3u8; 32]).as_bytes()));
+        assert!(!bloom_contains(&bloom, H256([4u8; 32]).as_bytes()));
+        assert_eq!(
+            H256(keccak256(fields.encode_envelope().as_bytes())).to_string(),
+            "0x1d3f8991f59986621a523cd4508b4af7d9ded39d62b5ea3d0fc4b845a9614d3b"
+        );
+
+        let fields = ReceiptFields {
+            transaction_type: 0,
+            status: 0,
+            ..fields
+        };
+
+        assert_eq!(
+            H256(keccak256(fields.encode_envelope().as_bytes())).to_string(),
+            "0xd5589ffa1a9d9f11d57066f18594e1dda891467138dc3bc2bee594142f8dbc9e"
+        );
+    }
+
+    #[test]
+    fn test_object_fields_encode_legacy() {
+        let mut r = [1u8; 32];
+        r[0] = 0;
+        let fields = ObjectFields {
+            gas: Quantity(21_000),
+            gas_price: Quantity(1_000_000_000),
+            input: Bytes::from("input".as_bytes()),
+            nonce: Quantity(7),
+            value: Quantity(10u128.pow(20)),
+            v: Quantity(27),
+            r: H256(r),
+            s: H256([2u8; 32]),
+        };
+
+        assert_eq!(
+            TxHash(keccak256(
+                fields.encode_legacy(Some(Address([5u8; 20]))).as_bytes()
+            ))
+            .to_string(),
+            "0x7dfe993063258bf3aec1d94a7af91bb41f9b9dc5d3b1c05cb984a48456c1e62e"
+        );
+        assert_eq!(
+            TxHash(keccak256(fields.encode_legacy(None).as_bytes())).to_string(),
+            "0x6fce2ef5ab33df60de35861c01904bb95edbd7d212d1ff013e37a014f885a1ba"
+        );
+    }
+
+    #[test]
+    fn test_object_fields_encode_legacy_eip155() {
+        // The signed transaction of the example of EIP-155.
+        let fields = ObjectFields {
+            gas: Quantity(21_000),
+            gas_price: Quantity(20_000_000_000),
+            input: Bytes::default(),
+            nonce: Quantity(9),
+            value: Quantity(10u128.pow(18)),
+            v: Quantity(37),
+            r: "0x28ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276"
+                .parse()
+                .unwrap(),
+            s: "0x67cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+                .parse()
+                .unwrap(),
+        };
+        let signed: Bytes = concat!(
+            "0xf86c098504a817c800825208943535353535353535353535353535353535353535",
+            "880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71",
+            "ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc6421",
+            "4b297fb1966a3b6d83",
+        )
+        .parse()
+        .unwrap();
+
+        assert_eq!(fields.encode_legacy(Some(Address([0x35u8; 20]))), signed);
+    }
+
+    #[test]
+    fn test_transaction_fields_invalid() {
+        assert!(matches!(
+            ReceiptFields::decode("receipt_fields".as_bytes()),
+            Err(DecodeError::Rlp { .. })
+        ));
+
+        let mut stream = RlpStream::new_list(4);
+        for _ in 0..3 {
+            append_u256_le(&mut stream, 1u64);
+        }
+        stream.begin_list(1);
+        stream.begin_list(LOG_FIELDS);
+        stream.append(&vec![1u8; 19]);
+        stream.begin_list(0);
+        stream.append_empty_data();
+
+        assert!(matches!(
+            ReceiptFields::decode(&stream.out()),
+            Err(DecodeError::InvalidField {
+                field: "address",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,182 @@
+use crate::dieselsqlite::{
+    decoder::keccak256,
+    types::{Bytes, H256},
+};
+use rlp::RlpStream;
+
+/// Key of an entry of the trie, as nibbles, and its value.
+type Entry<'a> = (Vec<u8>, &'a [u8]);
+
+/// Root of the Merkle Patricia trie mapping the RLP encoding of each index to
+/// the value at that index, like the transactions and receipts tries of
+/// Ethereum blocks.
+pub fn ordered_trie_root(values: &[Bytes]) -> H256 {
+    let entries = values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| (nibbles(&rlp::encode(&index)), value.as_bytes()))
+        .collect::<Vec<Entry>>();
+    trie_root(entries)
+}
+
+/// Root of the Merkle Patricia trie holding `entries`, in any order.
+fn trie_root(mut entries: Vec<Entry>) -> H256 {
+    entries.sort_by(|(key, _), (other_key, _)| key.cmp(other_key));
+
+    if entries.is_empty() {
+        return H256(keccak256(&rlp::NULL_RLP));
+    }
+    H256(keccak256(&node(&entries, 0)))
+}
+
+fn nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect()
+}
+
+/// Compact encoding of a path, flagging its parity and whether it ends in a leaf.
+fn hex_prefix(path: &[u8], leaf: bool) -> Vec<u8> {
+    let flag = if leaf { 2 } else { 0 } + path.len() as u8 % 2;
+    let mut encoded = Vec::with_capacity(path.len() / 2 + 1);
+    let rest = if path.len() % 2 == 1 {
+        encoded.push(flag << 4 | path[0]);
+        &path[1..]
+    } else {
+        encoded.push(flag << 4);
+        path
+    };
+    encoded.extend(rest.chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+    encoded
+}
+
+/// Appends a child node: inlined when its encoding is shorter than a hash.
+fn append_child(stream: &mut RlpStream, node: &[u8]) {
+    if node.len() < 32 {
+        stream.append_raw(node, 1);
+    } else {
+        stream.append(&keccak256(node).as_slice());
+    }
+}
+
+/// RLP encoding of the node holding `entries`, sorted by key, from nibble `depth`.
+fn node(entries: &[Entry], depth: usize) -> Vec<u8> {
+    if let [(key, value)] = entries {
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&hex_prefix(&key[depth..], true));
+        stream.append(value);
+        return stream.out().to_vec();
+    }
+
+    let (first, _) = &entries[0];
+    let (last, _) = &entries[entries.len() - 1];
+    let shared = first[depth..]
+        .iter()
+        .zip(&last[depth..])
+        .take_while(|(nibble, other)| nibble == other)
+        .count();
+    if shared > 0 {
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&hex_prefix(&first[depth..depth + shared], false));
+        append_child(&mut stream, &node(entries, depth + shared));
+        return stream.out().to_vec();
+    }
+
+    let mut stream = RlpStream::new_list(17);
+    let (ended, mut rest) = match entries.iter().position(|(key, _)| key.len() > depth) {
+        Some(position) => entries.split_at(position),
+        None => entries.split_at(entries.len()),
+    };
+    for branch in 0..16 {
+        let count = rest
+            .iter()
+            .take_while(|(key, _)| key[depth] == branch)
+            .count();
+        let (children, others) = rest.split_at(count);
+        if children.is_empty() {
+            stream.append_empty_data();
+        } else {
+            append_child(&mut stream, &node(children, depth + 1));
+        }
+        rest = others;
+    }
+    match ended.first() {
+        Some((_, value)) => stream.append(value),
+        None => stream.append_empty_data(),
+    };
+    stream.out().to_vec()
+}
+
+#[cfg(test)]
+mod trie_test {
+    use super::*;
+
+    #[test]
+    fn test_ordered_trie_root() {
+        assert_eq!(
+            ordered_trie_root(&[]).to_string(),
+            "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+        );
+
+        let values = (0..200u8)
+            .map(|index| Bytes(vec![index; usize::from(index % 50)]))
+            .collect::<Vec<Bytes>>();
+
+        assert_eq!(
+            ordered_trie_root(&values[..1]).to_string(),
+            "0x1e03594df303045ca22e7d8f7ff4504ea2a9e1168864bf7b16ec37b79d9e2671"
+        );
+        assert_eq!(
+            ordered_trie_root(&values[..3]).to_string(),
+            "0x8adee953ae5e7774ff7528818c4fb71f369fb2d8c7e71634e2e14768a69a3a54"
+        );
+        assert_eq!(
+            ordered_trie_root(&values).to_string(),
+            "0xd300027574861bb6a1e204e49134e66f59c99e920e2b8862f2f6c150e45e778d"
+        );
+    }
+
+    /// Root of the trie mapping each ASCII key to its ASCII value.
+    fn ascii_trie_root(pairs: &[(&str, &str)]) -> String {
+        let entries = pairs
+            .iter()
+            .map(|(key, value)| (nibbles(key.as_bytes()), value.as_bytes()))
+            .collect::<Vec<Entry>>();
+        trie_root(entries).to_string()
+    }
+
+    #[test]
+    fn test_trie_root_vectors() {
+        // Vectors of trieanyorder.json, from the ethereum/tests repository.
+        assert_eq!(
+            ascii_trie_root(&[
+                ("doe", "reindeer"),
+                ("dog", "puppy"),
+                ("dogglesworth", "cat")
+            ]),
+            "0x8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3"
+        );
+        assert_eq!(
+            ascii_trie_root(&[
+                ("do", "verb"),
+                ("horse", "stallion"),
+                ("doge", "coin"),
+                ("dog", "puppy"),
+            ]),
+            "0x5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84"
+        );
+        assert_eq!(
+            ascii_trie_root(&[("foo", "bar"), ("food", "bass")]),
+            "0x17beaa1648bafa633cda809c90c04af50fc8aed3cb40d16efbddee6fdf63c4c3"
+        );
+        assert_eq!(
+            ascii_trie_root(&[("be", "e"), ("dog", "puppy"), ("bed", "d")]),
+            "0x3f67c7a47520f79faa29255d2d3c084a7a6df0453116ed7232ff10277a8be68b"
+        );
+        assert_eq!(
+            ascii_trie_root(&[("test", "test"), ("te", "testy")]),
+            "0x8452568af70d8d140f58d941338542f645fcca50094b20f3c3d8c3df49337928"
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use diesel::result::{DatabaseErrorKind, Error as dieselError};
+use std::fmt::{self, Display, Formatter};
+
+pub type StoreResult<T> = Result<T, StoreError>;
+
+/// Errors of the store's API, classified from diesel's errors.
+#[derive(Debug, PartialEq)]
+pub enum StoreError {
+    /// The queried row doesn't exist.
+    NotFound,
+    /// The store doesn't have the tables or columns the query expects.
+    SchemaMismatch {
+        message: String,
+    },
+    /// The database is locked by another connection (e.g. the node).
+    Busy {
+        message: String,
+    },
+    ConstraintViolation {
+        message: String,
+    },
+    /// A value read from the store can't be decoded.
+    Decode {
+        message: String,
+    },
+    InvalidInput {
+        message: String,
+    },
+    Database {
+        error: dieselError,
+    },
+}
+
+impl Display for StoreError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let string = match self {
+            StoreError::NotFound => "Not found".to_string(),
+            StoreError::SchemaMismatch { message } => format!("Schema mismatch:{}", message),
+            StoreError::Busy { message } => format!("Database busy:{}", message),
+            StoreError::ConstraintViolation { message } => {
+                format!("Constraint violation:{}", message)
+            }
+            StoreError::Decode { message } => format!("Decoding error:{}", message),
+            StoreError::InvalidInput { message } => format!("Invalid input:{}", message),
+            StoreError::Database { error } => format!("Internal database error:{}", error),
+        };
+        write!(f, "{}", string)
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+const SCHEMA_MISMATCH_MESSAGES: [&str; 3] = ["no such table", "no such column", "was not present"];
+const BUSY_MESSAGES: [&str; 2] = ["database is locked", "database table is locked"];
+
+impl From<dieselError> for StoreError {
+    fn from(error: dieselError) -> StoreError {
+        match error {
+            dieselError::NotFound => StoreError::NotFound,
+            dieselError::DatabaseError(kind, ref information) => {
+                let message = information.message().to_string();
+                match kind {
+                    DatabaseErrorKind::UniqueViolation
+                    | DatabaseErrorKind::ForeignKeyViolation
+                    | DatabaseErrorKind::NotNullViolation
+                    | DatabaseErrorKind::CheckViolation
+                    | DatabaseErrorKind::RestrictViolation => {
+                        StoreError::ConstraintViolation { message }
+                    }
+                    _ if SCHEMA_MISMATCH_MESSAGES.iter().any(|m| message.contains(m)) => {
+                        StoreError::SchemaMismatch { message }
+                    }
+                    _ if BUSY_MESSAGES.iter().any(|m| message.contains(m)) => {
+                        StoreError::Busy { message }
+                    }
+                    _ => StoreError::Database { error },
+                }
+            }
+            dieselError::DeserializationError(ref source) => {
+                let message = source.to_string();
+                if SCHEMA_MISMATCH_MESSAGES.iter().any(|m| message.contains(m)) {
+                    StoreError::SchemaMismatch { message }
+                } else {
+                    StoreError::Decode { message }
+                }
+            }
+            dieselError::SerializationError(source) => StoreError::InvalidInput {
+                message: source.to_string(),
+            },
+            dieselError::InvalidCString(source) => StoreError::InvalidInput {
+                message: source.to_string(),
+            },
+            error => StoreError::Database { error },
+        }
+    }
+}
+
+#[cfg(test)]
+mod error_test {
+    use super::*;
+    use crate::dieselsqlite::{
+        bootstrap::create_store,
+        fixture::TempStoreFile,
+        models::{Metadata, PendingConfirmation},
+        store::{Store, StoreConfig},
+        types::{BlockHash, L2Level},
+    };
+    use diesel::connection::SimpleConnection;
+    use std::time::Duration;
+
+    #[test]
+    fn test_store_error_classification() {
+        let connection = &mut create_store(":memory:").unwrap();
+
+        let not_found = Metadata::get_history_mode(connection);
+
+        assert_eq!(not_found, Err(StoreError::NotFound));
+
+        let pending_confirmation = || PendingConfirmation {
+            level: L2Level(1),
+            hash: BlockHash([1u8; 32]),
+        };
+        pending_confirmation().insert(connection).unwrap();
+        let constraint_violation = pending_confirmation().insert(connection);
+
+        assert!(matches!(
+            constraint_violation,
+            Err(StoreError::ConstraintViolation { .. })
+        ));
+
+        connection.batch_execute("DROP TABLE metadata;").unwrap();
+        let schema_mismatch = Metadata::get_history_mode(connection);
+
+        assert!(matches!(
+            schema_mismatch,
+            Err(StoreError::SchemaMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_store_error_busy() {
+        let store_file = TempStoreFile::new("error");
+        let path = store_file.path();
+
+        let writer = &mut create_store(path).unwrap();
+        let other_writer =
+            &mut Store::open(StoreConfig::new(path).busy_timeout(Duration::from_millis(10)))
+                .unwrap()
+                .into_connection();
+
+        writer.batch_execute("BEGIN EXCLUSIVE;").unwrap();
+        let busy = Metadata::insert_history_mode(other_writer, "archive");
+
+        assert!(matches!(busy, Err(StoreError::Busy { .. })));
+
+        writer.batch_execute("ROLLBACK;").unwrap();
+    }
+}
@@ -0,0 +1,741 @@
+use crate::{
+    dieselsqlite::{
+        decoder::{EthBlock, Log, TransactionObject, TransactionReceipt},
+        error::{StoreError, StoreResult},
+        models::{Block, L1L2FinalizedLevel, Transaction},
+        server::MethodRegistry,
+        types::{Address, BlockHash, Bloom, Bytes, H256, L2Level, Quantity, TxHash},
+    },
+    rpc_params,
+};
+use diesel::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize, de};
+use std::fmt::Display;
+
+/// Keccak hash of the RLP of an empty list: Etherlink blocks have no ommers.
+const EMPTY_OMMERS_HASH: &str =
+    "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347";
+
+/// 256 bits number given by its big endian bytes, as a quantity.
+fn big_quantity(bytes: &H256) -> String {
+    let digits = hex::encode(bytes.as_bytes());
+    match digits.trim_start_matches('0') {
+        "" => "0x0".to_string(),
+        digits => format!("0x{}", digits),
+    }
+}
+
+/// Block selected by its number or by one of the tags of the API. `latest`
+/// and `pending` are the top of the store, `safe` and `finalized` the last
+/// level finalized on L1 and `earliest` the oldest block kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockTag {
+    Number(Quantity),
+    Earliest,
+    Latest,
+    Pending,
+    Safe,
+    Finalized,
+}
+
+impl<'de> Deserialize<'de> for BlockTag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        match s.as_str() {
+            "earliest" => Ok(BlockTag::Earliest),
+            "latest" => Ok(BlockTag::Latest),
+            "pending" => Ok(BlockTag::Pending),
+            "safe" => Ok(BlockTag::Safe),
+            "finalized" => Ok(BlockTag::Finalized),
+            _ => Quantity::deserialize(de::value::StrDeserializer::<D::Error>::new(&s))
+                .map(BlockTag::Number),
+        }
+    }
+}
+
+impl BlockTag {
+    /// Level of the block, `None` when the store doesn't have it.
+    pub fn resolve(&self, connection: &mut SqliteConnection) -> StoreResult<Option<L2Level>> {
+        let level = match self {
+            BlockTag::Number(Quantity(number)) => {
+                return Ok(i32::try_from(*number).ok().map(L2Level));
+            }
+            BlockTag::Earliest => Block::base_level(connection),
+            BlockTag::Latest | BlockTag::Pending => Block::top_level(connection),
+            BlockTag::Safe | BlockTag::Finalized => L1L2FinalizedLevel::last_l2_level(connection),
+        };
+        optional(level)
+    }
+}
+
+/// Block selected by its hash, or by its number or a tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockId {
+    Hash(BlockHash),
+    Tag(BlockTag),
+}
+
+impl<'de> Deserialize<'de> for BlockId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let deserializer = de::value::StrDeserializer::<D::Error>::new(&s);
+        if s.len() == 66 {
+            BlockHash::deserialize(deserializer).map(BlockId::Hash)
+        } else {
+            BlockTag::deserialize(deserializer).map(BlockId::Tag)
+        }
+    }
+}
+
+/// Quantity of a level or an index read from the store, which can't be negative.
+fn quantity<T: Copy + Display>(value: T) -> StoreResult<Quantity>
+where
+    Quantity: TryFrom<T>,
+{
+    Quantity::try_from(value).map_err(|_| StoreError::Decode {
+        message: format!("Negative level or index {}", value),
+    })
+}
+
+fn optional<T>(result: StoreResult<T>) -> StoreResult<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(StoreError::NotFound) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+crate::rpc_object! {
+    /// Transaction object of the API.
+    ///
+    /// The node doesn't store the chain id, fee caps and access list of typed
+    /// transactions: they only have the fields of legacy ones, with the signature
+    /// parity as `yParity`.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RpcTransaction {
+        pub block_hash: BlockHash,
+        pub block_number: Quantity,
+        pub from: Address,
+        pub gas: Quantity,
+        pub gas_price: Quantity,
+        pub hash: TxHash,
+        pub input: Bytes,
+        pub nonce: Quantity,
+        pub to: Option<Address>,
+        pub transaction_index: Quantity,
+        pub value: Quantity,
+        #[serde(rename = "type")]
+        pub transaction_type: Quantity,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub chain_id: Option<Quantity>,
+        pub v: Quantity,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub y_parity: Option<Quantity>,
+        pub r: String,
+        pub s: String,
+    }
+}
+
+impl RpcTransaction {
+    /// Transaction of the API from its stored object and its receipt, which
+    /// has its type.
+    pub fn new(
+        object: &TransactionObject,
+        receipt: &TransactionReceipt,
+    ) -> StoreResult<RpcTransaction> {
+        let fields = &object.fields;
+        let legacy = receipt.fields.is_legacy();
+        Ok(RpcTransaction {
+            block_hash: object.block_hash,
+            block_number: quantity(object.block_number)?,
+            from: object.from,
+            gas: fields.gas,
+            gas_price: fields.gas_price,
+            hash: object.hash,
+            input: fields.input.clone(),
+            nonce: fields.nonce,
+            to: object.to,
+            transaction_index: quantity(object.transaction_index)?,
+            value: fields.value,
+            transaction_type: Quantity(receipt.fields.transaction_type.into()),
+            chain_id: (legacy && fields.v.0 >= 35).then(|| Quantity((fields.v.0 - 35) / 2)),
+            v: fields.v,
+            y_parity: (!legacy).then_some(fields.v),
+            r: big_quantity(&fields.r),
+            s: big_quantity(&fields.s),
+        })
+    }
+}
+
+crate::rpc_object! {
+    /// Log of a receipt of the API. `log_index` is the position of the log among
+    /// the logs of its block.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RpcLog {
+        pub address: Address,
+        pub topics: Vec<H256>,
+        pub data: Bytes,
+        pub block_hash: BlockHash,
+        pub block_number: Quantity,
+        pub transaction_hash: TxHash,
+        pub transaction_index: Quantity,
+        pub log_index: Quantity,
+        pub removed: bool,
+    }
+}
+
+crate::rpc_object! {
+    /// Transaction receipt of the API.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RpcReceipt {
+        pub block_hash: BlockHash,
+        pub block_number: Quantity,
+        pub contract_address: Option<Address>,
+        pub cumulative_gas_used: Quantity,
+        pub effective_gas_price: Quantity,
+        pub from: Address,
+        pub gas_used: Quantity,
+        pub logs: Vec<RpcLog>,
+        pub logs_bloom: Bloom,
+        pub status: Quantity,
+        pub to: Option<Address>,
+        pub transaction_hash: TxHash,
+        pub transaction_index: Quantity,
+        #[serde(rename = "type")]
+        pub transaction_type: Quantity,
+    }
+}
+
+impl RpcReceipt {
+    /// Receipt of the API, whose logs are numbered from `first_log_index`.
+    pub fn new(receipt: &TransactionReceipt, first_log_index: usize) -> StoreResult<RpcReceipt> {
+        let fields = &receipt.fields;
+        let block_number = quantity(receipt.block_number)?;
+        let transaction_index = quantity(receipt.transaction_index)?;
+        let log = |(position, log): (usize, &Log)| RpcLog {
+            address: log.address,
+            topics: log.topics.clone(),
+            data: log.data.clone(),
+            block_hash: receipt.block_hash,
+            block_number,
+            transaction_hash: receipt.hash,
+            transaction_index,
+            log_index: Quantity((first_log_index + position) as u128),
+            removed: false,
+        };
+        Ok(RpcReceipt {
+            block_hash: receipt.block_hash,
+            block_number,
+            contract_address: fields.contract_address,
+            cumulative_gas_used: fields.cumulative_gas_used,
+            effective_gas_price: fields.effective_gas_price,
+            from: receipt.from,
+            gas_used: fields.gas_used,
+            logs: fields.logs.iter().enumerate().map(log).collect(),
+            logs_bloom: fields.logs_bloom,
+            status: Quantity(fields.status.into()),
+            to: receipt.to,
+            transaction_hash: receipt.hash,
+            transaction_index,
+            transaction_type: Quantity(fields.transaction_type.into()),
+        })
+    }
+
+    /// Receipts of the API of the receipts of a block, in order.
+    pub fn of_block(receipts: &[TransactionReceipt]) -> StoreResult<Vec<RpcReceipt>> {
+        let mut first_log_index = 0;
+        receipts
+            .iter()
+            .map(|receipt| {
+                let rpc_receipt = RpcReceipt::new(receipt, first_log_index);
+                first_log_index += receipt.fields.logs.len();
+                rpc_receipt
+            })
+            .collect()
+    }
+}
+
+/// Transactions of a block of the API: their hashes or the full objects.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum RpcBlockTransactions {
+    Hashes(Vec<TxHash>),
+    Full(Vec<RpcTransaction>),
+}
+
+crate::rpc_object! {
+    /// Block of the API. Etherlink blocks have no ommers, a zero difficulty and
+    /// nonce, and the zero address as default miner. The size isn't given: the
+    /// typed transactions can't be encoded back.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RpcBlock {
+        pub number: Quantity,
+        pub hash: BlockHash,
+        pub parent_hash: BlockHash,
+        pub nonce: Bytes,
+        pub sha3_uncles: H256,
+        pub logs_bloom: Bloom,
+        pub transactions_root: H256,
+        pub state_root: H256,
+        pub receipts_root: H256,
+        pub miner: Address,
+        pub difficulty: Quantity,
+        pub total_difficulty: Quantity,
+        pub extra_data: Bytes,
+        pub gas_limit: Quantity,
+        pub gas_used: Quantity,
+        pub timestamp: Quantity,
+        pub base_fee_per_gas: Quantity,
+        pub mix_hash: H256,
+        pub transactions: RpcBlockTransactions,
+        pub uncles: Vec<BlockHash>,
+    }
+}
+
+impl RpcBlock {
+    pub fn new(block: EthBlock, transactions: RpcBlockTransactions) -> RpcBlock {
+        RpcBlock {
+            number: block.number.into(),
+            hash: block.hash,
+            parent_hash: block.parent_hash,
+            nonce: Bytes(vec![0u8; 8]),
+            sha3_uncles: EMPTY_OMMERS_HASH.parse().expect("Valid hash"),
+            logs_bloom: block.logs_bloom,
+            transactions_root: block.transactions_root,
+            state_root: block.state_root,
+            receipts_root: block.receipts_root,
+            miner: block.miner.unwrap_or(Address([0u8; 20])),
+            difficulty: Quantity(0),
+            total_difficulty: Quantity(0),
+            extra_data: block.extra_data,
+            gas_limit: block.gas_limit.into(),
+            gas_used: block.gas_used.into(),
+            timestamp: Quantity(block.timestamp as u128),
+            base_fee_per_gas: block.base_fee_per_gas.into(),
+            mix_hash: block.mix_hash,
+            transactions,
+            uncles: Vec::new(),
+        }
+    }
+}
+
+/// Transactions of the API of the block at `level`.
+fn select_block_transactions(
+    connection: &mut SqliteConnection,
+    level: L2Level,
+) -> StoreResult<Vec<RpcTransaction>> {
+    let objects = Transaction::select_objects_from_block_number(connection, level)?;
+    let receipts = Transaction::select_receipts_from_block_number(connection, level)?;
+    objects
+        .iter()
+        .zip(&receipts)
+        .map(|(object, receipt)| RpcTransaction::new(object, receipt))
+        .collect()
+}
+
+fn rpc_block(
+    connection: &mut SqliteConnection,
+    block: EthBlock,
+    full: bool,
+) -> StoreResult<RpcBlock> {
+    let transactions = if full {
+        let level = i32::try_from(block.number)
+            .map(L2Level)
+            .map_err(|_| StoreError::Decode {
+                message: format!("Block number {} out of range", block.number),
+            })?;
+        RpcBlockTransactions::Full(select_block_transactions(connection, level)?)
+    } else {
+        RpcBlockTransactions::Hashes(block.transactions.clone())
+    };
+    Ok(RpcBlock::new(block, transactions))
+}
+
+pub fn get_block_by_number(
+    connection: &mut SqliteConnection,
+    tag: BlockTag,
+    full: bool,
+) -> StoreResult<Option<RpcBlock>> {
+    let Some(level) = tag.resolve(connection)? else {
+        return Ok(None);
+    };
+    let Some(block) = optional(Block::select_decoded_with_level(connection, level))? else {
+        return Ok(None);
+    };
+    rpc_block(connection, block, full).map(Some)
+}
+
+pub fn get_block_by_hash(
+    connection: &mut SqliteConnection,
+    hash: &BlockHash,
+    full: bool,
+) -> StoreResult<Option<RpcBlock>> {
+    let Some(block) = optional(Block::select_decoded_with_hash(connection, hash))? else {
+        return Ok(None);
+    };
+    rpc_block(connection, block, full).map(Some)
+}
+
+pub fn get_transaction_by_hash(
+    connection: &mut SqliteConnection,
+    hash: &TxHash,
+) -> StoreResult<Option<RpcTransaction>> {
+    let Some(object) = optional(Transaction::select_object(connection, hash))? else {
+        return Ok(None);
+    };
+    let receipt = Transaction::select_receipt(connection, hash)?;
+    RpcTransaction::new(&object, &receipt).map(Some)
+}
+
+pub fn get_transaction_receipt(
+    connection: &mut SqliteConnection,
+    hash: &TxHash,
+) -> StoreResult<Option<RpcReceipt>> {
+    let Some(receipt) = optional(Transaction::select_receipt(connection, hash))? else {
+        return Ok(None);
+    };
+    let receipts =
+        Transaction::select_receipts_from_block_number(connection, receipt.block_number)?;
+    let first_log_index = receipts
+        .iter()
+        .take_while(|other| other.transaction_index < receipt.transaction_index)
+        .map(|other| other.fields.logs.len())
+        .sum();
+    RpcReceipt::new(&receipt, first_log_index).map(Some)
+}
+
+pub fn get_block_receipts(
+    connection: &mut SqliteConnection,
+    block: BlockId,
+) -> StoreResult<Option<Vec<RpcReceipt>>> {
+    let level = match block {
+        BlockId::Hash(hash) => optional(Block::select_number_of_hash(connection, &hash))?,
+        BlockId::Tag(tag) => match tag.resolve(connection)? {
+            Some(level) => {
+                optional(Block::select_hash_of_number(connection, level))?.map(|_| level)
+            }
+            None => None,
+        },
+    };
+    let Some(level) = level else {
+        return Ok(None);
+    };
+    let receipts = Transaction::select_receipts_from_block_number(connection, level)?;
+    RpcReceipt::of_block(&receipts).map(Some)
+}
+
+pub fn get_transaction_by_block_number_and_index(
+    connection: &mut SqliteConnection,
+    tag: BlockTag,
+    index: Quantity,
+) -> StoreResult<Option<RpcTransaction>> {
+    let Some(level) = tag.resolve(connection)? else {
+        return Ok(None);
+    };
+    let transactions = select_block_transactions(connection, level)?;
+    Ok(usize::try_from(index.0)
+        .ok()
+        .and_then(|index| transactions.into_iter().nth(index)))
+}
+
+rpc_params! {
+    struct NoParams {}
+}
+
+rpc_params! {
+    struct BlockTagParams {
+        block: BlockTag,
+        full: bool,
+    }
+}
+
+rpc_params! {
+    struct BlockHashParams {
+        hash: BlockHash,
+        full: bool,
+    }
+}
+
+rpc_params! {
+    struct TxHashParams {
+        hash: TxHash,
+    }
+}
+
+rpc_params! {
+    struct BlockIdParams {
+        block: BlockId,
+    }
+}
+
+rpc_params! {
+    struct BlockIndexParams {
+        block: BlockTag,
+        index: Quantity,
+    }
+}
+
+/// Methods of the `ethrpc` binary. Blocks and transactions that aren't in
+/// the store are `null`.
+pub fn methods() -> MethodRegistry {
+    let mut registry = MethodRegistry::new();
+    registry
+        .register("eth_blockNumber", |connection, _, _: NoParams| {
+            Ok(quantity(Block::top_level(connection)?)?)
+        })
+        .register(
+            "eth_getBlockByNumber",
+            |connection, _, params: BlockTagParams| {
+                Ok(get_block_by_number(connection, params.block, params.full)?)
+            },
+        )
+        .register(
+            "eth_getBlockByHash",
+            |connection, _, params: BlockHashParams| {
+                Ok(get_block_by_hash(connection, &params.hash, params.full)?)
+            },
+        )
+        .register(
+            "eth_getTransactionByHash",
+            |connection, _, params: TxHashParams| {
+                Ok(get_transaction_by_hash(connection, &params.hash)?)
+            },
+        )
+        .register(
+            "eth_getTransactionReceipt",
+            |connection, _, params: TxHashParams| {
+                Ok(get_transaction_receipt(connection, &params.hash)?)
+            },
+        )
+        .register(
+            "eth_getBlockReceipts",
+            |connection, _, params: BlockIdParams| {
+                Ok(get_block_receipts(connection, params.block)?)
+            },
+        )
+        .register(
+            "eth_getTransactionByBlockNumberAndIndex",
+            |connection, _, params: BlockIndexParams| {
+                Ok(get_transaction_by_block_number_and_index(
+                    connection,
+                    params.block,
+                    params.index,
+                )?)
+            },
+        );
+    registry
+}
+
+#[cfg(test)]
+mod ethrpc_test {
+    use super::*;
+    use crate::dieselsqlite::{
+        bootstrap::create_store,
+        fixture::{FixtureConfig, TempStoreFile, generate},
+        jsonrpc::{INVALID_PARAMS, METHOD_NOT_FOUND},
+        openrpc::{DISCOVER_METHOD, Schema},
+        pool::{PoolConfig, build_pool},
+        server::Pools,
+        store::StoreConfig,
+    };
+    use serde_json::{Value, json};
+
+    #[test]
+    fn test_quantity() {
+        assert_eq!(json!(Quantity(0)), json!("0x0"));
+        assert_eq!(json!(Quantity(0x1a2b)), json!("0x1a2b"));
+        assert_eq!(
+            serde_json::from_value::<Quantity>(json!("0x01a")).unwrap(),
+            Quantity(26)
+        );
+        assert!(serde_json::from_value::<Quantity>(json!("0x")).is_err());
+        assert!(serde_json::from_value::<Quantity>(json!("12")).is_err());
+        assert!(serde_json::from_value::<Quantity>(json!(12)).is_err());
+        assert_eq!(
+            serde_json::from_value::<BlockTag>(json!("finalized")).unwrap(),
+            BlockTag::Finalized
+        );
+        assert_eq!(
+            serde_json::from_value::<BlockId>(json!("0x10")).unwrap(),
+            BlockId::Tag(BlockTag::Number(Quantity(16)))
+        );
+        assert_eq!(
+            serde_json::from_value::<BlockId>(json!(BlockHash([1u8; 32]))).unwrap(),
+            BlockId::Hash(BlockHash([1u8; 32]))
+        );
+        assert_eq!(big_quantity(&H256([0u8; 32])), "0x0");
+
+        let mut bytes = [0u8; 32];
+        bytes[30] = 0x01;
+        bytes[31] = 0x02;
+
+        assert_eq!(big_quantity(&H256(bytes)), "0x102");
+    }
+
+    #[test]
+    fn test_methods() {
+        let store_file = TempStoreFile::new("ethrpc");
+        let path = store_file.path();
+        let config = FixtureConfig {
+            levels: 10,
+            transactions_per_block: 3,
+            finalized_level_interval: Some(4),
+            ..FixtureConfig::default()
+        };
+        generate(&mut create_store(path).unwrap(), &config).unwrap();
+        let pool = build_pool(
+            &StoreConfig::new(path).into_read_only(),
+            &PoolConfig::default(),
+        );
+        let pools = Pools::new(pool.unwrap());
+        let methods = methods();
+        let start_level = config.start_level;
+        let top_level = config.top_level();
+        let call = |method: &str, params: Value| methods.call(&pools, method, params);
+
+        assert_eq!(
+            call("eth_blockNumber", json!([])),
+            Ok(json!(Quantity::try_from(top_level).unwrap()))
+        );
+
+        let block = call("eth_getBlockByNumber", json!(["latest", false])).unwrap();
+
+        assert_eq!(
+            block["number"],
+            json!(Quantity::try_from(top_level).unwrap())
+        );
+        assert_eq!(block["transactions"].as_array().unwrap().len(), 3);
+        assert_eq!(block["nonce"], "0x0000000000000000");
+        assert_eq!(
+            call("eth_getBlockByHash", json!([block["hash"].clone(), false])),
+            Ok(block.clone())
+        );
+        assert_eq!(
+            call("eth_getBlockByHash", json!([BlockHash([1u8; 32]), false])),
+            Ok(Value::Null)
+        );
+        assert_eq!(
+            call("eth_getBlockByNumber", json!(["earliest", false])).unwrap()["number"],
+            json!(Quantity::try_from(start_level).unwrap())
+        );
+        assert_eq!(
+            call(
+                "eth_getBlockByNumber",
+                json!([Quantity::try_from(top_level + 1).unwrap(), false])
+            ),
+            Ok(Value::Null)
+        );
+
+        let finalized = call("eth_getBlockByNumber", json!(["finalized", true])).unwrap();
+        let transaction = finalized["transactions"][1].clone();
+
+        assert_eq!(
+            call(
+                "eth_getTransactionByHash",
+                json!([transaction["hash"].clone()])
+            ),
+            Ok(transaction.clone())
+        );
+        assert_eq!(
+            call(
+                "eth_getTransactionByBlockNumberAndIndex",
+                json!([finalized["number"].clone(), "0x1"])
+            ),
+            Ok(transaction.clone())
+        );
+        assert_eq!(
+            call(
+                "eth_getTransactionByBlockNumberAndIndex",
+                json!([finalized["number"].clone(), "0x3"])
+            ),
+            Ok(Value::Null)
+        );
+        assert_eq!(transaction["type"], "0x0");
+        assert_eq!(transaction["blockHash"], finalized["hash"]);
+
+        let receipts = call("eth_getBlockReceipts", json!([finalized["hash"].clone()])).unwrap();
+
+        assert_eq!(
+            call("eth_getBlockReceipts", json!([finalized["number"].clone()])),
+            Ok(receipts.clone())
+        );
+        assert_eq!(
+            call(
+                "eth_getTransactionReceipt",
+                json!([transaction["hash"].clone()])
+            ),
+            Ok(receipts[1].clone())
+        );
+
+        let log_indexes = receipts
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|receipt| receipt["logs"].as_array().unwrap().clone())
+            .map(|log| log["logIndex"].clone())
+            .collect::<Vec<Value>>();
+
+        assert_eq!(
+            log_indexes,
+            (0..log_indexes.len() as u128)
+                .map(|index| json!(Quantity(index)))
+                .collect::<Vec<Value>>()
+        );
+        assert_eq!(
+            call("eth_getTransactionReceipt", json!([TxHash([1u8; 32])])),
+            Ok(Value::Null)
+        );
+        assert_eq!(
+            call("eth_getBlockByNumber", json!(["latest"]))
+                .unwrap_err()
+                .code,
+            INVALID_PARAMS
+        );
+        assert_eq!(
+            call("eth_getBlockByNumber", json!(["newest", false]))
+                .unwrap_err()
+                .code,
+            INVALID_PARAMS
+        );
+        assert_eq!(
+            call("eth_chainId", Value::Null).unwrap_err().code,
+            METHOD_NOT_FOUND
+        );
+        assert_eq!(
+            call(
+                "eth_getBlockByNumber",
+                json!({"block": "latest", "full": false})
+            ),
+            Ok(block)
+        );
+
+        let properties = RpcReceipt::schema()["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<String>>();
+        let mut fields = receipts[1]
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<String>>();
+        fields.sort();
+
+        assert_eq!(fields, properties);
+
+        let document = call(DISCOVER_METHOD, Value::Null).unwrap();
+
+        assert_eq!(document["methods"].as_array().unwrap().len(), 7);
+        assert_eq!(document["methods"][0]["name"], "eth_blockNumber");
+        assert_eq!(
+            document["methods"][0]["result"]["schema"],
+            Quantity::schema()
+        );
+    }
+}
@@ -0,0 +1,555 @@
+use crate::dieselsqlite::{
+    applyblueprint::{ApplyBlueprint, ApplyBlueprintError, apply_blueprint},
+    decoder::{
+        BlueprintChunk, BlueprintContent, EthBlock, Log, ObjectFields, ReceiptFields,
+        SequencerBlueprint, keccak256, ordered_trie_root,
+    },
+    models::*,
+    types::{
+        self, Address, BlockHash, Bloom, Bytes, H256, L1Level, L2Level, Quantity, RootHash,
+        Signature, TxHash,
+    },
+    verify::{SequencerKey, blake2b_256},
+};
+use diesel::prelude::*;
+use ed25519_dalek::{Signer, SigningKey};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+const GAS_LIMIT: u64 = 30_000_000;
+const TRANSFER_GAS: u64 = 21_000;
+const BASE_FEE_PER_GAS: u64 = 1_000_000_000;
+const BLUEPRINT_CHUNK_SIZE: usize = 4_000;
+const RAW_TRANSACTION_LENGTH: usize = 120;
+
+/// Shape of a synthetic chain. Intervals are in L2 levels, `None` disables
+/// the corresponding table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixtureConfig {
+    pub seed: u64,
+    pub start_level: L2Level,
+    pub levels: i32,
+    pub transactions_per_block: i32,
+    pub start_timestamp: i64,
+    pub block_time: i64,
+    pub kernel_upgrade_interval: Option<i32>,
+    pub sequencer_upgrade_interval: Option<i32>,
+    pub delayed_transaction_interval: Option<i32>,
+    pub finalized_level_interval: Option<i32>,
+    pub irmin_chunk_interval: Option<i32>,
+    pub history_mode: String,
+    pub smart_rollup_address: String,
+    /// Ed25519 secret key of the sequencer signing the first blueprints.
+    pub sequencer_secret_key: [u8; 32],
+}
+
+impl FixtureConfig {
+    /// Key of the sequencer signing the blueprints before the first sequencer upgrade.
+    pub fn sequencer_key(&self) -> SequencerKey {
+        SequencerKey::Ed25519(SigningKey::from_bytes(&self.sequencer_secret_key).verifying_key())
+    }
+
+    /// Last level of the chain.
+    pub fn top_level(&self) -> L2Level {
+        self.start_level + self.levels - 1
+    }
+}
+
+impl Default for FixtureConfig {
+    fn default() -> Self {
+        FixtureConfig {
+            seed: 0,
+            start_level: L2Level(1000),
+            levels: 100,
+            transactions_per_block: 10,
+            start_timestamp: 1_700_000_000,
+            block_time: 1,
+            kernel_upgrade_interval: None,
+            sequencer_upgrade_interval: None,
+            delayed_transaction_interval: None,
+            finalized_level_interval: Some(10),
+            irmin_chunk_interval: Some(50),
+            history_mode: "rolling:1".to_string(),
+            smart_rollup_address: "sr1Ghq66tYK9y3r8CC1Tf8i8m5nxh8nTvZEf".to_string(),
+            sequencer_secret_key: [1u8; 32],
+        }
+    }
+}
+
+/// Number of rows generated in each table.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FixtureReport {
+    pub levels: usize,
+    pub transactions: usize,
+    pub kernel_upgrades: usize,
+    pub sequencer_upgrades: usize,
+    pub delayed_transactions: usize,
+    pub finalized_levels: usize,
+    pub irmin_chunks: usize,
+}
+
+/// Deterministic generator of synthetic levels: the same configuration
+/// (including the seed) always produces the same store content.
+pub struct FixtureGenerator {
+    config: FixtureConfig,
+    rng: StdRng,
+    parent_hash: BlockHash,
+    sequencer: SigningKey,
+    pending_sequencer: Option<SigningKey>,
+}
+
+impl FixtureGenerator {
+    pub fn new(config: FixtureConfig) -> FixtureGenerator {
+        let rng = StdRng::seed_from_u64(config.seed);
+        let sequencer = SigningKey::from_bytes(&config.sequencer_secret_key);
+        FixtureGenerator {
+            config,
+            rng,
+            parent_hash: BlockHash([0u8; 32]),
+            sequencer,
+            pending_sequencer: None,
+        }
+    }
+
+    pub fn config(&self) -> &FixtureConfig {
+        &self.config
+    }
+
+    fn bytes(&mut self, length: usize) -> Bytes {
+        let mut bytes = vec![0u8; length];
+        self.rng.fill(&mut bytes[..]);
+        Bytes(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> [u8; N] {
+        let mut array = [0u8; N];
+        self.rng.fill(&mut array);
+        array
+    }
+
+    fn timestamp(&self, level: L2Level) -> i64 {
+        self.config.start_timestamp
+            + i64::from(level.0 - self.config.start_level.0) * self.config.block_time
+    }
+
+    fn is_due(&self, interval: Option<i32>, level: L2Level) -> bool {
+        let offset = level.0 - self.config.start_level.0;
+        matches!(interval, Some(interval) if interval > 0 && offset > 0 && offset % interval == 0)
+    }
+
+    fn log(&mut self) -> Log {
+        let topics = self.rng.random_range(0..=4);
+        let data_length = self.rng.random_range(0..64);
+        Log {
+            address: Address(self.array()),
+            topics: (0..topics).map(|_| H256(self.array())).collect(),
+            data: self.bytes(data_length),
+        }
+    }
+
+    /// Legacy transfer (or contract creation, one time out of ten) at `index_`
+    /// in the block, with the block hash left to fill, along with its receipt
+    /// and Ethereum encoding.
+    fn transaction(&mut self, level: L2Level, index_: i32) -> (Transaction, ReceiptFields, Bytes) {
+        let to_ = if self.rng.random_ratio(1, 10) {
+            None
+        } else {
+            Some(Address(self.array()))
+        };
+        let logs = self.rng.random_range(0..3);
+        let mut receipt_fields = ReceiptFields {
+            cumulative_gas_used: (TRANSFER_GAS * (index_ as u64 + 1)).into(),
+            effective_gas_price: BASE_FEE_PER_GAS.into(),
+            gas_used: TRANSFER_GAS.into(),
+            logs: (0..logs).map(|_| self.log()).collect(),
+            logs_bloom: Bloom([0u8; 256]),
+            transaction_type: 0,
+            status: 1,
+            contract_address: match to_ {
+                Some(_) => None,
+                None => Some(Address(self.array())),
+            },
+        };
+        let input_length = self.rng.random_range(0..100);
+        let object_fields = ObjectFields {
+            gas: TRANSFER_GAS.into(),
+            gas_price: BASE_FEE_PER_GAS.into(),
+            input: self.bytes(input_length),
+            nonce: Quantity(self.rng.random_range(0..1000)),
+            value: Quantity(self.rng.random_range(0..10u128.pow(20))),
+            v: Quantity(self.rng.random_range(27..=28)),
+            r: H256(self.array()),
+            s: H256(self.array()),
+        };
+        receipt_fields.logs_bloom = receipt_fields.compute_logs_bloom();
+        let transaction_envelope = object_fields.encode_legacy(to_);
+        let transaction = Transaction {
+            block_hash: BlockHash([0u8; 32]),
+            block_number: level,
+            index_,
+            hash: TxHash(keccak256(transaction_envelope.as_bytes())),
+            from_: Address(self.array()),
+            to_,
+            receipt_fields: receipt_fields.encode(),
+            object_fields: object_fields.encode(),
+        };
+        (transaction, receipt_fields, transaction_envelope)
+    }
+
+    /// Synthetic content of `level`, as applied by the node. The blocks chain
+    /// through their parent hash when the levels are generated in order.
+    pub fn apply_blueprint_input(&mut self, level: L2Level) -> ApplyBlueprint {
+        let timestamp = self.timestamp(level);
+        let (mut transactions, receipts, transaction_envelopes): (
+            Vec<Transaction>,
+            Vec<ReceiptFields>,
+            Vec<Bytes>,
+        ) = (0..self.config.transactions_per_block)
+            .map(|index_| self.transaction(level, index_))
+            .collect();
+        let mut logs_bloom = Bloom([0u8; 256]);
+        for receipt in &receipts {
+            logs_bloom.accrue_bloom(&receipt.logs_bloom);
+        }
+        let receipt_envelopes = receipts
+            .iter()
+            .map(ReceiptFields::encode_envelope)
+            .collect::<Vec<Bytes>>();
+        let content = BlueprintContent {
+            parent_hash: self.parent_hash,
+            delayed_transactions: Vec::new(),
+            transactions: (0..transactions.len())
+                .map(|_| self.bytes(RAW_TRANSACTION_LENGTH))
+                .collect(),
+            timestamp,
+        };
+        let chunks = BlueprintChunk::split(level.0 as u64, &content, BLUEPRINT_CHUNK_SIZE)
+            .into_iter()
+            .map(|chunk| BlueprintChunk {
+                signature: Signature(
+                    self.sequencer
+                        .sign(&blake2b_256(chunk.unsigned().as_bytes()))
+                        .to_bytes(),
+                ),
+                ..chunk
+            })
+            .collect();
+        let blueprint = SequencerBlueprint {
+            number: level.0 as u64,
+            chunks,
+            content,
+        };
+        let mut block = EthBlock {
+            number: level.0 as u64,
+            hash: BlockHash([0u8; 32]),
+            parent_hash: self.parent_hash,
+            logs_bloom,
+            transactions_root: ordered_trie_root(&transaction_envelopes),
+            state_root: H256(self.array()),
+            receipts_root: ordered_trie_root(&receipt_envelopes),
+            miner: None,
+            extra_data: Bytes::default(),
+            gas_limit: GAS_LIMIT,
+            transactions: transactions
+                .iter()
+                .map(|transaction| transaction.hash)
+                .collect(),
+            gas_used: TRANSFER_GAS * transactions.len() as u64,
+            timestamp,
+            base_fee_per_gas: BASE_FEE_PER_GAS,
+            mix_hash: H256(self.array()),
+        };
+        let block_hash = block.compute_hash();
+        block.hash = block_hash;
+        for transaction in &mut transactions {
+            transaction.block_hash = block_hash;
+        }
+        self.parent_hash = block_hash;
+
+        ApplyBlueprint {
+            blueprint: Blueprint {
+                id: level,
+                payload: blueprint.encode(),
+                timestamp,
+            },
+            block: Block {
+                level,
+                hash: block_hash,
+                block: block.encode(),
+            },
+            transactions,
+            context_hash: ContextHashRow {
+                id: level,
+                context_hash: types::ContextHash(self.array()),
+            },
+        }
+    }
+
+    /// Fills the store with the configured levels, in a single transaction.
+    pub fn generate(
+        &mut self,
+        connection: &mut SqliteConnection,
+    ) -> Result<FixtureReport, ApplyBlueprintError> {
+        connection.transaction(|conn| {
+            Metadata::insert_history_mode(conn, &self.config.history_mode)?;
+            Metadata::insert_smart_rollup_address(conn, &self.config.smart_rollup_address)?;
+
+            let mut report = FixtureReport::default();
+            let start_level = self.config.start_level;
+            let mut l1_level = L1Level(0);
+            let mut last_finalized_level = start_level - 1;
+
+            for level in (start_level.0..start_level.0 + self.config.levels).map(L2Level) {
+                if report.kernel_upgrades > 0 {
+                    KernelUpgrade::record_apply(conn, level)?;
+                }
+                if report.sequencer_upgrades > 0 {
+                    SequencerUpgrade::record_apply(conn, level)?;
+                }
+                if let Some(sequencer) = self.pending_sequencer.take() {
+                    self.sequencer = sequencer;
+                }
+
+                let input = self.apply_blueprint_input(level);
+                report.transactions += input.transactions.len();
+                apply_blueprint(conn, input)?;
+                report.levels += 1;
+
+                let timestamp = self.timestamp(level);
+
+                if self.is_due(self.config.kernel_upgrade_interval, level) {
+                    let kernel_upgrade = KernelUpgrade {
+                        injected_before: level + 1,
+                        root_hash: RootHash(self.array()),
+                        activation_timestamp: timestamp,
+                        applied_before: None,
+                    };
+                    kernel_upgrade.insert(conn)?;
+                    report.kernel_upgrades += 1;
+                }
+                if self.is_due(self.config.sequencer_upgrade_interval, level) {
+                    let sequencer = SigningKey::from_bytes(&self.array());
+                    let sequencer_upgrade = SequencerUpgrade {
+                        injected_before: level + 1,
+                        sequencer: SequencerKey::Ed25519(sequencer.verifying_key()).encode(),
+                        pool_address: Address(self.array()),
+                        activation_timestamp: timestamp,
+                        applied_before: None,
+                    };
+                    sequencer_upgrade.insert(conn)?;
+                    self.pending_sequencer = Some(sequencer);
+                    report.sequencer_upgrades += 1;
+                }
+                if self.is_due(self.config.delayed_transaction_interval, level) {
+                    let payload_length = self.rng.random_range(100..300);
+                    let delayed_transaction = DelayedTransaction {
+                        injected_before: level + 1,
+                        hash: TxHash(self.array()),
+                        payload: self.bytes(payload_length),
+                    };
+                    delayed_transaction.insert(conn)?;
+                    report.delayed_transactions += 1;
+                }
+                if self.is_due(self.config.finalized_level_interval, level) {
+                    l1_level += 1;
+                    let finalized_level = L1L2FinalizedLevel {
+                        l1_level,
+                        start_l2_level: last_finalized_level,
+                        end_l2_level: level,
+                    };
+                    finalized_level.insert(conn)?;
+                    let relationship = L1L2LevelRelationship {
+                        latest_l2_level: level,
+                        l1_level,
+                    };
+                    relationship.insert(conn)?;
+                    last_finalized_level = level;
+                    report.finalized_levels += 1;
+                }
+                if self.is_due(self.config.irmin_chunk_interval, level) {
+                    let irmin_chunk = IrminChunk { level, timestamp };
+                    irmin_chunk.insert(conn)?;
+                    report.irmin_chunks += 1;
+                }
+            }
+
+            Ok(report)
+        })
+    }
+}
+
+/// Fills the store with the synthetic chain described by `config`.
+pub fn generate(
+    connection: &mut SqliteConnection,
+    config: &FixtureConfig,
+) -> Result<FixtureReport, ApplyBlueprintError> {
+    FixtureGenerator::new(config.clone()).generate(connection)
+}
+
+/// Path of a store in the temporary directory, unique to the test, whose
+/// files (with the WAL and shared memory ones) are removed when dropped.
+#[cfg(test)]
+pub(crate) struct TempStoreFile {
+    path: String,
+}
+
+#[cfg(test)]
+impl TempStoreFile {
+    pub(crate) fn new(name: &str) -> TempStoreFile {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let file = format!(
+            "evmnodetooling-{}-{}-{}.sqlite",
+            name,
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        );
+        let path = std::env::temp_dir().join(file);
+        TempStoreFile {
+            path: path.to_str().unwrap().to_string(),
+        }
+    }
+
+    pub(crate) fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempStoreFile {
+    fn drop(&mut self) {
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", self.path, suffix));
+        }
+    }
+}
+
+/// In-memory store filled with `levels` levels of `transactions_per_block`
+/// transactions, and the configuration of its chain.
+#[cfg(test)]
+pub(crate) fn fixture_store(
+    levels: i32,
+    transactions_per_block: i32,
+) -> (SqliteConnection, FixtureConfig) {
+    let mut connection = crate::dieselsqlite::bootstrap::create_store(":memory:").unwrap();
+    let config = FixtureConfig {
+        levels,
+        transactions_per_block,
+        ..FixtureConfig::default()
+    };
+    generate(&mut connection, &config).unwrap();
+    (connection, config)
+}
+
+#[cfg(test)]
+mod fixture_test {
+    use super::*;
+    use crate::dieselsqlite::bootstrap::create_store;
+
+    fn all_block_hashes(
+        connection: &mut SqliteConnection,
+        config: &FixtureConfig,
+    ) -> Vec<BlockHash> {
+        (config.start_level.0..config.start_level.0 + config.levels)
+            .map(|level| Block::select_hash_of_number(connection, L2Level(level)).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_generate() {
+        let config = FixtureConfig {
+            levels: 30,
+            transactions_per_block: 3,
+            kernel_upgrade_interval: Some(10),
+            sequencer_upgrade_interval: Some(15),
+            delayed_transaction_interval: Some(5),
+            finalized_level_interval: Some(10),
+            irmin_chunk_interval: Some(10),
+            ..FixtureConfig::default()
+        };
+        let connection = &mut create_store(":memory:").unwrap();
+
+        let report = generate(connection, &config).unwrap();
+
+        let expected_report = FixtureReport {
+            levels: 30,
+            transactions: 90,
+            kernel_upgrades: 2,
+            sequencer_upgrades: 1,
+            delayed_transactions: 5,
+            finalized_levels: 2,
+            irmin_chunks: 2,
+        };
+
+        assert_eq!(report, expected_report);
+
+        let top_level = config.top_level();
+
+        assert_eq!(Block::base_level(connection).unwrap(), config.start_level);
+        assert_eq!(Block::top_level(connection).unwrap(), top_level);
+        assert_eq!(Blueprint::top_level(connection).unwrap(), top_level);
+        assert_eq!(ContextHashRow::get_latest(connection).unwrap().0, top_level);
+        assert_eq!(
+            Transaction::select_receipts_from_block_number(connection, top_level)
+                .unwrap()
+                .len(),
+            3
+        );
+        assert_eq!(
+            Metadata::get_history_mode(connection).unwrap(),
+            config.history_mode
+        );
+        assert_eq!(
+            KernelUpgrade::activation_levels(connection).unwrap(),
+            vec![config.start_level + 21, config.start_level + 11]
+        );
+        assert_eq!(
+            SequencerUpgrade::activation_levels(connection).unwrap(),
+            vec![config.start_level + 16]
+        );
+        assert_eq!(
+            L1L2FinalizedLevel::last(connection).unwrap(),
+            (L1Level(2), config.start_level + 10, config.start_level + 20)
+        );
+        assert_eq!(
+            L1L2FinalizedLevel::find_l1_level(connection, config.start_level + 15).unwrap(),
+            L1Level(2)
+        );
+        assert_eq!(
+            IrminChunk::latest(connection).unwrap().0,
+            config.start_level + 20
+        );
+    }
+
+    #[test]
+    fn test_generate_is_deterministic() {
+        let config = FixtureConfig {
+            levels: 5,
+            transactions_per_block: 2,
+            ..FixtureConfig::default()
+        };
+        let first = &mut create_store(":memory:").unwrap();
+        let second = &mut create_store(":memory:").unwrap();
+        let other_seed = &mut create_store(":memory:").unwrap();
+
+        generate(first, &config).unwrap();
+        generate(second, &config).unwrap();
+        generate(
+            other_seed,
+            &FixtureConfig {
+                seed: 1,
+                ..config.clone()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            all_block_hashes(first, &config),
+            all_block_hashes(second, &config)
+        );
+        assert_ne!(
+            all_block_hashes(first, &config),
+            all_block_hashes(other_seed, &config)
+        );
+    }
+}
@@ -0,0 +1,510 @@
+use crate::dieselsqlite::{
+    error::{StoreError, StoreResult},
+    level_batches,
+    models::*,
+    types::{BlockHash, L1Level, L2Level, TxHash},
+};
+use diesel::prelude::*;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display, Formatter},
+};
+
+/// Number of levels checked by a single batch of queries.
+const BATCH_SIZE: i32 = 1000;
+
+/// Inconsistency between the tables of the store.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub enum FsckIssue {
+    MissingRow {
+        table: &'static str,
+        level: L2Level,
+    },
+    TransactionBlockHashMismatch {
+        transaction_hash: TxHash,
+        block_number: L2Level,
+        block_hash: BlockHash,
+        expected: Option<BlockHash>,
+    },
+    FinalizedLevelsGap {
+        l1_level: L1Level,
+        start_l2_level: L2Level,
+        previous_end_l2_level: L2Level,
+    },
+    FinalizedLevelsOverlap {
+        l1_level: L1Level,
+        start_l2_level: L2Level,
+        previous_end_l2_level: L2Level,
+    },
+    NonMonotonicTimestamp {
+        level: L2Level,
+        timestamp: i64,
+        previous_timestamp: i64,
+    },
+    AppliedBeyondTopLevel {
+        table: &'static str,
+        applied_before: L2Level,
+        top_level: L2Level,
+    },
+    OrphanedPendingConfirmation {
+        level: L2Level,
+        hash: BlockHash,
+    },
+}
+
+impl Display for FsckIssue {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let string = match self {
+            FsckIssue::MissingRow { table, level } => {
+                format!("Level {}:missing row in {}", level, table)
+            }
+            FsckIssue::TransactionBlockHashMismatch {
+                transaction_hash,
+                block_number,
+                block_hash,
+                expected,
+            } => match expected {
+                Some(expected) => format!(
+                    "Level {}:transaction {} has block hash {}, expected {}",
+                    block_number, transaction_hash, block_hash, expected
+                ),
+                None => format!(
+                    "Level {}:transaction {} has block hash {}, but there is no block",
+                    block_number, transaction_hash, block_hash
+                ),
+            },
+            FsckIssue::FinalizedLevelsGap {
+                l1_level,
+                start_l2_level,
+                previous_end_l2_level,
+            } => format!(
+                "L1 level {}:finalized L2 levels start at {}, after a gap from {}",
+                l1_level, start_l2_level, previous_end_l2_level
+            ),
+            FsckIssue::FinalizedLevelsOverlap {
+                l1_level,
+                start_l2_level,
+                previous_end_l2_level,
+            } => format!(
+                "L1 level {}:finalized L2 levels start at {}, overlapping up to {}",
+                l1_level, start_l2_level, previous_end_l2_level
+            ),
+            FsckIssue::NonMonotonicTimestamp {
+                level,
+                timestamp,
+                previous_timestamp,
+            } => format!(
+                "Level {}:blueprint timestamp {} is before the previous one {}",
+                level, timestamp, previous_timestamp
+            ),
+            FsckIssue::AppliedBeyondTopLevel {
+                table,
+                applied_before,
+                top_level,
+            } => format!(
+                "Upgrade in {} applied before level {}, beyond the top level {}",
+                table, applied_before, top_level
+            ),
+            FsckIssue::OrphanedPendingConfirmation { level, hash } => format!(
+                "Level {}:pending confirmation of {} matches no block",
+                level, hash
+            ),
+        };
+        write!(f, "{}", string)
+    }
+}
+
+/// Issues found by `check` in a level range. Serializes to JSON for tools,
+/// displays one line per issue for humans.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FsckReport {
+    pub lower_level: L2Level,
+    pub upper_level: L2Level,
+    pub issues: Vec<FsckIssue>,
+}
+
+impl FsckReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl Display for FsckReport {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "{}", issue)?;
+        }
+        write!(
+            f,
+            "Levels {} to {}:{} issue(s)",
+            self.lower_level,
+            self.upper_level,
+            self.issues.len()
+        )
+    }
+}
+
+fn missing_rows(
+    connection: &mut SqliteConnection,
+    lowerlevel: L2Level,
+    upperlevel: L2Level,
+) -> StoreResult<Vec<FsckIssue>> {
+    let tables: [(&'static str, BTreeSet<L2Level>); 3] = [
+        (
+            "blueprints",
+            Blueprint::select_timestamps_range(connection, lowerlevel, upperlevel)?
+                .into_iter()
+                .map(|(level, _)| level)
+                .collect(),
+        ),
+        (
+            "blocks",
+            Block::select_hashes_range(connection, lowerlevel, upperlevel)?
+                .into_iter()
+                .map(|(level, _)| level)
+                .collect(),
+        ),
+        (
+            "context_hashes",
+            ContextHashRow::select_ids_range(connection, lowerlevel, upperlevel)?
+                .into_iter()
+                .collect(),
+        ),
+    ];
+    Ok((lowerlevel.0..=upperlevel.0)
+        .map(L2Level)
+        .flat_map(|level| {
+            tables
+                .iter()
+                .filter(move |(_, levels)| !levels.contains(&level))
+                .map(move |(table, _)| FsckIssue::MissingRow { table, level })
+        })
+        .collect())
+}
+
+fn transaction_block_hashes(
+    connection: &mut SqliteConnection,
+    lowerlevel: L2Level,
+    upperlevel: L2Level,
+) -> StoreResult<Vec<FsckIssue>> {
+    let block_hashes = Block::select_hashes_range(connection, lowerlevel, upperlevel)?
+        .into_iter()
+        .collect::<BTreeMap<L2Level, BlockHash>>();
+    Ok(
+        Transaction::select_block_hashes_range(connection, lowerlevel, upperlevel)?
+            .into_iter()
+            .filter_map(|(block_number, transaction_hash, block_hash)| {
+                let expected = block_hashes.get(&block_number).copied();
+                (expected != Some(block_hash)).then_some(FsckIssue::TransactionBlockHashMismatch {
+                    transaction_hash,
+                    block_number,
+                    block_hash,
+                    expected,
+                })
+            })
+            .collect(),
+    )
+}
+
+fn finalized_levels(
+    connection: &mut SqliteConnection,
+    batch_start: L2Level,
+    batch_end: L2Level,
+    upperlevel: L2Level,
+    previous: &mut Option<FinalizedLevelRow>,
+) -> StoreResult<Vec<FsckIssue>> {
+    let finalized_levels =
+        L1L2FinalizedLevel::list_by_start_l2_levels(connection, batch_start, batch_end)?;
+    Ok(finalized_levels
+        .into_iter()
+        .filter(|(_, _, end_l2_level)| *end_l2_level <= upperlevel)
+        .filter_map(|row| {
+            let (l1_level, start_l2_level, _) = row;
+            let (_, _, previous_end_l2_level) = previous.replace(row)?;
+            if start_l2_level > previous_end_l2_level {
+                Some(FsckIssue::FinalizedLevelsGap {
+                    l1_level,
+                    start_l2_level,
+                    previous_end_l2_level,
+                })
+            } else if start_l2_level < previous_end_l2_level {
+                Some(FsckIssue::FinalizedLevelsOverlap {
+                    l1_level,
+                    start_l2_level,
+                    previous_end_l2_level,
+                })
+            } else {
+                None
+            }
+        })
+        .collect())
+}
+
+fn blueprint_timestamps(
+    connection: &mut SqliteConnection,
+    batch_start: L2Level,
+    batch_end: L2Level,
+    previous: &mut Option<(L2Level, i64)>,
+) -> StoreResult<Vec<FsckIssue>> {
+    let timestamps = Blueprint::select_timestamps_range(connection, batch_start, batch_end)?;
+    Ok(timestamps
+        .into_iter()
+        .filter_map(|row| {
+            let (level, timestamp) = row;
+            let (_, previous_timestamp) = previous.replace(row)?;
+            (timestamp < previous_timestamp).then_some(FsckIssue::NonMonotonicTimestamp {
+                level,
+                timestamp,
+                previous_timestamp,
+            })
+        })
+        .collect())
+}
+
+fn applied_upgrades(
+    connection: &mut SqliteConnection,
+    top_level: Option<L2Level>,
+) -> StoreResult<Vec<FsckIssue>> {
+    let Some(top_level) = top_level else {
+        return Ok(Vec::new());
+    };
+    let upgrades = [
+        (
+            "kernel_upgrades",
+            KernelUpgrade::activation_levels(connection)?,
+        ),
+        (
+            "sequencer_upgrades",
+            SequencerUpgrade::activation_levels(connection)?,
+        ),
+    ];
+    Ok(upgrades
+        .into_iter()
+        .flat_map(|(table, activation_levels)| {
+            activation_levels
+                .into_iter()
+                .filter(|applied_before| *applied_before > top_level)
+                .map(move |applied_before| FsckIssue::AppliedBeyondTopLevel {
+                    table,
+                    applied_before,
+                    top_level,
+                })
+        })
+        .collect())
+}
+
+fn pending_confirmations(
+    connection: &mut SqliteConnection,
+    lowerlevel: L2Level,
+    upperlevel: L2Level,
+) -> StoreResult<Vec<FsckIssue>> {
+    let mut issues = Vec::new();
+    for (level, hash) in PendingConfirmation::select_all(connection)? {
+        if !(lowerlevel..=upperlevel).contains(&level) {
+            continue;
+        }
+        match Block::select_hash_of_number(connection, level) {
+            Ok(stored) if stored == hash => (),
+            Ok(_) | Err(StoreError::NotFound) => {
+                issues.push(FsckIssue::OrphanedPendingConfirmation { level, hash })
+            }
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(issues)
+}
+
+/// Checks the consistency of the tables from `lowerlevel` to `upperlevel`:
+/// rows of every level, block hashes of the transactions, finalized levels
+/// ranges, blueprint timestamps, applied upgrades and pending confirmations.
+/// The levels are read by batches, so only the issues are kept in memory.
+pub fn check(
+    connection: &mut SqliteConnection,
+    lowerlevel: L2Level,
+    upperlevel: L2Level,
+) -> StoreResult<FsckReport> {
+    check_by_batches(connection, lowerlevel, upperlevel, BATCH_SIZE)
+}
+
+fn check_by_batches(
+    connection: &mut SqliteConnection,
+    lowerlevel: L2Level,
+    upperlevel: L2Level,
+    batch_size: i32,
+) -> StoreResult<FsckReport> {
+    let top_level = match Block::top_level(connection) {
+        Ok(top_level) => Some(top_level),
+        Err(StoreError::NotFound) => None,
+        Err(error) => return Err(error),
+    };
+
+    let mut missing = Vec::new();
+    let mut mismatches = Vec::new();
+    let mut finalized = Vec::new();
+    let mut timestamps = Vec::new();
+    let mut previous_finalized = None;
+    let mut previous_timestamp = None;
+    for (batch_start, batch_end) in level_batches(lowerlevel, upperlevel, batch_size) {
+        missing.extend(missing_rows(connection, batch_start, batch_end)?);
+        mismatches.extend(transaction_block_hashes(
+            connection,
+            batch_start,
+            batch_end,
+        )?);
+        finalized.extend(finalized_levels(
+            connection,
+            batch_start,
+            batch_end,
+            upperlevel,
+            &mut previous_finalized,
+        )?);
+        timestamps.extend(blueprint_timestamps(
+            connection,
+            batch_start,
+            batch_end,
+            &mut previous_timestamp,
+        )?);
+    }
+
+    let mut issues = missing;
+    issues.extend(mismatches);
+    issues.extend(finalized);
+    issues.extend(timestamps);
+    issues.extend(applied_upgrades(connection, top_level)?);
+    issues.extend(pending_confirmations(connection, lowerlevel, upperlevel)?);
+
+    Ok(FsckReport {
+        lower_level: lowerlevel,
+        upper_level: upperlevel,
+        issues,
+    })
+}
+
+#[cfg(test)]
+mod fsck_test {
+    use super::*;
+    use crate::dieselsqlite::{
+        bootstrap::create_store,
+        fixture::{FixtureConfig, generate},
+        types::{self, Bytes, RootHash},
+    };
+
+    #[test]
+    fn test_check() {
+        let connection = &mut create_store(":memory:").unwrap();
+        let config = FixtureConfig {
+            levels: 30,
+            transactions_per_block: 2,
+            kernel_upgrade_interval: Some(10),
+            finalized_level_interval: Some(5),
+            ..FixtureConfig::default()
+        };
+        generate(connection, &config).unwrap();
+        let start_level = config.start_level;
+        let top_level = config.top_level();
+
+        let report = check(connection, start_level, top_level).unwrap();
+
+        assert!(report.is_clean(), "{}", report);
+
+        ContextHashRow::clear_after(connection, top_level - 1).unwrap();
+        Transaction {
+            block_hash: BlockHash([1u8; 32]),
+            block_number: top_level,
+            index_: 2,
+            hash: TxHash([2u8; 32]),
+            from_: types::Address([3u8; 20]),
+            to_: None,
+            receipt_fields: Bytes::default(),
+            object_fields: Bytes::default(),
+        }
+        .insert(connection)
+        .unwrap();
+        L1L2FinalizedLevel {
+            l1_level: L1Level(100),
+            start_l2_level: top_level - 8,
+            end_l2_level: top_level - 1,
+        }
+        .insert(connection)
+        .unwrap();
+        Blueprint {
+            id: top_level + 1,
+            payload: Bytes::default(),
+            timestamp: 0,
+        }
+        .insert(connection)
+        .unwrap();
+        KernelUpgrade {
+            injected_before: top_level,
+            root_hash: RootHash([4u8; 33]),
+            activation_timestamp: 0,
+            applied_before: None,
+        }
+        .insert(connection)
+        .unwrap();
+        KernelUpgrade::record_apply(connection, top_level + 5).unwrap();
+        PendingConfirmation {
+            level: top_level,
+            hash: BlockHash([5u8; 32]),
+        }
+        .insert(connection)
+        .unwrap();
+
+        let report = check(connection, start_level, top_level + 1).unwrap();
+
+        assert_eq!(
+            report.issues,
+            vec![
+                FsckIssue::MissingRow {
+                    table: "context_hashes",
+                    level: top_level,
+                },
+                FsckIssue::MissingRow {
+                    table: "blocks",
+                    level: top_level + 1,
+                },
+                FsckIssue::MissingRow {
+                    table: "context_hashes",
+                    level: top_level + 1,
+                },
+                FsckIssue::TransactionBlockHashMismatch {
+                    transaction_hash: TxHash([2u8; 32]),
+                    block_number: top_level,
+                    block_hash: BlockHash([1u8; 32]),
+                    expected: Some(Block::select_hash_of_number(connection, top_level).unwrap()),
+                },
+                FsckIssue::FinalizedLevelsOverlap {
+                    l1_level: L1Level(100),
+                    start_l2_level: top_level - 8,
+                    previous_end_l2_level: top_level - 4,
+                },
+                FsckIssue::NonMonotonicTimestamp {
+                    level: top_level + 1,
+                    timestamp: 0,
+                    previous_timestamp: Blueprint::select(connection, top_level).unwrap().1,
+                },
+                FsckIssue::AppliedBeyondTopLevel {
+                    table: "kernel_upgrades",
+                    applied_before: top_level + 5,
+                    top_level,
+                },
+                FsckIssue::OrphanedPendingConfirmation {
+                    level: top_level,
+                    hash: BlockHash([5u8; 32]),
+                },
+            ]
+        );
+
+        let json = serde_json::to_value(&report).unwrap();
+
+        assert_eq!(json["issues"][0]["type"], "MissingRow");
+        assert_eq!(json["issues"][0]["table"], "context_hashes");
+        assert!(report.to_string().ends_with("8 issue(s)"));
+        assert_eq!(
+            check_by_batches(connection, start_level, top_level + 1, 4).unwrap(),
+            report
+        );
+    }
+}
@@ -0,0 +1,443 @@
+use crate::dieselsqlite::error::StoreError;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value, json};
+use std::fmt::{self, Display, Formatter};
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+/// Errors of the store other than the ones below.
+pub const SERVER_ERROR: i64 = -32000;
+/// The queried row doesn't exist.
+pub const NOT_FOUND: i64 = -32001;
+/// The store is locked by another connection, the request can be retried.
+pub const BUSY: i64 = -32002;
+/// The method needs a resource the server wasn't started with.
+pub const UNAVAILABLE: i64 = -32003;
+
+const VERSION: &str = "2.0";
+
+/// Error object of a JSON-RPC 2.0 response.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> RpcError {
+        RpcError {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    pub fn method_not_found(method: &str) -> RpcError {
+        RpcError::new(METHOD_NOT_FOUND, format!("Unknown method:{}", method))
+    }
+
+    pub fn invalid_params(message: impl Display) -> RpcError {
+        RpcError::new(INVALID_PARAMS, format!("Invalid parameters:{}", message))
+    }
+
+    pub fn internal(message: impl Display) -> RpcError {
+        RpcError::new(INTERNAL_ERROR, format!("Internal error:{}", message))
+    }
+}
+
+impl Display for RpcError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.message, self.code)
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+impl From<StoreError> for RpcError {
+    fn from(error: StoreError) -> RpcError {
+        let code = match error {
+            StoreError::NotFound => NOT_FOUND,
+            StoreError::InvalidInput { message: _ } => INVALID_PARAMS,
+            StoreError::Busy { message: _ } => BUSY,
+            StoreError::SchemaMismatch { message: _ }
+            | StoreError::ConstraintViolation { message: _ }
+            | StoreError::Decode { message: _ }
+            | StoreError::Database { error: _ } => SERVER_ERROR,
+        };
+        RpcError::new(code, error.to_string())
+    }
+}
+
+/// Params of a call given by name.
+pub type NamedParams = Map<String, Value>;
+
+/// Parameter of a method: its name, the Rust type of its value, whether it
+/// can be omitted and the JSON Schema of its value.
+#[derive(Debug, Clone, Copy)]
+pub struct ParamField {
+    pub name: &'static str,
+    pub type_name: &'static str,
+    pub required: bool,
+    pub schema: fn() -> Value,
+}
+
+/// Params of a method, declared with `rpc_params!`. They can be given by
+/// position, in the order of `FIELDS`, or by name.
+pub trait Params: Sized {
+    const FIELDS: &'static [ParamField];
+
+    /// Reads the params from their values by name, taking them out of `values`.
+    fn from_named(values: &mut NamedParams) -> Result<Self, RpcError>;
+}
+
+fn invalid_param(field: &ParamField, message: String) -> RpcError {
+    RpcError {
+        data: Some(json!({ "field": field.name, "expected": field.type_name })),
+        ..RpcError::invalid_params(message)
+    }
+}
+
+/// Value of the param `field`. A missing param is read as `null`, so it can
+/// only be omitted when it is optional.
+pub fn take_param<T: DeserializeOwned>(
+    values: &mut NamedParams,
+    field: &ParamField,
+) -> Result<T, RpcError> {
+    match values.remove(field.name) {
+        Some(value) => serde_json::from_value(value).map_err(|error| {
+            invalid_param(
+                field,
+                format!("{} ({}):{}", field.name, field.type_name, error),
+            )
+        }),
+        None => serde_json::from_value(Value::Null).map_err(|_| {
+            invalid_param(
+                field,
+                format!("missing {} ({})", field.name, field.type_name),
+            )
+        }),
+    }
+}
+
+/// Reads the params of a call: an array of the values of `P::FIELDS`, an
+/// object of the values by name, or `null` when there are none.
+pub fn parse_params<P: Params>(params: Value) -> Result<P, RpcError> {
+    let mut values = match params {
+        Value::Null => NamedParams::new(),
+        Value::Object(values) => values,
+        Value::Array(values) if values.len() > P::FIELDS.len() => {
+            return Err(RpcError::invalid_params(format!(
+                "expected at most {} params, found {}",
+                P::FIELDS.len(),
+                values.len()
+            )));
+        }
+        Value::Array(values) => P::FIELDS
+            .iter()
+            .map(|field| field.name.to_string())
+            .zip(values)
+            .collect(),
+        _ => {
+            return Err(RpcError::invalid_params(
+                "params should be an array or object",
+            ));
+        }
+    };
+    let params = P::from_named(&mut values)?;
+    match values.keys().next() {
+        Some(name) => Err(RpcError::invalid_params(format!("unknown param {}", name))),
+        None => Ok(params),
+    }
+}
+
+/// Declares the struct of the params of a method and implements `Params`
+/// for it, naming each param after its field.
+#[macro_export]
+macro_rules! rpc_params {
+    ($(#[$meta:meta])* $vis:vis struct $name:ident {
+        $($(#[$field_meta:meta])* $field:ident: $type:ty),* $(,)?
+    }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq)]
+        $vis struct $name {
+            $($(#[$field_meta])* pub $field: $type,)*
+        }
+
+        impl $crate::dieselsqlite::jsonrpc::Params for $name {
+            const FIELDS: &'static [$crate::dieselsqlite::jsonrpc::ParamField] = &[
+                $($crate::dieselsqlite::jsonrpc::ParamField {
+                    name: stringify!($field),
+                    type_name: stringify!($type),
+                    required: !<$type as $crate::dieselsqlite::openrpc::Schema>::OPTIONAL,
+                    schema: <$type as $crate::dieselsqlite::openrpc::Schema>::schema,
+                },)*
+            ];
+
+            #[allow(unused_variables, unused_mut)]
+            fn from_named(
+                values: &mut $crate::dieselsqlite::jsonrpc::NamedParams,
+            ) -> Result<Self, $crate::dieselsqlite::jsonrpc::RpcError> {
+                let mut fields = <Self as $crate::dieselsqlite::jsonrpc::Params>::FIELDS.iter();
+                Ok($name {
+                    $($field: $crate::dieselsqlite::jsonrpc::take_param(
+                        values,
+                        fields.next().expect("One field per param"),
+                    )?,)*
+                })
+            }
+        }
+    };
+}
+
+fn response(id: Value, outcome: Result<Value, RpcError>) -> Value {
+    match outcome {
+        Ok(result) => json!({ "jsonrpc": VERSION, "id": id, "result": result }),
+        Err(error) => json!({ "jsonrpc": VERSION, "id": id, "error": error }),
+    }
+}
+
+/// Id of a request, when it has a valid one.
+fn request_id(request: &Map<String, Value>) -> Option<Value> {
+    request
+        .get("id")
+        .filter(|id| id.is_null() || id.is_string() || id.is_number())
+        .cloned()
+}
+
+/// Response to a single request, `None` for a notification (a request
+/// without id).
+fn handle_request<F>(request: Value, call: &mut F) -> Option<Value>
+where
+    F: FnMut(&str, Value) -> Result<Value, RpcError>,
+{
+    let invalid = |id| {
+        Some(response(
+            id,
+            Err(RpcError::new(INVALID_REQUEST, "Invalid request")),
+        ))
+    };
+    let Value::Object(mut request) = request else {
+        return invalid(Value::Null);
+    };
+    let id = request_id(&request);
+    if request.contains_key("id") && id.is_none() {
+        return invalid(Value::Null);
+    }
+    let params = match request.remove("params") {
+        None => Value::Null,
+        Some(params @ (Value::Array(_) | Value::Object(_))) => params,
+        Some(_) => return invalid(id.unwrap_or_default()),
+    };
+    let (Some(Value::String(method)), Some(VERSION)) = (
+        request.get("method"),
+        request.get("jsonrpc").and_then(Value::as_str),
+    ) else {
+        return invalid(id.unwrap_or_default());
+    };
+    let outcome = call(method, params);
+    id.map(|id| response(id, outcome))
+}
+
+/// Answers a JSON-RPC 2.0 request, or batch of requests, calling `call` with
+/// the method and params of each. Returns `None` when there is nothing to
+/// answer, i.e. the body only has notifications.
+pub fn handle<F>(body: &[u8], mut call: F) -> Option<Value>
+where
+    F: FnMut(&str, Value) -> Result<Value, RpcError>,
+{
+    let body = match serde_json::from_slice::<Value>(body) {
+        Ok(body) => body,
+        Err(error) => {
+            let error = RpcError::new(PARSE_ERROR, format!("Parse error:{}", error));
+            return Some(response(Value::Null, Err(error)));
+        }
+    };
+    match body {
+        Value::Array(requests) if requests.is_empty() => Some(response(
+            Value::Null,
+            Err(RpcError::new(INVALID_REQUEST, "Empty batch")),
+        )),
+        Value::Array(requests) => {
+            let responses = requests
+                .into_iter()
+                .filter_map(|request| handle_request(request, &mut call))
+                .collect::<Vec<Value>>();
+            (!responses.is_empty()).then_some(Value::Array(responses))
+        }
+        request => handle_request(request, &mut call),
+    }
+}
+
+#[cfg(test)]
+mod jsonrpc_test {
+    use super::*;
+    use crate::dieselsqlite::types::BlockHash;
+
+    crate::rpc_params! {
+        struct RangeParams {
+            lowerlevel: i32,
+            upperlevel: Option<i32>,
+            hash: BlockHash,
+        }
+    }
+
+    crate::rpc_params! {
+        struct NoParams {}
+    }
+
+    fn call(method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "echo" => Ok(params),
+            "missing" => Err(StoreError::NotFound.into()),
+            _ => Err(RpcError::method_not_found(method)),
+        }
+    }
+
+    fn handle_str(body: &str) -> Option<Value> {
+        handle(body.as_bytes(), call)
+    }
+
+    #[test]
+    fn test_handle() {
+        assert_eq!(
+            handle_str(r#"{"jsonrpc":"2.0","id":1,"method":"echo","params":[1,"a"]}"#),
+            Some(json!({"jsonrpc":"2.0","id":1,"result":[1,"a"]}))
+        );
+        assert_eq!(
+            handle_str(r#"{"jsonrpc":"2.0","id":"a","method":"missing"}"#),
+            Some(
+                json!({"jsonrpc":"2.0","id":"a","error":{"code":NOT_FOUND,"message":"Not found"}})
+            )
+        );
+        assert_eq!(
+            handle_str(r#"{"jsonrpc":"2.0","method":"echo","params":[]}"#),
+            None
+        );
+        assert_eq!(
+            handle_str(r#"{"jsonrpc":"2.0","id":2,"method":"other"}"#).unwrap()["error"]["code"],
+            METHOD_NOT_FOUND
+        );
+        assert_eq!(
+            handle_str(r#"{"jsonrpc":"2.0","id":2"#).unwrap()["error"]["code"],
+            PARSE_ERROR
+        );
+
+        let invalid = [
+            r#"1"#,
+            r#"[]"#,
+            r#"{"id":3,"method":"echo"}"#,
+            r#"{"jsonrpc":"2.0","id":3,"method":1}"#,
+            r#"{"jsonrpc":"2.0","id":3,"method":"echo","params":1}"#,
+            r#"{"jsonrpc":"2.0","id":[3],"method":"echo"}"#,
+        ];
+        for body in invalid {
+            assert_eq!(
+                handle_str(body).unwrap()["error"]["code"],
+                INVALID_REQUEST,
+                "{}",
+                body
+            );
+        }
+        assert_eq!(
+            handle_str(r#"{"jsonrpc":"2.0","id":3,"method":"echo","params":1}"#).unwrap()["id"],
+            3
+        );
+    }
+
+    #[test]
+    fn test_parse_params() {
+        let hash = BlockHash([1u8; 32]);
+        let expected = RangeParams {
+            lowerlevel: 1,
+            upperlevel: Some(2),
+            hash,
+        };
+
+        assert_eq!(
+            RangeParams::FIELDS
+                .iter()
+                .map(|field| field.type_name)
+                .collect::<Vec<_>>(),
+            vec!["i32", "Option<i32>", "BlockHash"]
+        );
+        assert_eq!(parse_params(json!([1, 2, hash])), Ok(expected.clone()));
+        assert_eq!(
+            parse_params(json!({"hash": hash, "upperlevel": 2, "lowerlevel": 1})),
+            Ok(expected)
+        );
+        assert_eq!(
+            parse_params(json!({"hash": hash, "lowerlevel": 1})),
+            Ok(RangeParams {
+                lowerlevel: 1,
+                upperlevel: None,
+                hash,
+            })
+        );
+        assert_eq!(parse_params(Value::Null), Ok(NoParams {}));
+        assert_eq!(parse_params(json!([])), Ok(NoParams {}));
+
+        let error = parse_params::<RangeParams>(json!([1])).unwrap_err();
+
+        assert_eq!(error.code, INVALID_PARAMS);
+        assert_eq!(error.message, "Invalid parameters:missing hash (BlockHash)");
+        assert_eq!(
+            error.data,
+            Some(json!({"field": "hash", "expected": "BlockHash"}))
+        );
+
+        let error = parse_params::<RangeParams>(json!({"lowerlevel": "1"})).unwrap_err();
+
+        assert_eq!(
+            error.message,
+            "Invalid parameters:lowerlevel (i32):invalid type: string \"1\", expected i32"
+        );
+        assert_eq!(
+            parse_params::<RangeParams>(json!([1, 2, "0x01"]))
+                .unwrap_err()
+                .data,
+            Some(json!({"field": "hash", "expected": "BlockHash"}))
+        );
+        assert_eq!(
+            parse_params::<RangeParams>(json!([1, 2, hash, 4]))
+                .unwrap_err()
+                .message,
+            "Invalid parameters:expected at most 3 params, found 4"
+        );
+        assert_eq!(
+            parse_params::<NoParams>(json!({"level": 1}))
+                .unwrap_err()
+                .message,
+            "Invalid parameters:unknown param level"
+        );
+    }
+
+    #[test]
+    fn test_handle_batch() {
+        let responses = handle_str(
+            r#"[
+                {"jsonrpc":"2.0","id":1,"method":"echo","params":{"a":1}},
+                {"jsonrpc":"2.0","method":"echo"},
+                2,
+                {"jsonrpc":"2.0","id":null,"method":"missing"}
+            ]"#,
+        );
+
+        assert_eq!(
+            responses,
+            Some(json!([
+                {"jsonrpc":"2.0","id":1,"result":{"a":1}},
+                {"jsonrpc":"2.0","id":null,"error":{"code":INVALID_REQUEST,"message":"Invalid request"}},
+                {"jsonrpc":"2.0","id":null,"error":{"code":NOT_FOUND,"message":"Not found"}},
+            ]))
+        );
+        assert_eq!(
+            handle_str(r#"[{"jsonrpc":"2.0","method":"echo"},{"jsonrpc":"2.0","method":"other"}]"#),
+            None
+        );
+    }
+}
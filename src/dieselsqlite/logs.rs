@@ -0,0 +1,273 @@
+use crate::dieselsqlite::{
+    decoder::{EthBlock, Log, TransactionReceipt, bloom_contains},
+    error::{StoreError, StoreResult},
+    level_batches,
+    models::{Block, Transaction},
+    sidecar, topicindex,
+    types::{Address, BlockHash, Bloom, H256, L2Level, TxHash},
+};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Number of blocks whose header is checked at once against the filter.
+const BATCH_SIZE: i32 = 1000;
+
+crate::rpc_object! {
+    /// Logs to search, with the semantics of `eth_getLogs`: a log matches when it
+    /// was emitted by one of the `addresses` (any address when empty) and, for
+    /// each position of `topics`, its topic at that position is one of the given
+    /// ones. A `null` or empty position matches any topic.
+    #[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+    pub struct LogFilter {
+        pub from_block: L2Level,
+        pub to_block: L2Level,
+        #[serde(default)]
+        pub addresses: Vec<Address>,
+        #[serde(default)]
+        pub topics: Vec<Option<Vec<H256>>>,
+    }
+}
+
+impl LogFilter {
+    /// Topics required at each position, skipping the wildcards.
+    fn topic_sets(&self) -> impl Iterator<Item = (usize, &Vec<H256>)> {
+        self.topics
+            .iter()
+            .enumerate()
+            .filter_map(|(position, topics)| match topics {
+                Some(topics) if !topics.is_empty() => Some((position, topics)),
+                _ => None,
+            })
+    }
+
+    pub fn matches(&self, log: &Log) -> bool {
+        (self.addresses.is_empty() || self.addresses.contains(&log.address))
+            && self
+                .topic_sets()
+                .all(|(position, topics)| matches!(log.topics.get(position), Some(topic) if topics.contains(topic)))
+    }
+
+    /// Whether a block with this logs bloom may have matching logs.
+    pub fn may_match(&self, bloom: &Bloom) -> bool {
+        (self.addresses.is_empty()
+            || self
+                .addresses
+                .iter()
+                .any(|address| bloom_contains(bloom, address.as_bytes())))
+            && self.topic_sets().all(|(_, topics)| {
+                topics
+                    .iter()
+                    .any(|topic| bloom_contains(bloom, topic.as_bytes()))
+            })
+    }
+}
+
+crate::rpc_object! {
+    /// Log matching a filter, with its position in the chain. `log_index` is the
+    /// position of the log among the logs of its block.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct FilteredLog {
+        #[serde(flatten)]
+        pub log: Log,
+        pub block_number: L2Level,
+        pub block_hash: BlockHash,
+        pub transaction_hash: TxHash,
+        pub transaction_index: i32,
+        pub log_index: usize,
+    }
+}
+
+fn push_matching_logs(
+    filter: &LogFilter,
+    receipts: &[TransactionReceipt],
+    limit: usize,
+    logs: &mut Vec<FilteredLog>,
+) -> StoreResult<()> {
+    let block_logs = receipts
+        .iter()
+        .flat_map(|receipt| receipt.fields.logs.iter().map(move |log| (receipt, log)));
+    for (log_index, (receipt, log)) in block_logs.enumerate() {
+        if !filter.matches(log) {
+            continue;
+        }
+        if logs.len() == limit {
+            return Err(StoreError::InvalidInput {
+                message: format!("Query returns more than {} logs", limit),
+            });
+        }
+        logs.push(FilteredLog {
+            log: log.clone(),
+            block_number: receipt.block_number,
+            block_hash: receipt.block_hash,
+            transaction_hash: receipt.hash,
+            transaction_index: receipt.transaction_index,
+            log_index,
+        });
+    }
+    Ok(())
+}
+
+/// Logs of the store matching `filter`, in chain order, failing when there
+/// are more than `limit`.
+///
+/// Blocks whose logs bloom rules the filter out are skipped. When the filter
+/// has topics and a `topic_index` matching the store is given, the blocks it
+/// covers are looked up in the index instead.
+pub fn select_logs(
+    store: &mut SqliteConnection,
+    topic_index: Option<&mut SqliteConnection>,
+    filter: &LogFilter,
+    limit: usize,
+) -> StoreResult<Vec<FilteredLog>> {
+    let mut logs = Vec::new();
+    let mut scan_from = filter.from_block;
+
+    if let Some(index) = topic_index.filter(|_| filter.topic_sets().next().is_some())
+        && let Some(indexed_level) = sidecar::valid_indexed_level(index, store)?
+        && indexed_level >= filter.from_block
+    {
+        let upper_level = indexed_level.min(filter.to_block);
+        let levels =
+            topicindex::candidate_levels(index, &filter.topics, filter.from_block, upper_level)?;
+        for level in levels {
+            let receipts = Transaction::select_receipts_from_block_number(store, level)?;
+            push_matching_logs(filter, &receipts, limit, &mut logs)?;
+        }
+        scan_from = upper_level + 1;
+    }
+
+    for (batch_start, batch_end) in level_batches(scan_from, filter.to_block, BATCH_SIZE) {
+        for (level, _, block) in Block::select_range(store, batch_start, batch_end)? {
+            let block = EthBlock::decode(block.as_bytes())?;
+            if filter.may_match(&block.logs_bloom) {
+                let receipts = Transaction::select_receipts_from_block_number(store, level)?;
+                push_matching_logs(filter, &receipts, limit, &mut logs)?;
+            }
+        }
+    }
+    Ok(logs)
+}
+
+#[cfg(test)]
+mod logs_test {
+    use super::*;
+    use crate::dieselsqlite::{
+        fixture::fixture_store,
+        topicindex::{create_topic_index, update_topic_index},
+    };
+
+    /// Matching logs found by going through every receipt.
+    fn expected_logs(store: &mut SqliteConnection, filter: &LogFilter) -> Vec<FilteredLog> {
+        let mut logs = Vec::new();
+        for level in (filter.from_block.0..=filter.to_block.0).map(L2Level) {
+            let receipts = Transaction::select_receipts_from_block_number(store, level).unwrap();
+            push_matching_logs(filter, &receipts, usize::MAX, &mut logs).unwrap();
+        }
+        logs
+    }
+
+    #[test]
+    fn test_select_logs() {
+        let (mut store, config) = fixture_store(30, 4);
+        let store = &mut store;
+        let start_level = config.start_level;
+        let top_level = config.top_level();
+
+        let all_logs = expected_logs(
+            store,
+            &LogFilter {
+                from_block: start_level,
+                to_block: top_level,
+                ..LogFilter::default()
+            },
+        );
+        let with_topics = all_logs
+            .iter()
+            .filter(|log| log.log.topics.len() >= 2)
+            .collect::<Vec<&FilteredLog>>();
+        let (first, second) = (&with_topics[0].log, &with_topics[with_topics.len() - 1].log);
+
+        let filters = [
+            LogFilter {
+                addresses: vec![first.address],
+                ..LogFilter::default()
+            },
+            LogFilter {
+                addresses: vec![first.address, second.address],
+                ..LogFilter::default()
+            },
+            LogFilter {
+                topics: vec![Some(vec![first.topics[0], second.topics[0]])],
+                ..LogFilter::default()
+            },
+            LogFilter {
+                topics: vec![Some(vec![first.topics[0]]), Some(vec![first.topics[1]])],
+                ..LogFilter::default()
+            },
+            LogFilter {
+                topics: vec![Some(vec![first.topics[0]]), Some(vec![second.topics[1]])],
+                ..LogFilter::default()
+            },
+            LogFilter {
+                topics: vec![None, Some(vec![first.topics[1]])],
+                ..LogFilter::default()
+            },
+            LogFilter {
+                topics: vec![Some(vec![first.topics[1]])],
+                ..LogFilter::default()
+            },
+            LogFilter {
+                addresses: vec![second.address],
+                topics: vec![Some(Vec::new()), Some(vec![second.topics[1]])],
+                ..LogFilter::default()
+            },
+        ]
+        .map(|filter| LogFilter {
+            from_block: start_level,
+            to_block: top_level,
+            ..filter
+        });
+        let counts = filters
+            .iter()
+            .map(|filter| select_logs(store, None, filter, 1000).unwrap().len())
+            .collect::<Vec<usize>>();
+
+        assert_eq!(counts, vec![1, 2, 2, 1, 0, 1, 0, 1]);
+
+        let index = &mut create_topic_index(":memory:").unwrap();
+        update_topic_index(index, store, Some(start_level + 15)).unwrap();
+
+        for filter in &filters {
+            let expected = expected_logs(store, filter);
+
+            assert_eq!(select_logs(store, None, filter, 1000).unwrap(), expected);
+            assert_eq!(
+                select_logs(store, Some(index), filter, 1000).unwrap(),
+                expected
+            );
+        }
+
+        let update = update_topic_index(index, store, None).unwrap();
+
+        assert_eq!(update.indexed_level, Some(top_level));
+        assert_eq!(
+            select_logs(store, Some(index), &filters[3], 1000).unwrap()[0].log,
+            *first
+        );
+
+        let filter = LogFilter {
+            from_block: start_level,
+            to_block: top_level,
+            ..LogFilter::default()
+        };
+
+        assert_eq!(
+            select_logs(store, None, &filter, 1000).unwrap().len(),
+            all_logs.len()
+        );
+        assert!(matches!(
+            select_logs(store, None, &filter, all_logs.len() - 1),
+            Err(StoreError::InvalidInput { .. })
+        ));
+    }
+}
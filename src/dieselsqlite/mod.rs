@@ -1,37 +1,137 @@
 use diesel::prelude::*;
 use dotenvy::dotenv;
-use std::env;
+use std::{
+    env,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
 
-use crate::dieselsqlite::models::Block;
+use crate::dieselsqlite::{
+    error::StoreError,
+    models::Block,
+    store::{Store, StoreConfig},
+    types::L2Level,
+};
 
+pub mod accountindex;
+pub mod applyblueprint;
+pub mod bootstrap;
+pub mod connection;
+pub mod decoder;
+pub mod error;
+pub mod ethrpc;
+pub mod fixture;
+pub mod fsck;
+pub mod jsonrpc;
+pub mod logs;
 pub mod models;
+pub mod openrpc;
+pub mod pool;
+pub mod prune;
+pub mod reset;
+#[rustfmt::skip]
 pub mod schema;
+pub mod server;
+pub mod sidecar;
+pub mod store;
+pub mod topicindex;
+pub mod types;
+pub mod verify;
 
 pub const DATABASE_URL_KEY: &str = "DATABASE_URL";
 pub const BLOCK_NUMBER_KEY: &str = "BLOCK_NUMBER";
 
+/// Opens the store described by the environment, see `StoreConfig::from_env`.
 pub fn establish_connection() -> ConnectionResult<SqliteConnection> {
-    let database_url = &load_database_url()?;
+    let config = StoreConfig::from_env()?;
 
-    SqliteConnection::establish(database_url)
+    Ok(Store::open(config)?.into_connection())
 }
 
-fn load_database_url() -> ConnectionResult<String> {
-    dotenv().ok();
-    env::var(DATABASE_URL_KEY).map_err(|_| {
-        ConnectionError::InvalidConnectionUrl("Database URL wasn't provided".to_string())
+/// Opens the store read-only, so it can be read while the node is running.
+pub fn establish_read_only_connection() -> ConnectionResult<SqliteConnection> {
+    let config = StoreConfig::from_env()?.into_read_only();
+
+    Ok(Store::open(config)?.into_connection())
+}
+
+/// Optional command line argument of a binary, panicking with its `name`
+/// when it doesn't parse.
+pub fn parse_argument<T: FromStr>(argument: Option<String>, name: &str) -> Option<T> {
+    argument.map(|argument| {
+        argument
+            .parse()
+            .unwrap_or_else(|_| panic!("Provide a valid {name}"))
     })
 }
 
+/// Consecutive ranges of at most `batch_size` levels, covering the levels
+/// from `lowerlevel` to `upperlevel`.
+pub(crate) fn level_batches(
+    lowerlevel: L2Level,
+    upperlevel: L2Level,
+    batch_size: i32,
+) -> impl Iterator<Item = (L2Level, L2Level)> {
+    (i64::from(lowerlevel.0)..=i64::from(upperlevel.0))
+        .step_by(batch_size as usize)
+        .map(move |start| {
+            let end = (start + i64::from(batch_size) - 1).min(i64::from(upperlevel.0));
+            (L2Level(start as i32), L2Level(end as i32))
+        })
+}
+
 //For benchmarking
 
-pub fn load_block_number() -> i32 {
+/// Errors of `load_block_number`.
+#[derive(Debug)]
+pub enum BlockNumberError {
+    InvalidBlockNumber { block_number: String },
+    ConnectionError { error: ConnectionError },
+    StoreError { error: StoreError },
+}
+
+impl Display for BlockNumberError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let string = match self {
+            BlockNumberError::InvalidBlockNumber { block_number } => {
+                format!("Invalid {}:{}", BLOCK_NUMBER_KEY, block_number)
+            }
+            BlockNumberError::ConnectionError { error } => {
+                format!("Error connecting to the database:{}", error)
+            }
+            BlockNumberError::StoreError { error } => {
+                format!("Can't read the top level of the store:{}", error)
+            }
+        };
+        write!(f, "{}", string)
+    }
+}
+
+impl std::error::Error for BlockNumberError {}
+
+impl From<ConnectionError> for BlockNumberError {
+    fn from(error: ConnectionError) -> BlockNumberError {
+        BlockNumberError::ConnectionError { error }
+    }
+}
+
+impl From<StoreError> for BlockNumberError {
+    fn from(error: StoreError) -> BlockNumberError {
+        BlockNumberError::StoreError { error }
+    }
+}
+
+/// Level given by `BLOCK_NUMBER`, or the top level of the store.
+pub fn load_block_number() -> Result<L2Level, BlockNumberError> {
     dotenv().ok();
-    env::var(BLOCK_NUMBER_KEY)
-        .map(|s| s.parse::<i32>().expect("Provide a valid block number"))
-        .unwrap_or_else(|_| {
-            let connection = &mut establish_connection().expect("Provide a valid database URL");
-            Block::top_level(connection)
-                .expect("Can't obtain default block value (top level block)")
-        })
+    match env::var(BLOCK_NUMBER_KEY) {
+        Ok(block_number) => block_number
+            .parse()
+            .map(L2Level)
+            .map_err(|_| BlockNumberError::InvalidBlockNumber { block_number }),
+        Err(_) => {
+            let connection = &mut establish_connection()?;
+            Ok(Block::top_level(connection)?)
+        }
+    }
 }
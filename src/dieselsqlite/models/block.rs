@@ -1,6 +1,9 @@
 use crate::dieselsqlite::{
+    decoder::EthBlock,
+    error::StoreResult,
     models::cast_hash_comparison,
     schema::{blocks, blocks::dsl::*},
+    types::{BlockHash, Bytes, L2Level},
 };
 use diesel::{dsl::*, prelude::*};
 
@@ -8,21 +11,21 @@ use diesel::{dsl::*, prelude::*};
 #[diesel(table_name = blocks)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct Block {
-    pub level: i32,
-    pub hash: Vec<u8>,
-    pub block: Vec<u8>,
+    pub level: L2Level,
+    pub hash: BlockHash,
+    pub block: Bytes,
 }
 
 impl Block {
-    pub fn insert(self, connection: &mut SqliteConnection) -> QueryResult<usize> {
+    pub fn insert(self, connection: &mut SqliteConnection) -> StoreResult<usize> {
         let inserted_rows = self.insert_into(blocks).execute(connection)?;
         Ok(inserted_rows)
     }
 
     pub fn select_with_level(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<Vec<u8>> {
+        queried_level: L2Level,
+    ) -> StoreResult<Bytes> {
         let b = blocks
             .find(queried_level)
             .select(block)
@@ -32,8 +35,8 @@ impl Block {
 
     pub fn select_with_hash(
         connection: &mut SqliteConnection,
-        queried_hash: &Vec<u8>,
-    ) -> QueryResult<Vec<u8>> {
+        queried_hash: &BlockHash,
+    ) -> StoreResult<Bytes> {
         let b = blocks
             .filter(cast_hash_comparison(queried_hash))
             .select(block)
@@ -42,10 +45,26 @@ impl Block {
         Ok(b)
     }
 
+    pub fn select_decoded_with_level(
+        connection: &mut SqliteConnection,
+        queried_level: L2Level,
+    ) -> StoreResult<EthBlock> {
+        let b = Block::select_with_level(connection, queried_level)?;
+        Ok(EthBlock::decode(b.as_bytes())?)
+    }
+
+    pub fn select_decoded_with_hash(
+        connection: &mut SqliteConnection,
+        queried_hash: &BlockHash,
+    ) -> StoreResult<EthBlock> {
+        let b = Block::select_with_hash(connection, queried_hash)?;
+        Ok(EthBlock::decode(b.as_bytes())?)
+    }
+
     pub fn select_hash_of_number(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<Vec<u8>> {
+        queried_level: L2Level,
+    ) -> StoreResult<BlockHash> {
         let h = blocks
             .find(queried_level)
             .select(hash)
@@ -53,10 +72,37 @@ impl Block {
         Ok(h)
     }
 
+    /// Level, hash and block of the blocks in the range.
+    pub fn select_range(
+        connection: &mut SqliteConnection,
+        lowerlevel: L2Level,
+        upperlevel: L2Level,
+    ) -> StoreResult<Vec<(L2Level, BlockHash, Bytes)>> {
+        let rows = blocks
+            .filter(level.between(lowerlevel, upperlevel))
+            .order(level.asc())
+            .select((level, hash, block))
+            .load(connection)?;
+        Ok(rows)
+    }
+
+    pub fn select_hashes_range(
+        connection: &mut SqliteConnection,
+        lowerlevel: L2Level,
+        upperlevel: L2Level,
+    ) -> StoreResult<Vec<(L2Level, BlockHash)>> {
+        let hashes = blocks
+            .filter(level.between(lowerlevel, upperlevel))
+            .order(level.asc())
+            .select((level, hash))
+            .load(connection)?;
+        Ok(hashes)
+    }
+
     pub fn select_number_of_hash(
         connection: &mut SqliteConnection,
-        queried_hash: &Vec<u8>,
-    ) -> QueryResult<i32> {
+        queried_hash: &BlockHash,
+    ) -> StoreResult<L2Level> {
         let n = blocks
             .filter(cast_hash_comparison(queried_hash))
             .select(level)
@@ -66,28 +112,28 @@ impl Block {
 
     pub fn clear_after(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<usize> {
+        queried_level: L2Level,
+    ) -> StoreResult<usize> {
         let cleared_rows = delete(blocks.filter(level.gt(queried_level))).execute(connection)?;
         Ok(cleared_rows)
     }
 
     pub fn clear_before(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<usize> {
+        queried_level: L2Level,
+    ) -> StoreResult<usize> {
         let cleared_rows = delete(blocks.filter(level.lt(queried_level))).execute(connection)?;
         Ok(cleared_rows)
     }
 
     //For testing
 
-    pub fn count(connection: &mut SqliteConnection) -> QueryResult<i64> {
+    pub fn count(connection: &mut SqliteConnection) -> StoreResult<i64> {
         let count = blocks.select(count(level)).first(connection)?;
         Ok(count)
     }
 
-    pub fn base_level(connection: &mut SqliteConnection) -> QueryResult<i32> {
+    pub fn base_level(connection: &mut SqliteConnection) -> StoreResult<L2Level> {
         let base_level = blocks
             .select(level)
             .order(level.asc())
@@ -96,7 +142,7 @@ impl Block {
         Ok(base_level)
     }
 
-    pub fn top_level(connection: &mut SqliteConnection) -> QueryResult<i32> {
+    pub fn top_level(connection: &mut SqliteConnection) -> StoreResult<L2Level> {
         let base_level = blocks
             .select(level)
             .order(level.desc())
@@ -109,20 +155,26 @@ impl Block {
 #[cfg(test)]
 mod block_test {
     use super::*;
-    use crate::dieselsqlite::establish_connection;
-    use diesel::result::Error;
+    use crate::dieselsqlite::{
+        bootstrap::create_store,
+        error::StoreError,
+        establish_connection,
+        fixture::{FixtureConfig, generate},
+        models::Transaction,
+        types::TxHash,
+    };
     #[test]
     fn test_block_insert_selects_clearafter() {
         let connection = &mut establish_connection().unwrap();
 
-        connection.test_transaction::<_, Error, _>(|conn| {
-            let inserted_hash = "hash".as_bytes().to_vec();
-            let inserted_block = "block".as_bytes().to_vec();
+        connection.test_transaction::<_, StoreError, _>(|conn| {
+            let inserted_hash = BlockHash([1u8; 32]);
+            let inserted_block = Bytes::from("block".as_bytes());
             let base_insert_index = Block::top_level(conn)?;
 
             let insert_block = Block {
                 level: base_insert_index + 1,
-                hash: inserted_hash.clone(),
+                hash: inserted_hash,
                 block: inserted_block.clone(),
             };
 
@@ -147,11 +199,56 @@ mod block_test {
         })
     }
 
+    #[test]
+    fn test_block_select_decoded() {
+        let connection = &mut create_store(":memory:").unwrap();
+        let config = FixtureConfig {
+            levels: 3,
+            transactions_per_block: 2,
+            ..FixtureConfig::default()
+        };
+        generate(connection, &config).unwrap();
+        let select_level = config.start_level + 1;
+
+        let decoded = Block::select_decoded_with_level(connection, select_level).unwrap();
+        let hash_of_number = Block::select_hash_of_number(connection, select_level).unwrap();
+        let transactions = Transaction::select_receipts_from_block_number(connection, select_level)
+            .unwrap()
+            .into_iter()
+            .map(|receipt| receipt.hash)
+            .collect::<Vec<TxHash>>();
+
+        assert_eq!(decoded.number, select_level.0 as u64);
+        assert_eq!(decoded.hash, hash_of_number);
+        assert_eq!(
+            decoded.parent_hash,
+            Block::select_hash_of_number(connection, select_level - 1).unwrap()
+        );
+        assert_eq!(decoded.transactions, transactions);
+        assert_eq!(
+            Block::select_decoded_with_hash(connection, &hash_of_number).unwrap(),
+            decoded
+        );
+
+        Block {
+            level: select_level + 10,
+            hash: BlockHash([1u8; 32]),
+            block: Bytes::from("block".as_bytes()),
+        }
+        .insert(connection)
+        .unwrap();
+
+        assert!(matches!(
+            Block::select_decoded_with_level(connection, select_level + 10),
+            Err(StoreError::Decode { .. })
+        ));
+    }
+
     #[test]
     fn test_block_selects() {
         let connection = &mut establish_connection().unwrap();
 
-        connection.test_transaction::<_, Error, _>(|conn| {
+        connection.test_transaction::<_, StoreError, _>(|conn| {
             let select_index = Block::top_level(conn)?;
 
             let block_from_level = Block::select_with_level(conn, select_index)?;
@@ -1,4 +1,7 @@
-use crate::dieselsqlite::schema::{block_storage_mode, block_storage_mode::dsl::*};
+use crate::dieselsqlite::{
+    error::StoreResult,
+    schema::{block_storage_mode, block_storage_mode::dsl::*},
+};
 use diesel::{dsl::*, prelude::*};
 
 #[derive(Queryable, Selectable, Insertable)]
@@ -9,12 +12,19 @@ pub struct BlockStorageMode {
 }
 
 impl BlockStorageMode {
-    pub fn legacy(connection: &mut SqliteConnection) -> QueryResult<i32> {
+    pub fn insert(self, connection: &mut SqliteConnection) -> StoreResult<usize> {
+        let inserted_rows = insert_into(block_storage_mode)
+            .values(&self)
+            .execute(connection)?;
+        Ok(inserted_rows)
+    }
+
+    pub fn legacy(connection: &mut SqliteConnection) -> StoreResult<i32> {
         let leg = block_storage_mode.select(legacy).get_result(connection)?;
         Ok(leg)
     }
 
-    pub fn force_legacy(connection: &mut SqliteConnection) -> QueryResult<usize> {
+    pub fn force_legacy(connection: &mut SqliteConnection) -> StoreResult<usize> {
         let updated_rows = update(block_storage_mode)
             .set(legacy.eq(1))
             .execute(connection)?;
@@ -25,14 +35,13 @@ impl BlockStorageMode {
 #[cfg(test)]
 mod block_storage_mode_test {
     use super::*;
-    use crate::dieselsqlite::establish_connection;
-    use diesel::result::Error;
+    use crate::dieselsqlite::{error::StoreError, establish_connection};
 
     #[test]
     fn test_block_storage_mode() {
         let connection = &mut establish_connection().unwrap();
 
-        connection.test_transaction::<_, Error, _>(|conn| {
+        connection.test_transaction::<_, StoreError, _>(|conn| {
             let mut expected_legacy = 0;
 
             let current_legacy = BlockStorageMode::legacy(conn)?;
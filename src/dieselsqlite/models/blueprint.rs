@@ -1,20 +1,25 @@
-use crate::dieselsqlite::schema::{blueprints, blueprints::dsl::*};
+use crate::dieselsqlite::{
+    decoder::SequencerBlueprint,
+    error::StoreResult,
+    schema::{blueprints, blueprints::dsl::*},
+    types::{Bytes, L2Level},
+};
 use diesel::{dsl::*, prelude::*};
 
 #[derive(Queryable, Selectable, Insertable)]
 #[diesel(table_name = blueprints)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct Blueprint {
-    pub id: i32,
-    pub payload: Vec<u8>,
+    pub id: L2Level,
+    pub payload: Bytes,
     pub timestamp: i64,
 }
 
 impl Blueprint {
     pub fn select(
         connection: &mut SqliteConnection,
-        queried_id: i32,
-    ) -> QueryResult<(Vec<u8>, i64)> {
+        queried_id: L2Level,
+    ) -> StoreResult<(Bytes, i64)> {
         let tuple = blueprints
             .find(queried_id)
             .select((payload, timestamp))
@@ -22,16 +27,24 @@ impl Blueprint {
         Ok(tuple)
     }
 
-    pub fn insert(self, connection: &mut SqliteConnection) -> QueryResult<usize> {
+    pub fn select_decoded(
+        connection: &mut SqliteConnection,
+        queried_id: L2Level,
+    ) -> StoreResult<SequencerBlueprint> {
+        let (p, _) = Blueprint::select(connection, queried_id)?;
+        Ok(SequencerBlueprint::decode(p.as_bytes())?)
+    }
+
+    pub fn insert(self, connection: &mut SqliteConnection) -> StoreResult<usize> {
         let inserted_rows = self.insert_into(blueprints).execute(connection)?;
         Ok(inserted_rows)
     }
 
     pub fn select_range(
         connection: &mut SqliteConnection,
-        lowerlevel: i32,
-        upperlevel: i32,
-    ) -> QueryResult<Vec<(i32, Vec<u8>)>> {
+        lowerlevel: L2Level,
+        upperlevel: L2Level,
+    ) -> StoreResult<Vec<(L2Level, Bytes)>> {
         let vec = blueprints
             .filter(id.ge(lowerlevel).and(id.le(upperlevel)))
             .order(id.asc())
@@ -40,23 +53,47 @@ impl Blueprint {
         Ok(vec)
     }
 
-    pub fn clear_after(connection: &mut SqliteConnection, level: i32) -> QueryResult<usize> {
+    pub fn select_timestamps_range(
+        connection: &mut SqliteConnection,
+        lowerlevel: L2Level,
+        upperlevel: L2Level,
+    ) -> StoreResult<Vec<(L2Level, i64)>> {
+        let vec = blueprints
+            .filter(id.ge(lowerlevel).and(id.le(upperlevel)))
+            .order(id.asc())
+            .select((id, timestamp))
+            .load(connection)?;
+        Ok(vec)
+    }
+
+    pub fn select_decoded_range(
+        connection: &mut SqliteConnection,
+        lowerlevel: L2Level,
+        upperlevel: L2Level,
+    ) -> StoreResult<Vec<(L2Level, SequencerBlueprint)>> {
+        Blueprint::select_range(connection, lowerlevel, upperlevel)?
+            .into_iter()
+            .map(|(i, p)| Ok((i, SequencerBlueprint::decode(p.as_bytes())?)))
+            .collect()
+    }
+
+    pub fn clear_after(connection: &mut SqliteConnection, level: L2Level) -> StoreResult<usize> {
         let cleared_rows = delete(blueprints.filter(id.gt(level))).execute(connection)?;
         Ok(cleared_rows)
     }
 
-    pub fn clear_before(connection: &mut SqliteConnection, level: i32) -> QueryResult<usize> {
+    pub fn clear_before(connection: &mut SqliteConnection, level: L2Level) -> StoreResult<usize> {
         let cleared_rows = delete(blueprints.filter(id.lt(level))).execute(connection)?;
         Ok(cleared_rows)
     }
     //For testing
 
-    pub fn count(connection: &mut SqliteConnection) -> QueryResult<i64> {
+    pub fn count(connection: &mut SqliteConnection) -> StoreResult<i64> {
         let count = blueprints.select(count(id)).first(connection)?;
         Ok(count)
     }
 
-    pub fn base_level(connection: &mut SqliteConnection) -> QueryResult<i32> {
+    pub fn base_level(connection: &mut SqliteConnection) -> StoreResult<L2Level> {
         let base_level = blueprints
             .select(id)
             .order(id.asc())
@@ -65,7 +102,7 @@ impl Blueprint {
         Ok(base_level)
     }
 
-    pub fn top_level(connection: &mut SqliteConnection) -> QueryResult<i32> {
+    pub fn top_level(connection: &mut SqliteConnection) -> StoreResult<L2Level> {
         let top_level = blueprints
             .select(id)
             .order(id.desc())
@@ -78,14 +115,19 @@ impl Blueprint {
 #[cfg(test)]
 mod blueprint_test {
     use super::*;
-    use crate::dieselsqlite::establish_connection;
-    use diesel::result::Error;
+    use crate::dieselsqlite::{
+        bootstrap::create_store,
+        error::StoreError,
+        establish_connection,
+        fixture::{FixtureConfig, generate},
+        models::Block,
+    };
     #[test]
     fn test_blueprint_insert_select_clearafter() {
         let connection = &mut establish_connection().unwrap();
 
-        connection.test_transaction::<_, Error, _>(|conn| {
-            let inserted_payload = "payload".as_bytes().to_vec();
+        connection.test_transaction::<_, StoreError, _>(|conn| {
+            let inserted_payload = Bytes::from("payload".as_bytes());
             let inserted_timestamp = 1000;
             let base_insert_index = Blueprint::top_level(conn)?;
 
@@ -116,13 +158,13 @@ mod blueprint_test {
     fn test_blueprint_insert_selectrange_clearafter() {
         let connection = &mut establish_connection().unwrap();
 
-        connection.test_transaction::<_, Error, _>(|conn| {
+        connection.test_transaction::<_, StoreError, _>(|conn| {
             let inserted_payloads = vec![
-                "payload1".as_bytes().to_vec(),
-                "payload2".as_bytes().to_vec(),
-                "payload3".as_bytes().to_vec(),
+                Bytes::from("payload1".as_bytes()),
+                Bytes::from("payload2".as_bytes()),
+                Bytes::from("payload3".as_bytes()),
             ];
-            let inserted_timestamps = vec![1000, 1001, 1002];
+            let inserted_timestamps = [1000, 1001, 1002];
             let base_insert_index = Blueprint::top_level(conn)?;
 
             let blueprint1 = Blueprint {
@@ -151,7 +193,7 @@ mod blueprint_test {
             ]
             .into_iter()
             .zip(inserted_payloads)
-            .collect::<Vec<(i32, Vec<u8>)>>();
+            .collect::<Vec<(L2Level, Bytes)>>();
             let vector =
                 Blueprint::select_range(conn, base_insert_index + 1, base_insert_index + 3)?;
 
@@ -166,4 +208,52 @@ mod blueprint_test {
             Ok(())
         })
     }
+
+    #[test]
+    fn test_blueprint_select_decoded() {
+        let connection = &mut create_store(":memory:").unwrap();
+        let config = FixtureConfig {
+            levels: 3,
+            transactions_per_block: 40,
+            ..FixtureConfig::default()
+        };
+        generate(connection, &config).unwrap();
+        let start_level = config.start_level;
+
+        let decoded = Blueprint::select_decoded(connection, start_level + 1).unwrap();
+        let block = Block::select_decoded_with_level(connection, start_level + 1).unwrap();
+
+        assert_eq!(decoded.number, (start_level + 1).0 as u64);
+        assert!(decoded.chunks.len() > 1);
+        assert_eq!(decoded.content.transactions.len(), 40);
+        assert!(decoded.mismatches(&block).is_empty());
+
+        let range = Blueprint::select_decoded_range(connection, start_level, start_level + 2)
+            .unwrap()
+            .into_iter()
+            .map(|(i, blueprint)| (i, blueprint.number))
+            .collect::<Vec<(L2Level, u64)>>();
+
+        assert_eq!(
+            range,
+            (start_level.0..=start_level.0 + 2)
+                .map(|i| (L2Level(i), i as u64))
+                .collect::<Vec<(L2Level, u64)>>()
+        );
+
+        let (_, select_timestamp) = Blueprint::select(connection, start_level).unwrap();
+        Blueprint::clear_after(connection, start_level - 1).unwrap();
+        Blueprint {
+            id: start_level,
+            payload: Bytes::from("payload".as_bytes()),
+            timestamp: select_timestamp,
+        }
+        .insert(connection)
+        .unwrap();
+
+        assert!(matches!(
+            Blueprint::select_decoded(connection, start_level),
+            Err(StoreError::Decode { .. })
+        ));
+    }
 }
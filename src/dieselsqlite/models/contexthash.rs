@@ -1,23 +1,30 @@
-use crate::dieselsqlite::schema::{context_hashes, context_hashes::dsl::*};
+use crate::dieselsqlite::{
+    error::StoreResult,
+    schema::{context_hashes, context_hashes::dsl::*},
+    types::{self, L2Level},
+};
 use diesel::{dsl::*, prelude::*};
 
 #[derive(Queryable, Selectable, Insertable)]
 #[diesel(table_name = context_hashes)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
-pub struct ContextHash {
-    pub id: i32,
-    pub context_hash: Vec<u8>,
+pub struct ContextHashRow {
+    pub id: L2Level,
+    pub context_hash: types::ContextHash,
 }
 
-impl ContextHash {
-    pub fn insert(self, connection: &mut SqliteConnection) -> QueryResult<usize> {
+impl ContextHashRow {
+    pub fn insert(self, connection: &mut SqliteConnection) -> StoreResult<usize> {
         let inserted_rows = replace_into(context_hashes)
             .values(&self)
             .execute(connection)?;
         Ok(inserted_rows)
     }
 
-    pub fn select(connection: &mut SqliteConnection, queried_id: i32) -> QueryResult<Vec<u8>> {
+    pub fn select(
+        connection: &mut SqliteConnection,
+        queried_id: L2Level,
+    ) -> StoreResult<types::ContextHash> {
         let hash = context_hashes
             .find(queried_id)
             .select(context_hash)
@@ -25,7 +32,22 @@ impl ContextHash {
         Ok(hash)
     }
 
-    pub fn get_latest(connection: &mut SqliteConnection) -> QueryResult<(i32, Vec<u8>)> {
+    pub fn select_ids_range(
+        connection: &mut SqliteConnection,
+        lowerlevel: L2Level,
+        upperlevel: L2Level,
+    ) -> StoreResult<Vec<L2Level>> {
+        let ids = context_hashes
+            .filter(id.between(lowerlevel, upperlevel))
+            .order(id.asc())
+            .select(id)
+            .load(connection)?;
+        Ok(ids)
+    }
+
+    pub fn get_latest(
+        connection: &mut SqliteConnection,
+    ) -> StoreResult<(L2Level, types::ContextHash)> {
         let latest_context = context_hashes
             .select((id, context_hash))
             .order(id.desc())
@@ -34,9 +56,11 @@ impl ContextHash {
         Ok(latest_context)
     }
 
-    pub fn get_earliest(connection: &mut SqliteConnection) -> QueryResult<(i32, Vec<u8>)> {
+    pub fn get_earliest(
+        connection: &mut SqliteConnection,
+    ) -> StoreResult<(L2Level, types::ContextHash)> {
         let earliest_context = context_hashes
-            .filter(id.ge(0))
+            .filter(id.ge(L2Level(0)))
             .select((id, context_hash))
             .order(id.asc())
             .limit(1)
@@ -44,12 +68,18 @@ impl ContextHash {
         Ok(earliest_context)
     }
 
-    pub fn clear_after(connection: &mut SqliteConnection, queried_id: i32) -> QueryResult<usize> {
+    pub fn clear_after(
+        connection: &mut SqliteConnection,
+        queried_id: L2Level,
+    ) -> StoreResult<usize> {
         let cleared_rows = delete(context_hashes.filter(id.gt(queried_id))).execute(connection)?;
         Ok(cleared_rows)
     }
 
-    pub fn clear_before(connection: &mut SqliteConnection, queried_id: i32) -> QueryResult<usize> {
+    pub fn clear_before(
+        connection: &mut SqliteConnection,
+        queried_id: L2Level,
+    ) -> StoreResult<usize> {
         let cleared_rows = delete(context_hashes.filter(id.lt(queried_id))).execute(connection)?;
         Ok(cleared_rows)
     }
@@ -58,61 +88,63 @@ impl ContextHash {
 #[cfg(test)]
 mod context_hash_test {
     use super::*;
-    use crate::dieselsqlite::establish_connection;
-    use diesel::result::Error;
+    use crate::dieselsqlite::{error::StoreError, establish_connection};
 
     #[test]
     fn test_context_hash_insert_select_get_clear() {
         let connection = &mut establish_connection().unwrap();
 
-        connection.test_transaction::<_, Error, _>(|conn| {
+        connection.test_transaction::<_, StoreError, _>(|conn| {
             let iter = 10;
             for i in -1..iter {
-                let inserted_level = i;
-                let inserted_hash = format!("hash {i}").as_bytes().to_vec();
+                let inserted_level = L2Level(i);
+                let inserted_hash = types::ContextHash([i as u8; 32]);
 
-                let contexthash = ContextHash {
+                let contexthash = ContextHashRow {
                     id: inserted_level,
-                    context_hash: inserted_hash.clone(),
+                    context_hash: inserted_hash,
                 };
                 contexthash.insert(conn)?;
             }
 
-            let earliest_id = 0;
-            let earliest_hash = "hash 0".as_bytes().to_vec();
+            let earliest_id = L2Level(0);
+            let earliest_hash = types::ContextHash([0u8; 32]);
 
-            let _ = ContextHash::clear_after(conn, iter)?;
+            let _ = ContextHashRow::clear_after(conn, L2Level(iter))?;
 
             let expected_earliest = (earliest_id, earliest_hash);
 
-            let earliest = ContextHash::get_earliest(conn)?;
+            let earliest = ContextHashRow::get_earliest(conn)?;
 
             assert_eq!(earliest, expected_earliest);
 
-            let new_earliest_hash = "Replaced hash".as_bytes().to_vec();
+            let new_earliest_hash = types::ContextHash([0xffu8; 32]);
 
-            let replace_earliest = ContextHash {
+            let replace_earliest = ContextHashRow {
                 id: earliest_id,
-                context_hash: new_earliest_hash.clone(),
+                context_hash: new_earliest_hash,
             };
 
             replace_earliest.insert(conn)?;
 
             let new_expected_earliest = (earliest_id, new_earliest_hash);
 
-            let new_earliest = ContextHash::get_earliest(conn)?;
+            let new_earliest = ContextHashRow::get_earliest(conn)?;
 
             assert_eq!(new_earliest, new_expected_earliest);
 
-            let expected_latest = (iter - 1, format!("hash {}", iter - 1).as_bytes().to_vec());
+            let expected_latest = (
+                L2Level(iter - 1),
+                types::ContextHash([(iter - 1) as u8; 32]),
+            );
 
-            let latest = ContextHash::get_latest(conn)?;
+            let latest = ContextHashRow::get_latest(conn)?;
 
             assert_eq!(latest, expected_latest);
 
             let expected_clear: usize = (iter + 1) as usize;
 
-            let clear = ContextHash::clear_before(conn, iter)?;
+            let clear = ContextHashRow::clear_before(conn, L2Level(iter))?;
 
             assert_eq!(clear, expected_clear);
 
@@ -1,6 +1,8 @@
 use crate::dieselsqlite::{
+    error::StoreResult,
     models::cast_hash_comparison,
     schema::{delayed_transactions, delayed_transactions::dsl::*},
+    types::{Bytes, L2Level, TxHash},
 };
 use diesel::{dsl::*, prelude::*};
 
@@ -8,21 +10,21 @@ use diesel::{dsl::*, prelude::*};
 #[diesel(table_name = delayed_transactions)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct DelayedTransaction {
-    pub injected_before: i32,
-    pub hash: Vec<u8>,
-    pub payload: Vec<u8>,
+    pub injected_before: L2Level,
+    pub hash: TxHash,
+    pub payload: Bytes,
 }
 
 impl DelayedTransaction {
-    pub fn insert(self, connection: &mut SqliteConnection) -> QueryResult<usize> {
+    pub fn insert(self, connection: &mut SqliteConnection) -> StoreResult<usize> {
         let inserted_rows = self.insert_into(delayed_transactions).execute(connection)?;
         Ok(inserted_rows)
     }
 
     pub fn select_at_level(
         connection: &mut SqliteConnection,
-        queried_injected_before: i32,
-    ) -> QueryResult<Vec<u8>> {
+        queried_injected_before: L2Level,
+    ) -> StoreResult<Bytes> {
         let p = delayed_transactions
             .filter(injected_before.eq(queried_injected_before))
             .select(payload)
@@ -32,8 +34,8 @@ impl DelayedTransaction {
 
     pub fn select_at_hash(
         connection: &mut SqliteConnection,
-        queried_hash: &Vec<u8>,
-    ) -> QueryResult<Vec<u8>> {
+        queried_hash: &TxHash,
+    ) -> StoreResult<Bytes> {
         let pld = delayed_transactions
             .filter(cast_hash_comparison(queried_hash))
             .select(payload)
@@ -43,8 +45,8 @@ impl DelayedTransaction {
 
     pub fn clear_after(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<usize> {
+        queried_level: L2Level,
+    ) -> StoreResult<usize> {
         let cleared_rows = delete(delayed_transactions.filter(injected_before.gt(queried_level)))
             .execute(connection)?;
         Ok(cleared_rows)
@@ -52,8 +54,8 @@ impl DelayedTransaction {
 
     pub fn clear_before(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<usize> {
+        queried_level: L2Level,
+    ) -> StoreResult<usize> {
         let cleared_rows = delete(delayed_transactions.filter(injected_before.lt(queried_level)))
             .execute(connection)?;
         Ok(cleared_rows)
@@ -63,21 +65,20 @@ impl DelayedTransaction {
 #[cfg(test)]
 mod delayed_transaction_test {
     use super::*;
-    use crate::dieselsqlite::establish_connection;
-    use diesel::result::Error;
+    use crate::dieselsqlite::{error::StoreError, establish_connection};
 
     #[test]
     fn test_delayed_transaction_all() {
         let connection = &mut establish_connection().unwrap();
 
-        connection.test_transaction::<_, Error, _>(|conn| {
-            let inserted_injected_before = 5000;
-            let inserted_hash = "hash".as_bytes().to_vec();
-            let inserted_payload = "payload".as_bytes().to_vec();
+        connection.test_transaction::<_, StoreError, _>(|conn| {
+            let inserted_injected_before = L2Level(5000);
+            let inserted_hash = TxHash([1u8; 32]);
+            let inserted_payload = Bytes::from("payload".as_bytes());
 
             let delayed_transaction = DelayedTransaction {
                 injected_before: inserted_injected_before,
-                hash: inserted_hash.clone(),
+                hash: inserted_hash,
                 payload: inserted_payload.clone(),
             };
 
@@ -1,21 +1,25 @@
-use crate::dieselsqlite::schema::{irmin_chunks, irmin_chunks::dsl::*};
+use crate::dieselsqlite::{
+    error::StoreResult,
+    schema::{irmin_chunks, irmin_chunks::dsl::*},
+    types::L2Level,
+};
 use diesel::{dsl::*, prelude::*};
 
 #[derive(Queryable, Selectable, Insertable)]
 #[diesel(table_name = irmin_chunks)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct IrminChunk {
-    pub level: i32,
+    pub level: L2Level,
     pub timestamp: i64,
 }
 
 impl IrminChunk {
-    pub fn insert(self, connection: &mut SqliteConnection) -> QueryResult<usize> {
+    pub fn insert(self, connection: &mut SqliteConnection) -> StoreResult<usize> {
         let inserted_rows = self.insert_into(irmin_chunks).execute(connection)?;
         Ok(inserted_rows)
     }
 
-    pub fn nth(connection: &mut SqliteConnection, offset: i64) -> QueryResult<(i32, i64)> {
+    pub fn nth(connection: &mut SqliteConnection, offset: i64) -> StoreResult<(L2Level, i64)> {
         let nth = irmin_chunks
             .select((level, timestamp))
             .order_by(level.desc())
@@ -25,7 +29,7 @@ impl IrminChunk {
         Ok(nth)
     }
 
-    pub fn latest(connection: &mut SqliteConnection) -> QueryResult<(i32, i64)> {
+    pub fn latest(connection: &mut SqliteConnection) -> StoreResult<(L2Level, i64)> {
         let latest = irmin_chunks
             .select((level, timestamp))
             .order_by(level.desc())
@@ -34,15 +38,15 @@ impl IrminChunk {
         Ok(latest)
     }
 
-    pub fn clear(connection: &mut SqliteConnection) -> QueryResult<usize> {
+    pub fn clear(connection: &mut SqliteConnection) -> StoreResult<usize> {
         let cleared_rows = delete(irmin_chunks).execute(connection)?;
         Ok(cleared_rows)
     }
 
     pub fn clear_after(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<usize> {
+        queried_level: L2Level,
+    ) -> StoreResult<usize> {
         let cleared_rows =
             delete(irmin_chunks.filter(level.gt(queried_level))).execute(connection)?;
         Ok(cleared_rows)
@@ -50,8 +54,8 @@ impl IrminChunk {
 
     pub fn clear_before_included(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<usize> {
+        queried_level: L2Level,
+    ) -> StoreResult<usize> {
         let cleared_rows =
             delete(irmin_chunks.filter(level.le(queried_level))).execute(connection)?;
         Ok(cleared_rows)
@@ -61,14 +65,13 @@ impl IrminChunk {
 #[cfg(test)]
 mod irmin_chunk_test {
     use super::*;
-    use crate::dieselsqlite::{Block, establish_connection};
-    use diesel::result::Error;
+    use crate::dieselsqlite::{Block, error::StoreError, establish_connection};
 
     #[test]
     fn test_irmin_chunk_all() {
         let connection = &mut establish_connection().unwrap();
 
-        connection.test_transaction::<_, Error, _>(|conn| {
+        connection.test_transaction::<_, StoreError, _>(|conn| {
             IrminChunk::clear(conn)?;
 
             let iter = 3;
@@ -1,18 +1,22 @@
-use crate::dieselsqlite::schema::{kernel_upgrades, kernel_upgrades::dsl::*};
+use crate::dieselsqlite::{
+    error::StoreResult,
+    schema::{kernel_upgrades, kernel_upgrades::dsl::*},
+    types::{L2Level, RootHash},
+};
 use diesel::{dsl::*, prelude::*};
 
 #[derive(Queryable, Selectable)]
 #[diesel(table_name = kernel_upgrades)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct KernelUpgrade {
-    pub injected_before: i32,
-    pub root_hash: Vec<u8>,
+    pub injected_before: L2Level,
+    pub root_hash: RootHash,
     pub activation_timestamp: i64,
-    pub applied_before: Option<i32>,
+    pub applied_before: Option<L2Level>,
 }
 
 impl KernelUpgrade {
-    pub fn insert(self, connection: &mut SqliteConnection) -> QueryResult<usize> {
+    pub fn insert(self, connection: &mut SqliteConnection) -> StoreResult<usize> {
         let inserted_rows = replace_into(kernel_upgrades)
             .values((
                 injected_before.eq(self.injected_before),
@@ -23,7 +27,7 @@ impl KernelUpgrade {
         Ok(inserted_rows)
     }
 
-    pub fn activation_levels(connection: &mut SqliteConnection) -> QueryResult<Vec<i32>> {
+    pub fn activation_levels(connection: &mut SqliteConnection) -> StoreResult<Vec<L2Level>> {
         let activation_levels = kernel_upgrades
             .filter(applied_before.is_not_null())
             .select(applied_before.assume_not_null())
@@ -34,7 +38,7 @@ impl KernelUpgrade {
 
     pub fn get_latest_unapplied(
         connection: &mut SqliteConnection,
-    ) -> QueryResult<(i32, Vec<u8>, i64)> {
+    ) -> StoreResult<(L2Level, RootHash, i64)> {
         let latest_unapplied = kernel_upgrades
             .filter(applied_before.is_null())
             .select((injected_before, root_hash, activation_timestamp))
@@ -46,8 +50,8 @@ impl KernelUpgrade {
 
     pub fn find_injected_before(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<(Vec<u8>, i64)> {
+        queried_level: L2Level,
+    ) -> StoreResult<(RootHash, i64)> {
         let result = kernel_upgrades
             .filter(injected_before.eq(queried_level))
             .select((root_hash, activation_timestamp))
@@ -57,8 +61,8 @@ impl KernelUpgrade {
 
     pub fn find_latest_injected_after(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<(Vec<u8>, i64)> {
+        queried_level: L2Level,
+    ) -> StoreResult<(RootHash, i64)> {
         let latest_injected_after = kernel_upgrades
             .filter(injected_before.gt(queried_level))
             .select((root_hash, activation_timestamp))
@@ -68,7 +72,7 @@ impl KernelUpgrade {
         Ok(latest_injected_after)
     }
 
-    pub fn record_apply(connection: &mut SqliteConnection, level: i32) -> QueryResult<usize> {
+    pub fn record_apply(connection: &mut SqliteConnection, level: L2Level) -> StoreResult<usize> {
         let updated_rows = update(kernel_upgrades.filter(applied_before.is_null()))
             .set(applied_before.eq(level))
             .execute(connection)?;
@@ -77,8 +81,8 @@ impl KernelUpgrade {
 
     pub fn clear_after(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<usize> {
+        queried_level: L2Level,
+    ) -> StoreResult<usize> {
         let cleared_rows = delete(kernel_upgrades.filter(injected_before.gt(queried_level)))
             .execute(connection)?;
         Ok(cleared_rows)
@@ -86,18 +90,18 @@ impl KernelUpgrade {
 
     pub fn nullify_after(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<usize> {
+        queried_level: L2Level,
+    ) -> StoreResult<usize> {
         let nullified_rows = update(kernel_upgrades.filter(applied_before.gt(queried_level)))
-            .set(applied_before.eq::<Option<i32>>(None))
+            .set(applied_before.eq::<Option<L2Level>>(None))
             .execute(connection)?;
         Ok(nullified_rows)
     }
 
     pub fn clear_before(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<usize> {
+        queried_level: L2Level,
+    ) -> StoreResult<usize> {
         let cleared_rows = delete(kernel_upgrades.filter(injected_before.lt(queried_level)))
             .execute(connection)?;
         Ok(cleared_rows)
@@ -107,27 +111,26 @@ impl KernelUpgrade {
 #[cfg(test)]
 mod kernel_upgrade_test {
     use super::*;
-    use crate::dieselsqlite::establish_connection;
-    use diesel::result::Error;
+    use crate::dieselsqlite::{error::StoreError, establish_connection};
 
     #[test]
     fn test_kernel_upgrade_all() {
         let connection = &mut establish_connection().unwrap();
 
-        connection.test_transaction::<_, Error, _>(|conn| {
-            let injected_before_base = 5000;
+        connection.test_transaction::<_, StoreError, _>(|conn| {
+            let injected_before_base = L2Level(5000);
             let iter = 10;
-            let applied_before_base = 6000;
-            let mut expected_activation_levels: Vec<i32> = Vec::new();
+            let applied_before_base = L2Level(6000);
+            let mut expected_activation_levels: Vec<L2Level> = Vec::new();
             for i in 0..iter {
                 let inserted_injected_before = injected_before_base + i;
-                let inserted_root_hash = format!("root_hash {}", i).as_bytes().to_vec();
+                let inserted_root_hash = RootHash([i as u8; 33]);
                 let inserted_activation_timestamp = i64::from(i);
                 let inserted_applied_before = None;
 
                 let kernel_upgrade = KernelUpgrade {
                     injected_before: inserted_injected_before,
-                    root_hash: inserted_root_hash.clone(),
+                    root_hash: inserted_root_hash,
                     activation_timestamp: inserted_activation_timestamp,
                     applied_before: inserted_applied_before,
                 };
@@ -144,13 +147,13 @@ mod kernel_upgrade_test {
             expected_activation_levels.reverse();
 
             let inserted_injected_before = injected_before_base + iter;
-            let inserted_root_hash = format!("root_hash {}", iter).as_bytes().to_vec();
+            let inserted_root_hash = RootHash([iter as u8; 33]);
             let inserted_activation_timestamp = i64::from(iter);
             let inserted_applied_before = None;
 
             let kernel_upgrade = KernelUpgrade {
                 injected_before: inserted_injected_before,
-                root_hash: inserted_root_hash.clone(),
+                root_hash: inserted_root_hash,
                 activation_timestamp: inserted_activation_timestamp,
                 applied_before: inserted_applied_before,
             };
@@ -171,7 +174,7 @@ mod kernel_upgrade_test {
             );
 
             let queried_injected_before = injected_before_base;
-            let expected_root_hash = "root_hash 0".as_bytes().to_vec();
+            let expected_root_hash = RootHash([0u8; 33]);
             let expected_activation_timestamp = 0;
 
             let (injected_before_root_hash, injected_before_activation_timestamp) =
@@ -200,7 +203,7 @@ mod kernel_upgrade_test {
 
             let result = KernelUpgrade::get_latest_unapplied(conn);
 
-            assert_eq!(result, Err(Error::NotFound));
+            assert_eq!(result, Err(StoreError::NotFound));
 
             let expected_nullified_rows = 1;
             let nullified_rows = KernelUpgrade::nullify_after(conn, applied_before_base + 9)?;
@@ -1,17 +1,24 @@
-use crate::dieselsqlite::schema::{l1_l2_finalized_levels, l1_l2_finalized_levels::dsl::*};
-use diesel::{dsl::*, prelude::*, result::Error::NotFound};
+use crate::dieselsqlite::{
+    error::{StoreError, StoreResult},
+    schema::{l1_l2_finalized_levels, l1_l2_finalized_levels::dsl::*},
+    types::{L1Level, L2Level},
+};
+use diesel::{dsl::*, prelude::*};
+
+/// L1 level, and the first and last L2 levels it finalized.
+pub type FinalizedLevelRow = (L1Level, L2Level, L2Level);
 
 #[derive(Queryable, Selectable, Insertable)]
 #[diesel(table_name = l1_l2_finalized_levels)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct L1L2FinalizedLevel {
-    pub l1_level: i32,
-    pub start_l2_level: i32,
-    pub end_l2_level: i32,
+    pub l1_level: L1Level,
+    pub start_l2_level: L2Level,
+    pub end_l2_level: L2Level,
 }
 
 impl L1L2FinalizedLevel {
-    pub fn insert(self, connection: &mut SqliteConnection) -> QueryResult<usize> {
+    pub fn insert(self, connection: &mut SqliteConnection) -> StoreResult<usize> {
         let inserted_rows = replace_into(l1_l2_finalized_levels)
             .values(&self)
             .execute(connection)?;
@@ -20,8 +27,8 @@ impl L1L2FinalizedLevel {
 
     pub fn get(
         connection: &mut SqliteConnection,
-        queried_l1_level: i32,
-    ) -> QueryResult<(i32, i32)> {
+        queried_l1_level: L1Level,
+    ) -> StoreResult<(L2Level, L2Level)> {
         let get = l1_l2_finalized_levels
             .find(queried_l1_level)
             .select((start_l2_level, end_l2_level))
@@ -29,14 +36,14 @@ impl L1L2FinalizedLevel {
         Ok(get)
     }
 
-    pub fn last_l2_level(connection: &mut SqliteConnection) -> QueryResult<i32> {
-        let max: Option<i32> = l1_l2_finalized_levels
+    pub fn last_l2_level(connection: &mut SqliteConnection) -> StoreResult<L2Level> {
+        let max: Option<L2Level> = l1_l2_finalized_levels
             .select(max(end_l2_level))
             .get_result(connection)?;
-        max.ok_or_else(|| NotFound)
+        max.ok_or(StoreError::NotFound)
     }
 
-    pub fn last(connection: &mut SqliteConnection) -> QueryResult<(i32, i32, i32)> {
+    pub fn last(connection: &mut SqliteConnection) -> StoreResult<FinalizedLevelRow> {
         let last = l1_l2_finalized_levels
             .select((l1_level, start_l2_level, end_l2_level))
             .order_by(l1_level.desc())
@@ -47,8 +54,8 @@ impl L1L2FinalizedLevel {
 
     pub fn find_l1_level(
         connection: &mut SqliteConnection,
-        queried_l2_level: i32,
-    ) -> QueryResult<i32> {
+        queried_l2_level: L2Level,
+    ) -> StoreResult<L1Level> {
         let find = l1_l2_finalized_levels
             .filter(
                 start_l2_level
@@ -64,9 +71,9 @@ impl L1L2FinalizedLevel {
 
     pub fn list_by_l2_levels(
         connection: &mut SqliteConnection,
-        start_l2: i32,
-        end_l2: i32,
-    ) -> QueryResult<Vec<(i32, i32, i32)>> {
+        start_l2: L2Level,
+        end_l2: L2Level,
+    ) -> StoreResult<Vec<FinalizedLevelRow>> {
         let list = l1_l2_finalized_levels
             .filter(start_l2_level.ge(start_l2).and(end_l2_level.le(end_l2)))
             .select((l1_level, start_l2_level, end_l2_level))
@@ -75,11 +82,26 @@ impl L1L2FinalizedLevel {
         Ok(list)
     }
 
+    /// Finalized levels whose first L2 level is between `start_l2` and `end_l2`,
+    /// by L1 level.
+    pub fn list_by_start_l2_levels(
+        connection: &mut SqliteConnection,
+        start_l2: L2Level,
+        end_l2: L2Level,
+    ) -> StoreResult<Vec<FinalizedLevelRow>> {
+        let list = l1_l2_finalized_levels
+            .filter(start_l2_level.between(start_l2, end_l2))
+            .select((l1_level, start_l2_level, end_l2_level))
+            .order_by(l1_level.asc())
+            .load(connection)?;
+        Ok(list)
+    }
+
     pub fn list_by_l1_levels(
         connection: &mut SqliteConnection,
-        start_l1: i32,
-        end_l1: i32,
-    ) -> QueryResult<Vec<(i32, i32, i32)>> {
+        start_l1: L1Level,
+        end_l1: L1Level,
+    ) -> StoreResult<Vec<FinalizedLevelRow>> {
         let list = l1_l2_finalized_levels
             .filter(l1_level.between(start_l1, end_l1))
             .select((l1_level, start_l2_level, end_l2_level))
@@ -90,8 +112,8 @@ impl L1L2FinalizedLevel {
 
     pub fn clear_after(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<usize> {
+        queried_level: L2Level,
+    ) -> StoreResult<usize> {
         let cleared_rows = delete(l1_l2_finalized_levels.filter(end_l2_level.gt(queried_level)))
             .execute(connection)?;
         Ok(cleared_rows)
@@ -99,8 +121,8 @@ impl L1L2FinalizedLevel {
 
     pub fn clear_before(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<usize> {
+        queried_level: L2Level,
+    ) -> StoreResult<usize> {
         let cleared_rows = delete(l1_l2_finalized_levels.filter(start_l2_level.lt(queried_level)))
             .execute(connection)?;
         Ok(cleared_rows)
@@ -111,16 +133,15 @@ impl L1L2FinalizedLevel {
 mod l1_l2_finalized_level_test {
     use super::*;
     use crate::dieselsqlite::{Block, establish_connection};
-    use diesel::result::Error;
 
     #[test]
     fn test_l1_l2_finalized_level_all() {
         let connection = &mut establish_connection().unwrap();
 
-        connection.test_transaction::<_, Error, _>(|conn| {
-            L1L2FinalizedLevel::clear_after(conn, 0)?;
+        connection.test_transaction::<_, StoreError, _>(|conn| {
+            L1L2FinalizedLevel::clear_after(conn, L2Level(0))?;
             let iter = 3;
-            let l1_level_base = 6000;
+            let l1_level_base = L1Level(6000);
             let start_l2_level_base = Block::top_level(conn)?;
             let span = iter;
             let end_l2_level_base = start_l2_level_base + span;
@@ -170,7 +191,7 @@ mod l1_l2_finalized_level_test {
                         end_l2_level_base + i,
                     )
                 })
-                .collect::<Vec<(i32, i32, i32)>>();
+                .collect::<Vec<FinalizedLevelRow>>();
 
             let list_by_l2 = L1L2FinalizedLevel::list_by_l2_levels(
                 conn,
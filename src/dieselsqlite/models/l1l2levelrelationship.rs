@@ -1,23 +1,27 @@
-use crate::dieselsqlite::schema::{l1_l2_levels_relationships, l1_l2_levels_relationships::dsl::*};
+use crate::dieselsqlite::{
+    error::StoreResult,
+    schema::{l1_l2_levels_relationships, l1_l2_levels_relationships::dsl::*},
+    types::{L1Level, L2Level},
+};
 use diesel::{dsl::*, prelude::*};
 
 #[derive(Queryable, Selectable, Insertable)]
 #[diesel(table_name = l1_l2_levels_relationships)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct L1L2LevelRelationship {
-    pub latest_l2_level: i32,
-    pub l1_level: i32,
+    pub latest_l2_level: L2Level,
+    pub l1_level: L1Level,
 }
 
 impl L1L2LevelRelationship {
-    pub fn insert(self, connection: &mut SqliteConnection) -> QueryResult<usize> {
+    pub fn insert(self, connection: &mut SqliteConnection) -> StoreResult<usize> {
         let inserted_rows = self
             .insert_into(l1_l2_levels_relationships)
             .execute(connection)?;
         Ok(inserted_rows)
     }
 
-    pub fn get(connection: &mut SqliteConnection) -> QueryResult<(i32, i32)> {
+    pub fn get(connection: &mut SqliteConnection) -> StoreResult<(L2Level, L1Level)> {
         let get = l1_l2_levels_relationships
             .select((latest_l2_level, l1_level))
             .order_by(latest_l2_level.desc())
@@ -28,8 +32,8 @@ impl L1L2LevelRelationship {
 
     pub fn clear_after(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<usize> {
+        queried_level: L2Level,
+    ) -> StoreResult<usize> {
         let cleared_rows =
             delete(l1_l2_levels_relationships.filter(latest_l2_level.gt(queried_level)))
                 .execute(connection)?;
@@ -38,8 +42,8 @@ impl L1L2LevelRelationship {
 
     pub fn clear_before(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<usize> {
+        queried_level: L2Level,
+    ) -> StoreResult<usize> {
         let cleared_rows =
             delete(l1_l2_levels_relationships.filter(latest_l2_level.lt(queried_level)))
                 .execute(connection)?;
@@ -50,17 +54,16 @@ impl L1L2LevelRelationship {
 #[cfg(test)]
 mod l1_l2_level_relationship_test {
     use super::*;
-    use crate::dieselsqlite::{Block, establish_connection};
-    use diesel::result::Error;
+    use crate::dieselsqlite::{Block, error::StoreError, establish_connection};
 
     #[test]
     fn test_l1_l2_level_relationship_all() {
         let connection = &mut establish_connection().unwrap();
 
-        connection.test_transaction::<_, Error, _>(|conn| {
+        connection.test_transaction::<_, StoreError, _>(|conn| {
             let iter = 3;
             let latest_l2_level_base = Block::top_level(conn)?;
-            let l1_level_base = 6000;
+            let l1_level_base = L1Level(6000);
             for i in 0..iter {
                 let relationship = L1L2LevelRelationship {
                     latest_l2_level: latest_l2_level_base + i,
@@ -1,4 +1,7 @@
-use crate::dieselsqlite::schema::{metadata, metadata::dsl::*};
+use crate::dieselsqlite::{
+    error::StoreResult,
+    schema::{metadata, metadata::dsl::*},
+};
 use diesel::{prelude::*, upsert::excluded};
 
 #[derive(Queryable, Selectable, Insertable)]
@@ -13,22 +16,22 @@ impl Metadata {
     pub fn insert_smart_rollup_address(
         connection: &mut SqliteConnection,
         inserted_value: &str,
-    ) -> QueryResult<usize> {
+    ) -> StoreResult<usize> {
         Metadata::insert_key_value(connection, "smart_rollup_address", inserted_value)
     }
 
-    pub fn get_smart_rollup_address(connection: &mut SqliteConnection) -> QueryResult<String> {
+    pub fn get_smart_rollup_address(connection: &mut SqliteConnection) -> StoreResult<String> {
         Metadata::get_value(connection, "smart_rollup_address")
     }
 
     pub fn insert_history_mode(
         connection: &mut SqliteConnection,
         inserted_value: &str,
-    ) -> QueryResult<usize> {
+    ) -> StoreResult<usize> {
         Metadata::insert_key_value(connection, "history_mode", inserted_value)
     }
 
-    pub fn get_history_mode(connection: &mut SqliteConnection) -> QueryResult<String> {
+    pub fn get_history_mode(connection: &mut SqliteConnection) -> StoreResult<String> {
         Metadata::get_value(connection, "history_mode")
     }
 
@@ -36,7 +39,7 @@ impl Metadata {
         connection: &mut SqliteConnection,
         inserted_key: &str,
         inserted_value: &str,
-    ) -> QueryResult<usize> {
+    ) -> StoreResult<usize> {
         let metadata_object = Metadata {
             key: inserted_key.to_string(),
             value: inserted_value.to_string(),
@@ -50,7 +53,7 @@ impl Metadata {
         Ok(inserted_rows)
     }
 
-    fn get_value(connection: &mut SqliteConnection, queried_key: &str) -> QueryResult<String> {
+    fn get_value(connection: &mut SqliteConnection, queried_key: &str) -> StoreResult<String> {
         let returned_value = metadata
             .find(queried_key)
             .select(value)
@@ -62,14 +65,13 @@ impl Metadata {
 #[cfg(test)]
 mod metadata_test {
     use super::*;
-    use crate::dieselsqlite::establish_connection;
-    use diesel::result::Error;
+    use crate::dieselsqlite::{error::StoreError, establish_connection};
 
     #[test]
     fn test_metadata_insert_select() {
         let connection = &mut establish_connection().unwrap();
 
-        connection.test_transaction::<_, Error, _>(|conn| {
+        connection.test_transaction::<_, StoreError, _>(|conn| {
             let history_mode = "new history mode";
 
             Metadata::insert_history_mode(conn, history_mode)?;
@@ -1,4 +1,7 @@
-use crate::dieselsqlite::schema::{migrations, migrations::dsl::*};
+use crate::dieselsqlite::{
+    error::StoreResult,
+    schema::{migrations, migrations::dsl::*},
+};
 use diesel::{dsl::*, prelude::*};
 
 #[derive(Queryable, Selectable, Insertable)]
@@ -10,7 +13,7 @@ pub struct Migration {
 }
 
 impl Migration {
-    pub fn create_table(connection: &mut SqliteConnection) -> QueryResult<usize> {
+    pub fn create_table(connection: &mut SqliteConnection) -> StoreResult<usize> {
         let create = sql_query(
             "CREATE TABLE migrations (
         id SERIAL PRIMARY KEY,
@@ -21,7 +24,7 @@ impl Migration {
         Ok(create)
     }
 
-    pub fn current_migration(connection: &mut SqliteConnection) -> QueryResult<i32> {
+    pub fn current_migration(connection: &mut SqliteConnection) -> StoreResult<i32> {
         let current_id = migrations
             .select(id)
             .order_by(id.desc())
@@ -30,7 +33,16 @@ impl Migration {
         Ok(current_id)
     }
 
-    pub fn register_migration(self, connection: &mut SqliteConnection) -> QueryResult<usize> {
+    /// Migrations applied to the store, in order.
+    pub fn select_all(connection: &mut SqliteConnection) -> StoreResult<Vec<Migration>> {
+        let all = migrations
+            .order_by(id.asc())
+            .select(Migration::as_select())
+            .load(connection)?;
+        Ok(all)
+    }
+
+    pub fn register_migration(self, connection: &mut SqliteConnection) -> StoreResult<usize> {
         let inserted_rows = insert_into(migrations).values(&self).execute(connection)?;
         Ok(inserted_rows)
     }
@@ -39,14 +51,13 @@ impl Migration {
 #[cfg(test)]
 mod migration_test {
     use super::*;
-    use crate::dieselsqlite::establish_connection;
-    use diesel::result::Error;
+    use crate::dieselsqlite::{error::StoreError, establish_connection};
 
     #[test]
     fn test_migration_all() {
         let connection = &mut establish_connection().unwrap();
 
-        connection.test_transaction::<_, Error, _>(|conn| {
+        connection.test_transaction::<_, StoreError, _>(|conn| {
             let _ = Migration::create_table(conn);
 
             let current_migration = Migration::current_migration(conn)?;
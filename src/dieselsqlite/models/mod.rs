@@ -37,20 +37,24 @@ use diesel::{
     sql_types::{Binary, Bool},
 };
 
-use crate::dieselsqlite::schema::{blocks, context_hashes};
+use crate::dieselsqlite::{
+    error::StoreResult,
+    schema::{blocks, context_hashes},
+    types::{self, BlockHash},
+};
 
 const CASTINGLITERALSQL: &str = "CAST(hash as BLOB) = ";
 
-pub fn cast_hash_comparison(
-    queried_hash: &Vec<u8>,
-) -> UncheckedBind<SqlLiteral<Bool>, <&Vec<u8> as AsExpression<Binary>>::Expression> {
-    sql(CASTINGLITERALSQL).bind::<Binary, &Vec<u8>>(queried_hash)
+pub fn cast_hash_comparison<T: AsRef<[u8]>>(
+    queried_hash: &T,
+) -> UncheckedBind<SqlLiteral<Bool>, <&[u8] as AsExpression<Binary>>::Expression> {
+    sql(CASTINGLITERALSQL).bind::<Binary, &[u8]>(queried_hash.as_ref())
 }
 
 pub fn context_hash_of_block_hash(
     connection: &mut SqliteConnection,
-    queried_block_hash: &Vec<u8>,
-) -> QueryResult<Vec<u8>> {
+    queried_block_hash: &BlockHash,
+) -> StoreResult<types::ContextHash> {
     let c_h = context_hashes::table
         .inner_join(blocks::table)
         .filter(blocks::hash.eq(queried_block_hash))
@@ -62,29 +66,33 @@ pub fn context_hash_of_block_hash(
 #[cfg(test)]
 mod mod_test {
     use super::*;
-    use crate::dieselsqlite::establish_connection;
-    use diesel::{Connection, result::Error};
+    use crate::dieselsqlite::{
+        error::StoreError,
+        establish_connection,
+        types::{Bytes, L2Level},
+    };
+    use diesel::Connection;
 
     #[test]
     fn test_join_context_hash_block_hash() {
         let connection = &mut establish_connection().unwrap();
 
-        connection.test_transaction::<_, Error, _>(|conn| {
-            let id = 10;
+        connection.test_transaction::<_, StoreError, _>(|conn| {
+            let id = L2Level(10);
 
-            let inserted_context_hash = "context_hash".as_bytes().to_vec();
-            let inserted_block_hash = "block_hash".as_bytes().to_vec();
-            let inserted_block = "block".as_bytes().to_vec();
+            let inserted_context_hash = types::ContextHash([2u8; 32]);
+            let inserted_block_hash = BlockHash([1u8; 32]);
+            let inserted_block = Bytes::from("block".as_bytes());
 
             let block = Block {
                 level: id,
-                hash: inserted_block_hash.clone(),
-                block: inserted_block.clone(),
+                hash: inserted_block_hash,
+                block: inserted_block,
             };
 
-            let context_hash = ContextHash {
+            let context_hash = ContextHashRow {
                 id,
-                context_hash: inserted_context_hash.clone(),
+                context_hash: inserted_context_hash,
             };
 
             block.insert(conn)?;
@@ -1,16 +1,20 @@
-use crate::dieselsqlite::schema::{pending_confirmations, pending_confirmations::dsl::*};
+use crate::dieselsqlite::{
+    error::StoreResult,
+    schema::{pending_confirmations, pending_confirmations::dsl::*},
+    types::{BlockHash, L2Level},
+};
 use diesel::{dsl::*, prelude::*};
 
 #[derive(Queryable, Selectable, Insertable)]
 #[diesel(table_name = pending_confirmations)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct PendingConfirmation {
-    pub level: i32,
-    pub hash: Vec<u8>,
+    pub level: L2Level,
+    pub hash: BlockHash,
 }
 
 impl PendingConfirmation {
-    pub fn insert(self, connection: &mut SqliteConnection) -> QueryResult<usize> {
+    pub fn insert(self, connection: &mut SqliteConnection) -> StoreResult<usize> {
         let inserted_rows = self
             .insert_into(pending_confirmations)
             .execute(connection)?;
@@ -19,8 +23,8 @@ impl PendingConfirmation {
 
     pub fn select_with_level(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<Vec<u8>> {
+        queried_level: L2Level,
+    ) -> StoreResult<BlockHash> {
         let h = pending_confirmations
             .find(queried_level)
             .select(hash)
@@ -28,21 +32,29 @@ impl PendingConfirmation {
         Ok(h)
     }
 
+    pub fn select_all(connection: &mut SqliteConnection) -> StoreResult<Vec<(L2Level, BlockHash)>> {
+        let all = pending_confirmations
+            .order(level.asc())
+            .select((level, hash))
+            .load(connection)?;
+        Ok(all)
+    }
+
     pub fn delete_with_level(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<usize> {
+        queried_level: L2Level,
+    ) -> StoreResult<usize> {
         let deleted_rows =
             delete(pending_confirmations.filter(level.eq(queried_level))).execute(connection)?;
         Ok(deleted_rows)
     }
 
-    pub fn clear(connection: &mut SqliteConnection) -> QueryResult<usize> {
+    pub fn clear(connection: &mut SqliteConnection) -> StoreResult<usize> {
         let deleted_rows = delete(pending_confirmations).execute(connection)?;
         Ok(deleted_rows)
     }
 
-    pub fn count(connection: &mut SqliteConnection) -> QueryResult<i64> {
+    pub fn count(connection: &mut SqliteConnection) -> StoreResult<i64> {
         let count = pending_confirmations
             .select(count(level))
             .first(connection)?;
@@ -53,20 +65,19 @@ impl PendingConfirmation {
 #[cfg(test)]
 mod pending_confirmation_test {
     use super::*;
-    use crate::dieselsqlite::establish_connection;
-    use diesel::result::Error;
+    use crate::dieselsqlite::{error::StoreError, establish_connection};
 
     #[test]
     fn test_pending_confirmation_insert_select_delete() {
         let connection = &mut establish_connection().unwrap();
 
-        connection.test_transaction::<_, Error, _>(|conn| {
-            let inserted_level = 0;
-            let inserted_hash = "hash".as_bytes().to_vec();
+        connection.test_transaction::<_, StoreError, _>(|conn| {
+            let inserted_level = L2Level(0);
+            let inserted_hash = BlockHash([1u8; 32]);
 
             let pendingconfirmation = PendingConfirmation {
                 level: inserted_level,
-                hash: inserted_hash.clone(),
+                hash: inserted_hash,
             };
             pendingconfirmation.insert(conn)?;
 
@@ -88,15 +99,15 @@ mod pending_confirmation_test {
     fn test_pending_confirmation_insert_count_clear() {
         let connection = &mut establish_connection().unwrap();
 
-        connection.test_transaction::<_, Error, _>(|conn| {
+        connection.test_transaction::<_, StoreError, _>(|conn| {
             let iter = 10;
             for i in 0..iter {
-                let inserted_level = i;
-                let inserted_hash = format!("hash {i}").as_bytes().to_vec();
+                let inserted_level = L2Level(i);
+                let inserted_hash = BlockHash([i as u8; 32]);
 
                 let pendingconfirmation = PendingConfirmation {
                     level: inserted_level,
-                    hash: inserted_hash.clone(),
+                    hash: inserted_hash,
                 };
                 pendingconfirmation.insert(conn)?;
             }
@@ -1,7 +1,8 @@
-use crate::dieselsqlite::schema::sqlite_schema;
+use crate::dieselsqlite::{error::StoreResult, schema::sqlite_schema};
 use diesel::{
-    dsl::{exists, select},
+    dsl::{exists, select, sql_query},
     prelude::*,
+    sql_types::{Bool, Integer, Text},
 };
 
 #[derive(Queryable, Selectable, QueryableByName)]
@@ -15,8 +16,30 @@ pub struct Schema {
     pub sql: String,
 }
 
+/// Statement creating a table or an index.
+#[derive(QueryableByName)]
+struct CreateStatement {
+    #[diesel(sql_type = Text)]
+    sql: String,
+}
+
 impl Schema {
-    pub fn get_all(connection: &mut SqliteConnection) -> QueryResult<Vec<String>> {
+    /// Statements creating the tables and indexes of the database, SQLite's
+    /// own ones excluded, the tables first, each kind in creation order.
+    pub fn create_statements(connection: &mut SqliteConnection) -> StoreResult<Vec<String>> {
+        let statements = sql_query(
+            "SELECT sql FROM sqlite_schema
+            WHERE sql IS NOT NULL AND name NOT LIKE 'sqlite_%'
+            ORDER BY type <> 'table', rowid",
+        )
+        .load::<CreateStatement>(connection)?;
+        Ok(statements
+            .into_iter()
+            .map(|statement| statement.sql)
+            .collect())
+    }
+
+    pub fn get_all(connection: &mut SqliteConnection) -> StoreResult<Vec<String>> {
         use crate::dieselsqlite::schema::sqlite_schema::dsl::*;
         let sqls = sqlite_schema
             .filter(name.not_like("sqlite_%").and(name.ne("migrations")))
@@ -25,7 +48,18 @@ impl Schema {
         Ok(sqls)
     }
 
-    pub fn table_exists(connection: &mut SqliteConnection, table_name: &str) -> QueryResult<bool> {
+    /// Names of the tables of the database, SQLite's own ones excluded.
+    pub fn table_names(connection: &mut SqliteConnection) -> StoreResult<Vec<String>> {
+        use crate::dieselsqlite::schema::sqlite_schema::dsl::*;
+        let names = sqlite_schema
+            .filter(schema_type.eq("table").and(name.not_like("sqlite_%")))
+            .select(name)
+            .order_by(name)
+            .load(connection)?;
+        Ok(names)
+    }
+
+    pub fn table_exists(connection: &mut SqliteConnection, table_name: &str) -> StoreResult<bool> {
         use crate::dieselsqlite::schema::sqlite_schema::dsl::*;
         let exists_bool = select(exists(
             sqlite_schema.filter(schema_type.eq("table").and(name.eq(table_name))),
@@ -35,17 +69,47 @@ impl Schema {
     }
 }
 
+/// Column of a table, as described by `PRAGMA table_info`.
+#[derive(Debug, Clone, PartialEq, Eq, QueryableByName)]
+pub struct SchemaColumn {
+    #[diesel(sql_type = Text)]
+    pub name: String,
+    /// Declared type of the column.
+    #[diesel(sql_type = Text)]
+    pub column_type: String,
+    #[diesel(sql_type = Bool)]
+    pub not_null: bool,
+    /// Position of the column in the primary key, 0 when it isn't part of it.
+    #[diesel(sql_type = Integer)]
+    pub primary_key: i32,
+}
+
+impl SchemaColumn {
+    /// Columns of `table_name`, in their declaration order.
+    pub fn of_table(
+        connection: &mut SqliteConnection,
+        table_name: &str,
+    ) -> StoreResult<Vec<SchemaColumn>> {
+        let columns = sql_query(
+            "SELECT name, type AS column_type, \"notnull\" AS not_null, pk AS primary_key
+            FROM pragma_table_info(?) ORDER BY cid",
+        )
+        .bind::<Text, _>(table_name)
+        .load(connection)?;
+        Ok(columns)
+    }
+}
+
 #[cfg(test)]
 mod schema_test {
     use super::*;
-    use crate::dieselsqlite::establish_connection;
-    use diesel::result::Error;
+    use crate::dieselsqlite::{error::StoreError, establish_connection};
 
     #[test]
     fn test_schema() {
         let connection = &mut establish_connection().unwrap();
 
-        connection.test_transaction::<_, Error, _>(|conn| {
+        connection.test_transaction::<_, StoreError, _>(|conn| {
             let schema = Schema::get_all(conn).unwrap();
 
             let create_in_string = schema.iter().any(|s| s.contains("CREATE"));
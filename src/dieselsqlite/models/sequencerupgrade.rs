@@ -1,19 +1,23 @@
-use crate::dieselsqlite::schema::{sequencer_upgrades, sequencer_upgrades::dsl::*};
+use crate::dieselsqlite::{
+    error::StoreResult,
+    schema::{sequencer_upgrades, sequencer_upgrades::dsl::*},
+    types::{Address, Bytes, L2Level},
+};
 use diesel::{dsl::*, prelude::*};
 
 #[derive(Queryable, Selectable)]
 #[diesel(table_name = sequencer_upgrades)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct SequencerUpgrade {
-    pub injected_before: i32,
-    pub sequencer: Vec<u8>,
-    pub pool_address: Vec<u8>,
+    pub injected_before: L2Level,
+    pub sequencer: Bytes,
+    pub pool_address: Address,
     pub activation_timestamp: i64,
-    pub applied_before: Option<i32>,
+    pub applied_before: Option<L2Level>,
 }
 
 impl SequencerUpgrade {
-    pub fn insert(self, connection: &mut SqliteConnection) -> QueryResult<usize> {
+    pub fn insert(self, connection: &mut SqliteConnection) -> StoreResult<usize> {
         let inserted_rows = replace_into(sequencer_upgrades)
             .values((
                 injected_before.eq(self.injected_before),
@@ -25,7 +29,7 @@ impl SequencerUpgrade {
         Ok(inserted_rows)
     }
 
-    pub fn activation_levels(connection: &mut SqliteConnection) -> QueryResult<Vec<i32>> {
+    pub fn activation_levels(connection: &mut SqliteConnection) -> StoreResult<Vec<L2Level>> {
         let activation_levels = sequencer_upgrades
             .filter(applied_before.is_not_null())
             .select(applied_before.assume_not_null())
@@ -34,9 +38,21 @@ impl SequencerUpgrade {
         Ok(activation_levels)
     }
 
+    /// Activation level and key of the applied sequencers, from the oldest.
+    pub fn applied_sequencers(
+        connection: &mut SqliteConnection,
+    ) -> StoreResult<Vec<(L2Level, Bytes)>> {
+        let applied_sequencers = sequencer_upgrades
+            .filter(applied_before.is_not_null())
+            .select((applied_before.assume_not_null(), sequencer))
+            .order_by(applied_before.asc())
+            .load(connection)?;
+        Ok(applied_sequencers)
+    }
+
     pub fn get_latest_unapplied(
         connection: &mut SqliteConnection,
-    ) -> QueryResult<(i32, Vec<u8>, Vec<u8>, i64)> {
+    ) -> StoreResult<(L2Level, Bytes, Address, i64)> {
         let latest_unapplied = sequencer_upgrades
             .filter(applied_before.is_null())
             .select((
@@ -53,8 +69,8 @@ impl SequencerUpgrade {
 
     pub fn find_injected_before(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<(Vec<u8>, Vec<u8>, i64)> {
+        queried_level: L2Level,
+    ) -> StoreResult<(Bytes, Address, i64)> {
         let result = sequencer_upgrades
             .filter(injected_before.eq(queried_level))
             .select((sequencer, pool_address, activation_timestamp))
@@ -64,8 +80,8 @@ impl SequencerUpgrade {
 
     pub fn find_latest_injected_after(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<(Vec<u8>, Vec<u8>, i64)> {
+        queried_level: L2Level,
+    ) -> StoreResult<(Bytes, Address, i64)> {
         let latest_injected_after = sequencer_upgrades
             .filter(injected_before.gt(queried_level))
             .select((sequencer, pool_address, activation_timestamp))
@@ -75,7 +91,7 @@ impl SequencerUpgrade {
         Ok(latest_injected_after)
     }
 
-    pub fn record_apply(connection: &mut SqliteConnection, level: i32) -> QueryResult<usize> {
+    pub fn record_apply(connection: &mut SqliteConnection, level: L2Level) -> StoreResult<usize> {
         let updated_rows = update(sequencer_upgrades.filter(applied_before.is_null()))
             .set(applied_before.eq(level))
             .execute(connection)?;
@@ -84,8 +100,8 @@ impl SequencerUpgrade {
 
     pub fn clear_after(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<usize> {
+        queried_level: L2Level,
+    ) -> StoreResult<usize> {
         let cleared_rows = delete(sequencer_upgrades.filter(injected_before.gt(queried_level)))
             .execute(connection)?;
         Ok(cleared_rows)
@@ -93,18 +109,18 @@ impl SequencerUpgrade {
 
     pub fn nullify_after(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<usize> {
+        queried_level: L2Level,
+    ) -> StoreResult<usize> {
         let nullified_rows = update(sequencer_upgrades.filter(applied_before.gt(queried_level)))
-            .set(applied_before.eq::<Option<i32>>(None))
+            .set(applied_before.eq::<Option<L2Level>>(None))
             .execute(connection)?;
         Ok(nullified_rows)
     }
 
     pub fn clear_before(
         connection: &mut SqliteConnection,
-        queried_level: i32,
-    ) -> QueryResult<usize> {
+        queried_level: L2Level,
+    ) -> StoreResult<usize> {
         let cleared_rows = delete(sequencer_upgrades.filter(injected_before.lt(queried_level)))
             .execute(connection)?;
         Ok(cleared_rows)
@@ -114,30 +130,29 @@ impl SequencerUpgrade {
 #[cfg(test)]
 mod sequencer_upgrade_test {
     use super::*;
-    use crate::dieselsqlite::establish_connection;
-    use diesel::result::Error;
+    use crate::dieselsqlite::{error::StoreError, establish_connection};
 
     #[test]
     fn test_sequencer_upgrade_all() {
         let connection = &mut establish_connection().unwrap();
 
-        connection.test_transaction::<_, Error, _>(|conn| {
-            let injected_before_base = 5000;
+        connection.test_transaction::<_, StoreError, _>(|conn| {
+            let injected_before_base = L2Level(5000);
             let iter = 10;
-            let applied_before_base = 6000;
-            let mut expected_activation_levels: Vec<i32> = Vec::new();
+            let applied_before_base = L2Level(6000);
+            let mut expected_activation_levels: Vec<L2Level> = Vec::new();
 
             for i in 0..iter {
                 let inserted_injected_before = injected_before_base + i;
-                let inserted_sequencer = format!("sequencer {}", i).as_bytes().to_vec();
-                let inserted_pool_address = format!("pool_address {}", i).as_bytes().to_vec();
+                let inserted_sequencer = Bytes::from(format!("sequencer {}", i).as_bytes());
+                let inserted_pool_address = Address([i as u8; 20]);
                 let inserted_activation_timestamp = i64::from(i);
                 let inserted_applied_before = None;
 
                 let sequencer_upgrade = SequencerUpgrade {
                     injected_before: inserted_injected_before,
                     sequencer: inserted_sequencer.clone(),
-                    pool_address: inserted_pool_address.clone(),
+                    pool_address: inserted_pool_address,
                     activation_timestamp: inserted_activation_timestamp,
                     applied_before: inserted_applied_before,
                 };
@@ -154,15 +169,15 @@ mod sequencer_upgrade_test {
             expected_activation_levels.reverse();
 
             let inserted_injected_before = injected_before_base + iter;
-            let inserted_sequencer = format!("sequencer {}", iter).as_bytes().to_vec();
-            let inserted_pool_address = format!("pool_address {}", iter).as_bytes().to_vec();
+            let inserted_sequencer = Bytes::from(format!("sequencer {}", iter).as_bytes());
+            let inserted_pool_address = Address([iter as u8; 20]);
             let inserted_activation_timestamp = i64::from(iter);
             let inserted_applied_before = None;
 
             let sequencer_upgrade = SequencerUpgrade {
                 injected_before: inserted_injected_before,
                 sequencer: inserted_sequencer.clone(),
-                pool_address: inserted_pool_address.clone(),
+                pool_address: inserted_pool_address,
                 activation_timestamp: inserted_activation_timestamp,
                 applied_before: inserted_applied_before,
             };
@@ -185,8 +200,8 @@ mod sequencer_upgrade_test {
             );
 
             let queried_injected_before = injected_before_base;
-            let expected_sequencer = "sequencer 0".as_bytes().to_vec();
-            let expected_pool_address = "pool_address 0".as_bytes().to_vec();
+            let expected_sequencer = Bytes::from("sequencer 0".as_bytes());
+            let expected_pool_address = Address([0u8; 20]);
             let expected_activation_timestamp = 0;
 
             let (
@@ -206,6 +221,17 @@ mod sequencer_upgrade_test {
 
             assert_eq!(activations_levels, expected_activation_levels);
 
+            let applied_sequencers = SequencerUpgrade::applied_sequencers(conn)?
+                .into_iter()
+                .filter(|(level, _)| *level >= applied_before_base)
+                .collect::<Vec<(L2Level, Bytes)>>();
+
+            assert_eq!(applied_sequencers.len(), iter as usize);
+            assert_eq!(
+                applied_sequencers[0],
+                (applied_before_base, expected_sequencer)
+            );
+
             let (
                 latest_injected_after_sequencer,
                 latest_injected_after_pool_address,
@@ -223,7 +249,7 @@ mod sequencer_upgrade_test {
 
             let result = SequencerUpgrade::get_latest_unapplied(conn);
 
-            assert_eq!(result, Err(Error::NotFound));
+            assert_eq!(result, Err(StoreError::NotFound));
 
             let expected_nullified_rows = 1;
             let nullified_rows = SequencerUpgrade::nullify_after(conn, applied_before_base + 9)?;
@@ -1,26 +1,69 @@
 use crate::dieselsqlite::{
+    decoder::{ObjectFields, ReceiptFields, TransactionObject, TransactionReceipt},
+    error::StoreResult,
     models::cast_hash_comparison,
     schema::{transactions, transactions::dsl::*},
+    types::{Address, BlockHash, Bytes, L2Level, TxHash},
 };
 use diesel::{dsl::*, prelude::*};
+use std::collections::BTreeMap;
+
+/// Block hash, block number, index, hash, from, to and receipt (or object) fields.
+type TransactionRow = (
+    BlockHash,
+    L2Level,
+    i32,
+    TxHash,
+    Address,
+    Option<Address>,
+    Bytes,
+);
+
+/// Block number, hash, to, receipt and object fields.
+pub type TransactionFieldsRow = (L2Level, TxHash, Option<Address>, Bytes, Bytes);
+
+/// Block number, index, hash, from, to and the address of the contract
+/// created by the transaction.
+pub type TransactionAccountsRow = (
+    L2Level,
+    i32,
+    TxHash,
+    Address,
+    Option<Address>,
+    Option<Address>,
+);
 
 #[derive(Queryable, Selectable, Insertable)]
 #[diesel(table_name = transactions)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 #[diesel(treat_none_as_default_value = false)]
 pub struct Transaction {
-    pub block_hash: Vec<u8>,
-    pub block_number: i32,
+    pub block_hash: BlockHash,
+    pub block_number: L2Level,
     pub index_: i32,
-    pub hash: Vec<u8>,
-    pub from_: Vec<u8>,
-    pub to_: Option<Vec<u8>>,
-    pub receipt_fields: Vec<u8>,
-    pub object_fields: Vec<u8>,
+    pub hash: TxHash,
+    pub from_: Address,
+    pub to_: Option<Address>,
+    pub receipt_fields: Bytes,
+    pub object_fields: Bytes,
 }
 
 impl Transaction {
-    pub fn insert(self, connection: &mut SqliteConnection) -> QueryResult<usize> {
+    /// Row of a transaction, with its fields encoded back as stored by the node.
+    pub fn from_decoded(receipt: &TransactionReceipt, object: &TransactionObject) -> Transaction {
+        Transaction {
+            block_hash: receipt.block_hash,
+            block_number: receipt.block_number,
+            index_: receipt.transaction_index,
+            hash: receipt.hash,
+            from_: receipt.from,
+            to_: receipt.to,
+            receipt_fields: receipt.fields.encode(),
+            object_fields: object.fields.encode(),
+        }
+    }
+
+    pub fn insert(self, connection: &mut SqliteConnection) -> StoreResult<usize> {
         let inserted_rows = self.insert_into(transactions).execute(connection)?;
         Ok(inserted_rows)
     }
@@ -28,7 +71,7 @@ impl Transaction {
     pub fn batch_insert(
         connection: &mut SqliteConnection,
         batch: &Vec<Self>,
-    ) -> QueryResult<usize> {
+    ) -> StoreResult<usize> {
         let inserted_rows = insert_into(transactions)
             .values(batch)
             .execute(connection)?;
@@ -37,17 +80,9 @@ impl Transaction {
 
     pub fn select_receipt(
         connection: &mut SqliteConnection,
-        queried_hash: &Vec<u8>,
-    ) -> QueryResult<(
-        Vec<u8>,
-        i32,
-        i32,
-        Vec<u8>,
-        Vec<u8>,
-        Option<Vec<u8>>,
-        Vec<u8>,
-    )> {
-        let (block_h, block_n, index, h, from, to, receipt_f) = transactions
+        queried_hash: &TxHash,
+    ) -> StoreResult<TransactionReceipt> {
+        let row = transactions
             .filter(cast_hash_comparison(queried_hash))
             .select((
                 block_hash,
@@ -59,33 +94,34 @@ impl Transaction {
                 receipt_fields,
             ))
             .get_result(connection)?;
-        Ok((block_h, block_n, index, h, from, to, receipt_f))
+        decode_receipt(row)
     }
 
     pub fn select_receipts_from_block_number(
         connection: &mut SqliteConnection,
-        queried_block_number: i32,
-    ) -> QueryResult<Vec<(Vec<u8>, i32, Vec<u8>, Vec<u8>, Option<Vec<u8>>, Vec<u8>)>> {
-        let receipts = transactions
+        queried_block_number: L2Level,
+    ) -> StoreResult<Vec<TransactionReceipt>> {
+        let rows = transactions
             .filter(block_number.eq(queried_block_number))
-            .select((block_hash, index_, hash, from_, to_, receipt_fields))
+            .order(index_.asc())
+            .select((
+                block_hash,
+                block_number,
+                index_,
+                hash,
+                from_,
+                to_,
+                receipt_fields,
+            ))
             .load(connection)?;
-        Ok(receipts)
+        rows.into_iter().map(decode_receipt).collect()
     }
 
     pub fn select_object(
         connection: &mut SqliteConnection,
-        queried_hash: &Vec<u8>,
-    ) -> QueryResult<(
-        Vec<u8>,
-        i32,
-        i32,
-        Vec<u8>,
-        Vec<u8>,
-        Option<Vec<u8>>,
-        Vec<u8>,
-    )> {
-        let (block_h, block_n, index, h, from, to, object_f) = transactions
+        queried_hash: &TxHash,
+    ) -> StoreResult<TransactionObject> {
+        let row = transactions
             .filter(cast_hash_comparison(queried_hash))
             .select((
                 block_hash,
@@ -97,24 +133,116 @@ impl Transaction {
                 object_fields,
             ))
             .get_result(connection)?;
-        Ok((block_h, block_n, index, h, from, to, object_f))
+        decode_object(row)
     }
 
     pub fn select_objects_from_block_number(
         connection: &mut SqliteConnection,
-        queried_block_number: i32,
-    ) -> QueryResult<Vec<(i32, Vec<u8>, Vec<u8>, Option<Vec<u8>>, Vec<u8>)>> {
-        let objects = transactions
+        queried_block_number: L2Level,
+    ) -> StoreResult<Vec<TransactionObject>> {
+        let rows = transactions
             .filter(block_number.eq(queried_block_number))
-            .select((index_, hash, from_, to_, object_fields))
+            .order(index_.asc())
+            .select((
+                block_hash,
+                block_number,
+                index_,
+                hash,
+                from_,
+                to_,
+                object_fields,
+            ))
+            .load(connection)?;
+        rows.into_iter().map(decode_object).collect()
+    }
+
+    /// Block number, hash and block hash of the transactions of the blocks in the range.
+    pub fn select_block_hashes_range(
+        connection: &mut SqliteConnection,
+        lower_block_number: L2Level,
+        upper_block_number: L2Level,
+    ) -> StoreResult<Vec<(L2Level, TxHash, BlockHash)>> {
+        let rows = transactions
+            .filter(block_number.between(lower_block_number, upper_block_number))
+            .order((block_number.asc(), index_.asc()))
+            .select((block_number, hash, block_hash))
+            .load(connection)?;
+        Ok(rows)
+    }
+
+    /// Transactions of the blocks in the range, in block order.
+    pub fn select_fields_range(
+        connection: &mut SqliteConnection,
+        lower_block_number: L2Level,
+        upper_block_number: L2Level,
+    ) -> StoreResult<Vec<TransactionFieldsRow>> {
+        let rows = transactions
+            .filter(block_number.between(lower_block_number, upper_block_number))
+            .order((block_number.asc(), index_.asc()))
+            .select((block_number, hash, to_, receipt_fields, object_fields))
             .load(connection)?;
-        Ok(objects)
+        Ok(rows)
+    }
+
+    /// Receipts of the transactions of the blocks in the range, in block order.
+    pub fn select_receipts_range(
+        connection: &mut SqliteConnection,
+        lower_block_number: L2Level,
+        upper_block_number: L2Level,
+    ) -> StoreResult<Vec<TransactionReceipt>> {
+        let rows = transactions
+            .filter(block_number.between(lower_block_number, upper_block_number))
+            .order((block_number.asc(), index_.asc()))
+            .select((
+                block_hash,
+                block_number,
+                index_,
+                hash,
+                from_,
+                to_,
+                receipt_fields,
+            ))
+            .load(connection)?;
+        rows.into_iter().map(decode_receipt).collect()
+    }
+
+    /// Senders and recipients of the transactions of the blocks in the range, in block order,
+    /// with the contracts created, read from the receipts of the transactions without `to_`.
+    pub fn select_accounts_range(
+        connection: &mut SqliteConnection,
+        lower_block_number: L2Level,
+        upper_block_number: L2Level,
+    ) -> StoreResult<Vec<TransactionAccountsRow>> {
+        let rows: Vec<(L2Level, i32, TxHash, Address, Option<Address>)> = transactions
+            .filter(block_number.between(lower_block_number, upper_block_number))
+            .order((block_number.asc(), index_.asc()))
+            .select((block_number, index_, hash, from_, to_))
+            .load(connection)?;
+        let creations = transactions
+            .filter(block_number.between(lower_block_number, upper_block_number))
+            .filter(to_.is_null())
+            .select((block_number, index_, receipt_fields))
+            .load::<(L2Level, i32, Bytes)>(connection)?
+            .into_iter()
+            .map(|(block_n, index, fields)| {
+                let fields = ReceiptFields::decode(fields.as_bytes())?;
+                Ok(((block_n, index), fields.contract_address))
+            })
+            .collect::<StoreResult<BTreeMap<(L2Level, i32), Option<Address>>>>()?;
+        let rows = rows
+            .into_iter()
+            .map(|(block_n, index, h, from, to)| {
+                let contract_address = creations.get(&(block_n, index)).copied().flatten();
+                (block_n, index, h, from, to, contract_address)
+            })
+            .collect();
+        Ok(rows)
     }
 
     pub fn clear_after(
         connection: &mut SqliteConnection,
-        queried_block_number: i32,
-    ) -> QueryResult<usize> {
+        queried_block_number: L2Level,
+    ) -> StoreResult<usize> {
         let cleared_rows = delete(transactions.filter(block_number.gt(queried_block_number)))
             .execute(connection)?;
         Ok(cleared_rows)
@@ -122,64 +250,109 @@ impl Transaction {
 
     pub fn clear_before(
         connection: &mut SqliteConnection,
-        queried_block_number: i32,
-    ) -> QueryResult<usize> {
+        queried_block_number: L2Level,
+    ) -> StoreResult<usize> {
         let cleared_rows = delete(transactions.filter(block_number.lt(queried_block_number)))
             .execute(connection)?;
         Ok(cleared_rows)
     }
 }
 
+fn decode_receipt(row: TransactionRow) -> StoreResult<TransactionReceipt> {
+    let (block_h, block_n, index, h, from, to, fields) = row;
+    Ok(TransactionReceipt {
+        block_hash: block_h,
+        block_number: block_n,
+        transaction_index: index,
+        hash: h,
+        from,
+        to,
+        fields: ReceiptFields::decode(fields.as_bytes())?,
+    })
+}
+
+fn decode_object(row: TransactionRow) -> StoreResult<TransactionObject> {
+    let (block_h, block_n, index, h, from, to, fields) = row;
+    Ok(TransactionObject {
+        block_hash: block_h,
+        block_number: block_n,
+        transaction_index: index,
+        hash: h,
+        from,
+        to,
+        fields: ObjectFields::decode(fields.as_bytes())?,
+    })
+}
+
 #[cfg(test)]
 mod transaction_test {
     use super::*;
-    use crate::dieselsqlite::{establish_connection, models::Block};
-    use diesel::result::Error;
+    use crate::dieselsqlite::{
+        error::StoreError,
+        establish_connection,
+        models::Block,
+        types::{Bloom, H256, Quantity},
+    };
 
     #[test]
     fn test_transaction_insert_select_clear() {
         let connection = &mut establish_connection().unwrap();
 
-        connection.test_transaction::<_, Error, _>(|conn| {
-            let inserted_block_hash: Vec<u8> = "block_hash".as_bytes().to_vec();
+        connection.test_transaction::<_, StoreError, _>(|conn| {
+            let inserted_block_hash = BlockHash([1u8; 32]);
             let inserted_block_number = Block::top_level(conn)? + 1;
             let inserted_index_ = 0;
-            let inserted_hash: Vec<u8> = "transactionHash".as_bytes().to_vec();
-            let inserted_from_ = "from_".as_bytes().to_vec();
-            let inserted_to_ = Some("to_".as_bytes().to_vec());
-            let inserted_receipt_fields = "receipt_fields".as_bytes().to_vec();
-            let inserted_object_fields = "object_fields".as_bytes().to_vec();
+            let inserted_hash = TxHash([1u8; 32]);
+            let inserted_from_ = Address([1u8; 20]);
+            let inserted_to_ = Some(Address([2u8; 20]));
+            let inserted_receipt_fields = ReceiptFields {
+                cumulative_gas_used: Quantity(21_000),
+                effective_gas_price: Quantity(1_000_000_000),
+                gas_used: Quantity(21_000),
+                logs: Vec::new(),
+                logs_bloom: Bloom([0u8; 256]),
+                transaction_type: 2,
+                status: 1,
+                contract_address: None,
+            };
+            let inserted_object_fields = ObjectFields {
+                gas: Quantity(21_000),
+                gas_price: Quantity(1_000_000_000),
+                input: Bytes::default(),
+                nonce: Quantity(0),
+                value: Quantity(1),
+                v: Quantity(0),
+                r: H256([1u8; 32]),
+                s: H256([2u8; 32]),
+            };
 
             let transaction = Transaction {
-                block_hash: inserted_block_hash.clone(),
+                block_hash: inserted_block_hash,
                 block_number: inserted_block_number,
                 index_: inserted_index_,
-                hash: inserted_hash.clone(),
-                from_: inserted_from_.clone(),
-                to_: inserted_to_.clone(),
-                receipt_fields: inserted_receipt_fields.clone(),
-                object_fields: inserted_object_fields.clone(),
+                hash: inserted_hash,
+                from_: inserted_from_,
+                to_: inserted_to_,
+                receipt_fields: inserted_receipt_fields.encode(),
+                object_fields: inserted_object_fields.encode(),
             };
 
             let _ = transaction.insert(conn);
 
-            let (
-                select_block_hash,
-                select_block_number,
-                select_index_,
-                select_hash,
-                select_from_,
-                select_to_,
-                select_receipt_fields,
-            ) = Transaction::select_receipt(conn, &inserted_hash)?;
-
-            assert_eq!(select_block_hash, inserted_block_hash);
-            assert_eq!(select_block_number, inserted_block_number);
-            assert_eq!(select_index_, inserted_index_);
-            assert_eq!(select_hash, inserted_hash);
-            assert_eq!(select_from_, inserted_from_);
-            assert_eq!(select_to_, inserted_to_);
-            assert_eq!(select_receipt_fields, inserted_receipt_fields);
+            let receipt = Transaction::select_receipt(conn, &inserted_hash)?;
+
+            assert_eq!(receipt.block_hash, inserted_block_hash);
+            assert_eq!(receipt.block_number, inserted_block_number);
+            assert_eq!(receipt.transaction_index, inserted_index_);
+            assert_eq!(receipt.hash, inserted_hash);
+            assert_eq!(receipt.from, inserted_from_);
+            assert_eq!(receipt.to, inserted_to_);
+            assert_eq!(receipt.fields, inserted_receipt_fields);
+
+            let object = Transaction::select_object(conn, &inserted_hash)?;
+
+            assert_eq!(object.hash, inserted_hash);
+            assert_eq!(object.fields, inserted_object_fields);
 
             let expected_rows_cleared = 1;
 
@@ -194,7 +367,7 @@ mod transaction_test {
     fn test_transaction_selects() {
         let connection = &mut establish_connection().unwrap();
 
-        connection.test_transaction::<_, Error, _>(|conn| {
+        connection.test_transaction::<_, StoreError, _>(|conn| {
             let select_block_level = Block::top_level(conn)?;
 
             let receipts =
@@ -202,32 +375,15 @@ mod transaction_test {
 
             let objects = Transaction::select_objects_from_block_number(conn, select_block_level)?;
 
-            let length = receipts.len();
-            for i in 0..length {
-                let (vec_block_hash, vec_index_, vec_hash, vec_from_, vec_to_, vec_receipt_fields) =
-                    (&receipts[i]).clone();
-                let (_, _, _, _, vec_object_fields) = (&objects[i]).clone();
-
-                let (
-                    select_block_hash,
-                    select_block_number,
-                    select_index_,
-                    select_hash,
-                    select_from_,
-                    select_to_,
-                    select_receipt_fields,
-                ) = Transaction::select_receipt(conn, &vec_hash)?;
-                let (_, _, _, _, _, _, select_object_fields) =
-                    Transaction::select_object(conn, &vec_hash)?;
-
-                assert_eq!(select_block_hash, vec_block_hash);
-                assert_eq!(select_block_number, select_block_level);
-                assert_eq!(select_index_, vec_index_);
-                assert_eq!(select_hash, vec_hash);
-                assert_eq!(select_from_, vec_from_);
-                assert_eq!(select_to_, vec_to_);
-                assert_eq!(select_receipt_fields, vec_receipt_fields);
-                assert_eq!(select_object_fields, vec_object_fields);
+            assert_eq!(receipts.len(), objects.len());
+            for (receipt, object) in receipts.iter().zip(&objects) {
+                let select_receipt = Transaction::select_receipt(conn, &receipt.hash)?;
+                let select_object = Transaction::select_object(conn, &receipt.hash)?;
+
+                assert_eq!(&select_receipt, receipt);
+                assert_eq!(&select_object, object);
+                assert_eq!(select_receipt.block_number, select_block_level);
+                assert_eq!(select_object.hash, receipt.hash);
             }
 
             Ok(())
@@ -238,27 +394,27 @@ mod transaction_test {
     fn test_transaction_batch_insert_clear() {
         let connection = &mut establish_connection().unwrap();
 
-        connection.test_transaction::<_, Error, _>(|conn| {
+        connection.test_transaction::<_, StoreError, _>(|conn| {
             let iter = 5;
-            let inserted_block_hash: Vec<u8> = "block_hash".as_bytes().to_vec();
+            let inserted_block_hash = BlockHash([1u8; 32]);
             let inserted_block_number = Block::top_level(conn)? + 1;
             let inserted_index_ = 0;
-            let inserted_from_ = "from_".as_bytes().to_vec();
-            let inserted_to_ = Some("to_".as_bytes().to_vec());
-            let inserted_receipt_fields = "receipt_fields".as_bytes().to_vec();
-            let inserted_object_fields = "object_fields".as_bytes().to_vec();
+            let inserted_from_ = Address([1u8; 20]);
+            let inserted_to_ = Some(Address([2u8; 20]));
+            let inserted_receipt_fields = Bytes::from("receipt_fields".as_bytes());
+            let inserted_object_fields = Bytes::from("object_fields".as_bytes());
 
             let mut batch = Vec::new();
             for i in 0..iter {
-                let inserted_hash: Vec<u8> = format!("transactionHash:{i}").as_bytes().to_vec();
+                let inserted_hash = TxHash([i as u8; 32]);
 
                 let transaction = Transaction {
-                    block_hash: inserted_block_hash.clone(),
+                    block_hash: inserted_block_hash,
                     block_number: inserted_block_number,
                     index_: inserted_index_,
-                    hash: inserted_hash.clone(),
-                    from_: inserted_from_.clone(),
-                    to_: inserted_to_.clone(),
+                    hash: inserted_hash,
+                    from_: inserted_from_,
+                    to_: inserted_to_,
                     receipt_fields: inserted_receipt_fields.clone(),
                     object_fields: inserted_object_fields.clone(),
                 };
@@ -268,6 +424,11 @@ mod transaction_test {
 
             Transaction::batch_insert(conn, &batch)?;
 
+            assert!(matches!(
+                Transaction::select_receipt(conn, &TxHash([0u8; 32])),
+                Err(StoreError::Decode { .. })
+            ));
+
             let expected_rows_cleared = iter as usize;
 
             let rows_cleared = Transaction::clear_after(conn, inserted_block_number - 1)?;
@@ -0,0 +1,652 @@
+use crate::dieselsqlite::{
+    accountindex::AccountDirection,
+    ethrpc::{BlockId, BlockTag, RpcBlockTransactions, RpcTransaction},
+    jsonrpc::ParamField,
+    types::{
+        Address, BlockHash, Bloom, Bytes, ContextHash, H256, L1Level, L2Level, Quantity, RootHash,
+        Signature, TxHash,
+    },
+};
+use serde_json::{Value, json};
+
+/// Version of the OpenRPC specification of the documents.
+pub const OPENRPC_VERSION: &str = "1.2.6";
+/// Method answering the OpenRPC document of a server.
+pub const DISCOVER_METHOD: &str = "rpc.discover";
+
+/// JSON Schema of the values of a type, as sent and received by the server.
+pub trait Schema {
+    /// Whether `null` is a value of the type, so it can be omitted as a param.
+    const OPTIONAL: bool = false;
+
+    fn schema() -> Value;
+}
+
+macro_rules! integer_schema {
+    ($($type:ty),*) => {
+        $(impl Schema for $type {
+            fn schema() -> Value {
+                json!({ "type": "integer" })
+            }
+        })*
+    };
+}
+
+integer_schema!(u8, u64, u128, usize, i32, i64, L1Level, L2Level);
+
+macro_rules! fixed_bytes_schema {
+    ($($type:ident),*) => {
+        $(impl Schema for $type {
+            fn schema() -> Value {
+                json!({
+                    "type": "string",
+                    "pattern": format!("^0x[0-9a-fA-F]{{{}}}$", 2 * $type::LENGTH),
+                })
+            }
+        })*
+    };
+}
+
+fixed_bytes_schema!(
+    BlockHash,
+    TxHash,
+    Address,
+    ContextHash,
+    RootHash,
+    H256,
+    Bloom,
+    Signature
+);
+
+impl Schema for Quantity {
+    fn schema() -> Value {
+        json!({ "type": "string", "pattern": "^0x[0-9a-fA-F]+$" })
+    }
+}
+
+impl Schema for Bytes {
+    fn schema() -> Value {
+        json!({ "type": "string", "pattern": "^0x([0-9a-fA-F]{2})*$" })
+    }
+}
+
+impl Schema for bool {
+    fn schema() -> Value {
+        json!({ "type": "boolean" })
+    }
+}
+
+impl Schema for String {
+    fn schema() -> Value {
+        json!({ "type": "string" })
+    }
+}
+
+impl Schema for Value {
+    fn schema() -> Value {
+        json!({})
+    }
+}
+
+impl<T: Schema> Schema for Option<T> {
+    const OPTIONAL: bool = true;
+
+    fn schema() -> Value {
+        json!({ "oneOf": [T::schema(), { "type": "null" }] })
+    }
+}
+
+impl<T: Schema> Schema for Vec<T> {
+    fn schema() -> Value {
+        json!({ "type": "array", "items": T::schema() })
+    }
+}
+
+macro_rules! tuple_schema {
+    ($($type:ident),*) => {
+        impl<$($type: Schema),*> Schema for ($($type,)*) {
+            fn schema() -> Value {
+                let items = vec![$($type::schema()),*];
+                json!({
+                    "type": "array",
+                    "minItems": items.len(),
+                    "items": items,
+                    "additionalItems": false,
+                })
+            }
+        }
+    };
+}
+
+tuple_schema!(A, B);
+tuple_schema!(A, B, C);
+
+/// Schema of an object with the given properties, all required unless
+/// they are optional.
+pub fn object_schema(properties: &[(String, Value, bool)]) -> Value {
+    let required = properties
+        .iter()
+        .filter(|(_, _, optional)| !optional)
+        .map(|(name, _, _)| name.as_str())
+        .collect::<Vec<&str>>();
+    let properties = properties
+        .iter()
+        .map(|(name, schema, _)| (name.clone(), schema.clone()))
+        .collect::<serde_json::Map<String, Value>>();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+/// Arguments of the `serde(...)` attributes among `attributes`, split on the
+/// commas outside of quotes, as their key and unquoted value:
+/// `serde(default, rename = "x")` gives `default` and `rename` with `x`.
+fn serde_arguments<'a>(attributes: &[&'a str]) -> Vec<(&'a str, Option<&'a str>)> {
+    let mut arguments = Vec::new();
+    for attribute in attributes {
+        let Some(inner) = attribute
+            .strip_prefix("serde(")
+            .and_then(|inner| inner.strip_suffix(')'))
+        else {
+            continue;
+        };
+        let mut quoted = false;
+        let mut start = 0;
+        for (index, character) in inner.char_indices().chain([(inner.len(), ',')]) {
+            match character {
+                '"' => quoted = !quoted,
+                ',' if !quoted => {
+                    let argument = inner[start..index].trim();
+                    start = index + 1;
+                    if argument.is_empty() {
+                        continue;
+                    }
+                    arguments.push(match argument.split_once('=') {
+                        Some((key, value)) => (key.trim(), Some(value.trim().trim_matches('"'))),
+                        None => (argument, None),
+                    });
+                }
+                _ => (),
+            }
+        }
+    }
+    arguments
+}
+
+fn camel_case(name: &str) -> String {
+    let mut words = name.split('_');
+    let first = words.next().unwrap_or_default().to_string();
+    words.fold(first, |mut camel, word| {
+        let mut chars = word.chars();
+        if let Some(initial) = chars.next() {
+            camel.extend(initial.to_uppercase());
+            camel.push_str(chars.as_str());
+        }
+        camel
+    })
+}
+
+/// Properties of `object_schema` of the field `name` of type `T`, given the
+/// serde attributes of its struct and of the field: the properties of `T`
+/// when the field is flattened, otherwise the field under its serialized
+/// name, optional when `T` is, when it defaults or when it can be skipped.
+/// Panics on the serde attributes the schema doesn't follow, so they can't
+/// be silently left out of the document.
+pub fn field_properties<T: Schema>(
+    name: &str,
+    struct_attributes: &[&str],
+    attributes: &[&str],
+) -> Vec<(String, Value, bool)> {
+    let mut rename_all = None;
+    for (key, value) in serde_arguments(struct_attributes) {
+        match (key, value) {
+            ("rename_all", Some(case @ ("camelCase" | "snake_case"))) => rename_all = Some(case),
+            ("deny_unknown_fields", None) => (),
+            _ => panic!(
+                "Unsupported serde attribute {} of the struct of {}",
+                key, name
+            ),
+        }
+    }
+    let mut flatten = false;
+    let mut rename = None;
+    let mut optional = T::OPTIONAL;
+    for (key, value) in serde_arguments(attributes) {
+        match (key, value) {
+            ("flatten", None) => flatten = true,
+            ("rename", Some(value)) => rename = Some(value),
+            ("default", _) | ("skip_serializing_if", Some(_)) => optional = true,
+            _ => panic!("Unsupported serde attribute {} of the field {}", key, name),
+        }
+    }
+
+    if flatten {
+        let schema = T::schema();
+        let required = schema["required"].as_array().cloned().unwrap_or_default();
+        let properties = schema["properties"]
+            .as_object()
+            .cloned()
+            .unwrap_or_default();
+        properties
+            .into_iter()
+            .map(|(name, schema)| {
+                let optional = !required.contains(&Value::String(name.clone()));
+                (name, schema, optional)
+            })
+            .collect()
+    } else {
+        let name = match (rename, rename_all) {
+            (Some(rename), _) => rename.to_string(),
+            (None, Some("camelCase")) => camel_case(name),
+            (None, _) => name.to_string(),
+        };
+        vec![(name, T::schema(), optional)]
+    }
+}
+
+impl Schema for AccountDirection {
+    fn schema() -> Value {
+        json!({ "type": "string", "enum": ["sent", "received", "any"] })
+    }
+}
+
+impl Schema for BlockTag {
+    fn schema() -> Value {
+        json!({
+            "oneOf": [
+                Quantity::schema(),
+                { "type": "string", "enum": ["earliest", "latest", "pending", "safe", "finalized"] },
+            ]
+        })
+    }
+}
+
+impl Schema for BlockId {
+    fn schema() -> Value {
+        json!({ "oneOf": [BlockHash::schema(), BlockTag::schema()] })
+    }
+}
+
+impl Schema for RpcBlockTransactions {
+    fn schema() -> Value {
+        json!({ "oneOf": [Vec::<TxHash>::schema(), Vec::<RpcTransaction>::schema()] })
+    }
+}
+
+/// Declares a struct sent or received as an object of its fields, and
+/// implements `Schema` for it. The attributes of the struct and its fields
+/// are kept and followed by the schema: `#[serde(flatten)]` fields contribute
+/// the properties of their type, `rename` and `rename_all = "camelCase"`
+/// name the properties, and `default` or `skip_serializing_if` fields are
+/// optional. Other serde attributes make `schema` panic.
+#[macro_export]
+macro_rules! rpc_object {
+    ($(#[$meta:meta])* $vis:vis struct $name:ident {
+        $($(#[$field_meta:meta])* $field_vis:vis $field:ident: $type:ty),* $(,)?
+    }) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $($(#[$field_meta])* $field_vis $field: $type,)*
+        }
+
+        impl $crate::dieselsqlite::openrpc::Schema for $name {
+            fn schema() -> serde_json::Value {
+                let struct_attributes: &[&str] = &[$(stringify!($meta)),*];
+                let mut properties = Vec::new();
+                $(properties.extend($crate::dieselsqlite::openrpc::field_properties::<$type>(
+                    stringify!($field),
+                    struct_attributes,
+                    &[$(stringify!($field_meta)),*],
+                ));)*
+                $crate::dieselsqlite::openrpc::object_schema(&properties)
+            }
+        }
+    };
+}
+
+/// Declares the struct of the result of a method, serialized as an object of
+/// its fields, and implements `Schema` for it.
+#[macro_export]
+macro_rules! rpc_result {
+    ($(#[$meta:meta])* $vis:vis struct $name:ident {
+        $($(#[$field_meta:meta])* $field:ident: $type:ty),* $(,)?
+    }) => {
+        $crate::rpc_object! {
+            $(#[$meta])*
+            #[derive(Debug, Clone, PartialEq, serde::Serialize)]
+            $vis struct $name {
+                $($(#[$field_meta])* pub $field: $type,)*
+            }
+        }
+    };
+}
+
+/// OpenRPC description of a method, whose params can be given by position
+/// or by name.
+pub fn method_description(name: &str, params: &[ParamField], result: Value) -> Value {
+    let params = params
+        .iter()
+        .map(|param| {
+            json!({
+                "name": param.name,
+                "required": param.required,
+                "schema": (param.schema)(),
+            })
+        })
+        .collect::<Vec<Value>>();
+    json!({
+        "name": name,
+        "paramStructure": "either",
+        "params": params,
+        "result": { "name": "result", "schema": result },
+    })
+}
+
+/// OpenRPC document of a server serving `methods`.
+pub fn document(title: &str, version: &str, methods: Vec<Value>) -> Value {
+    json!({
+        "openrpc": OPENRPC_VERSION,
+        "info": { "title": title, "version": version },
+        "methods": methods,
+    })
+}
+
+/// Whether `value` is valid against the subset of JSON Schema used by
+/// `openrpc`.
+#[cfg(test)]
+pub(crate) fn conforms(schema: &Value, value: &Value) -> bool {
+    if let Some(schemas) = schema["oneOf"].as_array() {
+        return schemas.iter().any(|schema| conforms(schema, value));
+    }
+    if let Some(values) = schema["enum"].as_array() {
+        return values.contains(value);
+    }
+    match (schema["type"].as_str(), value) {
+        (None, _) | (Some("null"), Value::Null) | (Some("boolean"), Value::Bool(_)) => true,
+        (Some("integer"), Value::Number(number)) => number.is_i64() || number.is_u64(),
+        (Some("string"), Value::String(string)) => {
+            let Some(pattern) = schema["pattern"].as_str() else {
+                return true;
+            };
+            let Some(digits) = string.strip_prefix("0x") else {
+                return false;
+            };
+            let length = pattern
+                .strip_prefix("^0x[0-9a-fA-F]{")
+                .and_then(|pattern| pattern.strip_suffix("}$"));
+            digits.chars().all(|digit| digit.is_ascii_hexdigit())
+                && match (pattern, length) {
+                    ("^0x[0-9a-fA-F]+$", _) => !digits.is_empty(),
+                    ("^0x([0-9a-fA-F]{2})*$", _) => digits.len() % 2 == 0,
+                    (_, Some(length)) => digits.len() == length.parse::<usize>().unwrap(),
+                    (pattern, None) => panic!("Unsupported pattern {}", pattern),
+                }
+        }
+        (Some("array"), Value::Array(values)) => match &schema["items"] {
+            Value::Array(schemas) => {
+                schemas.len() == values.len()
+                    && schemas
+                        .iter()
+                        .zip(values)
+                        .all(|(schema, value)| conforms(schema, value))
+            }
+            schema => values.iter().all(|value| conforms(schema, value)),
+        },
+        (Some("object"), Value::Object(values)) => {
+            schema["required"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .all(|name| values.contains_key(name.as_str().unwrap()))
+                && values.iter().all(|(name, value)| {
+                    schema["properties"]
+                        .get(name)
+                        .is_some_and(|schema| conforms(schema, value))
+                })
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod openrpc_test {
+    use super::*;
+    use crate::dieselsqlite::{
+        decoder::{TransactionObject, TransactionReceipt},
+        fixture::fixture_store,
+        jsonrpc::Params,
+        logs::{FilteredLog, LogFilter},
+        models::Transaction,
+    };
+    use serde::de::DeserializeOwned;
+
+    crate::rpc_params! {
+        struct RangeParams {
+            lowerlevel: i32,
+            upperlevel: Option<i32>,
+        }
+    }
+
+    crate::rpc_result! {
+        struct HashResult {
+            id: L2Level,
+            hash: BlockHash,
+        }
+    }
+
+    #[test]
+    fn test_schema() {
+        assert_eq!(
+            HashResult::schema(),
+            json!({
+                "type": "object",
+                "properties": {
+                    "id": {"type": "integer"},
+                    "hash": {"type": "string", "pattern": "^0x[0-9a-fA-F]{64}$"},
+                },
+                "required": ["id", "hash"],
+                "additionalProperties": false,
+            })
+        );
+        assert_eq!(
+            method_description("select_range", RangeParams::FIELDS, HashResult::schema())["params"],
+            json!([
+                {"name": "lowerlevel", "required": true, "schema": {"type": "integer"}},
+                {
+                    "name": "upperlevel",
+                    "required": false,
+                    "schema": {"oneOf": [{"type": "integer"}, {"type": "null"}]},
+                },
+            ])
+        );
+        assert_eq!(
+            <(i32, Bytes)>::schema()["items"][1],
+            json!({"type": "string", "pattern": "^0x([0-9a-fA-F]{2})*$"})
+        );
+        assert_eq!(
+            LogFilter::schema()["required"],
+            json!(["from_block", "to_block"])
+        );
+
+        let object = TransactionObject::schema();
+
+        assert_eq!(object["properties"]["value"], Quantity::schema());
+        assert!(object["properties"].get("fields").is_none());
+        assert_eq!(
+            object["required"].as_array().unwrap().len(),
+            object["properties"].as_object().unwrap().len() - 1
+        );
+        assert_eq!(
+            TransactionReceipt::schema()["properties"]["logs"]["items"]["required"],
+            json!(["address", "topics", "data"])
+        );
+        assert!(
+            FilteredLog::schema()["required"]
+                .as_array()
+                .unwrap()
+                .contains(&json!("address"))
+        );
+    }
+
+    #[test]
+    fn test_serde_arguments() {
+        assert_eq!(
+            serde_arguments(&[
+                "doc = \"Level, or tag\"",
+                "serde(default, rename = \"a, b\")",
+                "serde(flatten)",
+            ]),
+            vec![
+                ("default", None),
+                ("rename", Some("a, b")),
+                ("flatten", None)
+            ]
+        );
+        assert_eq!(
+            field_properties::<i32>(
+                "block_number",
+                &["serde(rename_all = \"camelCase\", deny_unknown_fields)"],
+                &["serde(default, rename = \"number\")"],
+            ),
+            vec![("number".to_string(), json!({"type": "integer"}), true)]
+        );
+        assert_eq!(
+            field_properties::<i32>(
+                "block_number",
+                &["serde(deny_unknown_fields, rename_all = \"camelCase\")"],
+                &[],
+            ),
+            vec![("blockNumber".to_string(), json!({"type": "integer"}), false)]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Unsupported serde attribute skip_deserializing")]
+    fn test_unsupported_serde_attribute() {
+        field_properties::<i32>("level", &[], &["serde(default, skip_deserializing)"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unsupported serde attribute rename_all")]
+    fn test_unsupported_rename_all() {
+        field_properties::<i32>("level", &["serde(rename_all = \"PascalCase\")"], &[]);
+    }
+
+    /// Values of `T` read from `values`, checking that the schema of `T`
+    /// accepts exactly the ones that can be read.
+    fn read<T: Schema + DeserializeOwned>(values: &[Value]) -> Vec<T> {
+        values
+            .iter()
+            .filter_map(|value| {
+                let read = serde_json::from_value::<T>(value.clone()).ok();
+                assert_eq!(
+                    conforms(&T::schema(), value),
+                    read.is_some(),
+                    "{} in {}",
+                    value,
+                    T::schema()
+                );
+                read
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_enum_schemas() {
+        let directions = [
+            AccountDirection::Sent,
+            AccountDirection::Received,
+            AccountDirection::Any,
+        ];
+        for direction in directions {
+            match direction {
+                AccountDirection::Sent | AccountDirection::Received | AccountDirection::Any => (),
+            }
+        }
+        let mut values = AccountDirection::schema()["enum"]
+            .as_array()
+            .unwrap()
+            .clone();
+        values.extend([json!("all"), json!(1)]);
+
+        assert_eq!(read::<AccountDirection>(&values), directions);
+
+        let tags = [
+            BlockTag::Number(Quantity(0x1a)),
+            BlockTag::Earliest,
+            BlockTag::Latest,
+            BlockTag::Pending,
+            BlockTag::Safe,
+            BlockTag::Finalized,
+        ];
+        for tag in tags {
+            match tag {
+                BlockTag::Number(_)
+                | BlockTag::Earliest
+                | BlockTag::Latest
+                | BlockTag::Pending
+                | BlockTag::Safe
+                | BlockTag::Finalized => (),
+            }
+        }
+        let mut values = vec![json!("0x1a")];
+        values.extend(
+            BlockTag::schema()["oneOf"][1]["enum"]
+                .as_array()
+                .unwrap()
+                .clone(),
+        );
+        let invalid = [
+            json!("0x"),
+            json!("1a"),
+            json!("0xg"),
+            json!("newest"),
+            json!(26),
+        ];
+        values.extend(invalid.clone());
+
+        assert_eq!(read::<BlockTag>(&values), tags);
+        assert_eq!(
+            read::<BlockTag>(&[json!("0x01a")]),
+            [BlockTag::Number(Quantity(0x1a))]
+        );
+
+        let hash = BlockHash([1u8; 32]);
+        let ids = [BlockId::Hash(hash), BlockId::Tag(BlockTag::Latest)];
+        for id in ids {
+            match id {
+                BlockId::Hash(_) | BlockId::Tag(_) => (),
+            }
+        }
+        let mut values = vec![json!(hash), json!("latest")];
+        values.extend(invalid);
+
+        assert_eq!(read::<BlockId>(&values), ids);
+
+        let (mut store, config) = fixture_store(1, 1);
+        let object = &Transaction::select_objects_from_block_number(&mut store, config.start_level)
+            .unwrap()[0];
+        let receipt =
+            &Transaction::select_receipts_from_block_number(&mut store, config.start_level)
+                .unwrap()[0];
+        let transactions = [
+            RpcBlockTransactions::Hashes(vec![object.hash]),
+            RpcBlockTransactions::Full(vec![RpcTransaction::new(object, receipt).unwrap()]),
+        ];
+        for transactions in &transactions {
+            match transactions {
+                RpcBlockTransactions::Hashes(_) | RpcBlockTransactions::Full(_) => (),
+            }
+            let value = serde_json::to_value(transactions).unwrap();
+
+            assert!(
+                conforms(&RpcBlockTransactions::schema(), &value),
+                "{}",
+                value
+            );
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use crate::dieselsqlite::store::StoreConfig;
+use diesel::{
+    connection::SimpleConnection,
+    prelude::*,
+    r2d2::{ConnectionManager, CustomizeConnection, Error, Pool, PoolError, PooledConnection},
+};
+use dotenvy::dotenv;
+use std::{env, num::ParseIntError, time::Duration};
+
+pub const POOL_SIZE_KEY: &str = "POOL_SIZE";
+
+/// Store connections shared between threads.
+pub type StorePool = Pool<ConnectionManager<SqliteConnection>>;
+pub type PooledStoreConnection = PooledConnection<ConnectionManager<SqliteConnection>>;
+
+/// Size and health checks of a `StorePool`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolConfig {
+    max_size: u32,
+    min_idle: Option<u32>,
+    connection_timeout: Duration,
+    test_on_check_out: bool,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        PoolConfig {
+            max_size: 8,
+            min_idle: None,
+            connection_timeout: Duration::from_secs(30),
+            test_on_check_out: true,
+        }
+    }
+}
+
+impl PoolConfig {
+    /// Default configuration, with the maximum size taken from `POOL_SIZE` if set.
+    pub fn from_env() -> Result<PoolConfig, ParseIntError> {
+        dotenv().ok();
+        let config = PoolConfig::default();
+        match env::var(POOL_SIZE_KEY) {
+            Ok(max_size) => Ok(config.max_size(max_size.parse()?)),
+            Err(_) => Ok(config),
+        }
+    }
+
+    pub fn max_size(mut self, max_size: u32) -> PoolConfig {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Connections kept open when idle, defaults to `max_size`.
+    pub fn min_idle(mut self, min_idle: u32) -> PoolConfig {
+        self.min_idle = Some(min_idle);
+        self
+    }
+
+    /// How long `get` waits for a connection before failing.
+    pub fn connection_timeout(mut self, connection_timeout: Duration) -> PoolConfig {
+        self.connection_timeout = connection_timeout;
+        self
+    }
+
+    /// Pings connections before handing them out, replacing the broken ones.
+    pub fn test_on_check_out(mut self, test_on_check_out: bool) -> PoolConfig {
+        self.test_on_check_out = test_on_check_out;
+        self
+    }
+}
+
+/// Applies the store's PRAGMAs to each connection opened by the pool.
+#[derive(Debug)]
+struct StoreCustomizer {
+    pragmas: String,
+}
+
+impl CustomizeConnection<SqliteConnection, Error> for StoreCustomizer {
+    fn on_acquire(&self, connection: &mut SqliteConnection) -> Result<(), Error> {
+        connection
+            .batch_execute(&self.pragmas)
+            .map_err(Error::QueryError)
+    }
+}
+
+/// Opens a pool of connections to the store, each one set up like
+/// `Store::open` would.
+pub fn build_pool(store: &StoreConfig, pool: &PoolConfig) -> Result<StorePool, PoolError> {
+    let manager = ConnectionManager::<SqliteConnection>::new(store.uri());
+    Pool::builder()
+        .max_size(pool.max_size)
+        .min_idle(pool.min_idle)
+        .connection_timeout(pool.connection_timeout)
+        .test_on_check_out(pool.test_on_check_out)
+        .connection_customizer(Box::new(StoreCustomizer {
+            pragmas: store.pragmas(),
+        }))
+        .build(manager)
+}
+
+#[cfg(test)]
+mod pool_test {
+    use super::*;
+    use crate::dieselsqlite::{
+        bootstrap::create_store,
+        connection::DEFAULT_BUSY_TIMEOUT,
+        fixture::TempStoreFile,
+        models::{Block, Metadata},
+        types::{BlockHash, Bytes, L2Level},
+    };
+    use diesel::sql_types::BigInt;
+    use std::thread;
+
+    #[derive(QueryableByName)]
+    struct BusyTimeout {
+        #[diesel(sql_type = BigInt)]
+        timeout: i64,
+    }
+
+    #[test]
+    fn test_build_pool() {
+        let store_file = TempStoreFile::new("pool");
+        let path = store_file.path();
+
+        let writer = &mut create_store(path).unwrap();
+        Metadata::insert_history_mode(writer, "rolling:1").unwrap();
+        Block {
+            level: L2Level(0),
+            hash: BlockHash([0u8; 32]),
+            block: Bytes::from("block".as_bytes()),
+        }
+        .insert(writer)
+        .unwrap();
+
+        let store = StoreConfig::new(path)
+            .read_only(true)
+            .busy_timeout(DEFAULT_BUSY_TIMEOUT);
+        let pool = build_pool(&store, &PoolConfig::default().max_size(2)).unwrap();
+
+        assert_eq!(pool.max_size(), 2);
+
+        let handles = (0..4)
+            .map(|_| {
+                let pool = pool.clone();
+                thread::spawn(move || {
+                    let connection = &mut pool.get().unwrap();
+                    let busy_timeout: BusyTimeout =
+                        diesel::sql_query("SELECT timeout FROM pragma_busy_timeout()")
+                            .get_result(connection)
+                            .unwrap();
+                    (busy_timeout.timeout, Block::top_level(connection).unwrap())
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), (5000, L2Level(0)));
+        }
+
+        let connection = &mut pool.get().unwrap();
+
+        assert!(Metadata::insert_history_mode(connection, "archive").is_err());
+    }
+}
@@ -0,0 +1,339 @@
+use crate::dieselsqlite::{
+    error::{StoreError, StoreResult},
+    models::*,
+    types::L2Level,
+};
+use diesel::{prelude::*, result::Error::RollbackTransaction};
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+/// Retention used by the node when the history mode doesn't specify one.
+pub const DEFAULT_RETENTION: u32 = 14;
+
+/// History mode of the node, as stored in the `history_mode` metadata
+/// (`archive`, `full:<retention>` or `rolling:<retention>`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryMode {
+    Archive,
+    Full(u32),
+    Rolling(u32),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct InvalidHistoryMode {
+    pub history_mode: String,
+}
+
+impl Display for InvalidHistoryMode {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Invalid history mode:{}", self.history_mode)
+    }
+}
+
+impl std::error::Error for InvalidHistoryMode {}
+
+impl FromStr for HistoryMode {
+    type Err = InvalidHistoryMode;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidHistoryMode {
+            history_mode: s.to_string(),
+        };
+        let (mode, retention) = match s.split_once(':') {
+            Some((mode, retention)) => {
+                let retention = retention.parse::<u32>().map_err(|_| invalid())?;
+                if retention == 0 {
+                    return Err(invalid());
+                }
+                (mode, Some(retention))
+            }
+            None => (s, None),
+        };
+        match (mode, retention) {
+            ("archive", None) => Ok(HistoryMode::Archive),
+            ("full", retention) => Ok(HistoryMode::Full(retention.unwrap_or(DEFAULT_RETENTION))),
+            ("rolling", retention) => {
+                Ok(HistoryMode::Rolling(retention.unwrap_or(DEFAULT_RETENTION)))
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl Display for HistoryMode {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            HistoryMode::Archive => write!(f, "archive"),
+            HistoryMode::Full(retention) => write!(f, "full:{}", retention),
+            HistoryMode::Rolling(retention) => write!(f, "rolling:{}", retention),
+        }
+    }
+}
+
+impl HistoryMode {
+    pub fn from_metadata(connection: &mut SqliteConnection) -> StoreResult<HistoryMode> {
+        let history_mode = Metadata::get_history_mode(connection)?;
+        history_mode
+            .parse()
+            .map_err(|error: InvalidHistoryMode| StoreError::Decode {
+                message: error.to_string(),
+            })
+    }
+}
+
+/// Number of rows deleted (or that would be deleted on a dry run) in each
+/// table by [`prune_before`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PruneReport {
+    pub history_mode: HistoryMode,
+    pub boundary: Option<L2Level>,
+    pub dry_run: bool,
+    pub blueprints: usize,
+    pub blocks: usize,
+    pub transactions: usize,
+    pub context_hashes: usize,
+    pub kernel_upgrades: usize,
+    pub sequencer_upgrades: usize,
+    pub delayed_transactions: usize,
+    pub l1_l2_levels_relationships: usize,
+    pub l1_l2_finalized_levels: usize,
+    pub irmin_chunks: usize,
+}
+
+impl PruneReport {
+    fn empty(history_mode: HistoryMode, boundary: Option<L2Level>, dry_run: bool) -> PruneReport {
+        PruneReport {
+            history_mode,
+            boundary,
+            dry_run,
+            blueprints: 0,
+            blocks: 0,
+            transactions: 0,
+            context_hashes: 0,
+            kernel_upgrades: 0,
+            sequencer_upgrades: 0,
+            delayed_transactions: 0,
+            l1_l2_levels_relationships: 0,
+            l1_l2_finalized_levels: 0,
+            irmin_chunks: 0,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.blueprints
+            + self.blocks
+            + self.transactions
+            + self.context_hashes
+            + self.kernel_upgrades
+            + self.sequencer_upgrades
+            + self.delayed_transactions
+            + self.l1_l2_levels_relationships
+            + self.l1_l2_finalized_levels
+            + self.irmin_chunks
+    }
+}
+
+/// First level kept by the history mode, or `None` if nothing can be pruned yet.
+///
+/// The node splits its context into irmin chunks as it goes, and keeps the
+/// last `retention` of them: the boundary is the level at which the oldest
+/// retained chunk starts, capped by the top block so the head is never pruned.
+pub fn retention_boundary(
+    connection: &mut SqliteConnection,
+    history_mode: HistoryMode,
+) -> StoreResult<Option<L2Level>> {
+    let retention = match history_mode {
+        HistoryMode::Archive => return Ok(None),
+        HistoryMode::Full(retention) | HistoryMode::Rolling(retention) => retention,
+    };
+    let (chunk_level, _) = match IrminChunk::nth(connection, i64::from(retention) - 1) {
+        Err(StoreError::NotFound) => return Ok(None),
+        nth => nth?,
+    };
+    let top_level = match Block::top_level(connection) {
+        Err(StoreError::NotFound) => return Ok(None),
+        top_level => top_level?,
+    };
+    Ok(Some(chunk_level.min(top_level)))
+}
+
+/// Prunes the store according to the history mode in the metadata table.
+///
+/// In rolling mode everything older than the retention boundary is deleted.
+/// In full mode the chain is kept and only the context hashes and irmin
+/// chunks are pruned. Archive stores are left untouched. With `dry_run`,
+/// the deletions are rolled back and the report tells what would be deleted.
+pub fn prune_before(connection: &mut SqliteConnection, dry_run: bool) -> StoreResult<PruneReport> {
+    let history_mode = HistoryMode::from_metadata(connection)?;
+    prune_with_history_mode(connection, history_mode, dry_run)
+}
+
+pub fn prune_with_history_mode(
+    connection: &mut SqliteConnection,
+    history_mode: HistoryMode,
+    dry_run: bool,
+) -> StoreResult<PruneReport> {
+    let mut dry_run_report = None;
+
+    let result = connection.transaction::<_, StoreError, _>(|conn| {
+        let boundary = retention_boundary(conn, history_mode)?;
+        let mut report = PruneReport::empty(history_mode, boundary, dry_run);
+
+        if let Some(boundary) = boundary {
+            if let HistoryMode::Rolling(_) = history_mode {
+                report.blueprints = Blueprint::clear_before(conn, boundary)?;
+                report.blocks = Block::clear_before(conn, boundary)?;
+                report.transactions = Transaction::clear_before(conn, boundary)?;
+                report.kernel_upgrades = KernelUpgrade::clear_before(conn, boundary)?;
+                report.sequencer_upgrades = SequencerUpgrade::clear_before(conn, boundary)?;
+                report.delayed_transactions = DelayedTransaction::clear_before(conn, boundary)?;
+                report.l1_l2_levels_relationships =
+                    L1L2LevelRelationship::clear_before(conn, boundary)?;
+                report.l1_l2_finalized_levels = L1L2FinalizedLevel::clear_before(conn, boundary)?;
+            }
+            report.context_hashes = ContextHashRow::clear_before(conn, boundary)?;
+            report.irmin_chunks = IrminChunk::clear_before_included(conn, boundary - 1)?;
+        }
+
+        if dry_run {
+            dry_run_report = Some(report);
+            return Err(RollbackTransaction.into());
+        }
+        Ok(report)
+    });
+
+    match (result, dry_run_report) {
+        (
+            Err(StoreError::Database {
+                error: RollbackTransaction,
+            }),
+            Some(report),
+        ) => Ok(report),
+        (result, _) => result,
+    }
+}
+
+#[cfg(test)]
+mod prune_test {
+    use super::*;
+    use crate::dieselsqlite::establish_connection;
+
+    #[test]
+    fn test_history_mode_parse() {
+        assert_eq!("archive".parse(), Ok(HistoryMode::Archive));
+        assert_eq!("full:7".parse(), Ok(HistoryMode::Full(7)));
+        assert_eq!("rolling:1".parse(), Ok(HistoryMode::Rolling(1)));
+        assert_eq!(
+            "rolling".parse(),
+            Ok(HistoryMode::Rolling(DEFAULT_RETENTION))
+        );
+
+        for invalid in [
+            "archive:1",
+            "rolling:0",
+            "rolling:-1",
+            "full:a",
+            "experimental",
+        ] {
+            assert!(invalid.parse::<HistoryMode>().is_err());
+        }
+
+        for history_mode in [
+            HistoryMode::Archive,
+            HistoryMode::Full(3),
+            HistoryMode::Rolling(1),
+        ] {
+            assert_eq!(history_mode.to_string().parse(), Ok(history_mode));
+        }
+    }
+
+    #[test]
+    fn test_prune_before_rolling_dry_run() {
+        let connection = &mut establish_connection().unwrap();
+
+        connection.test_transaction::<_, StoreError, _>(|conn| {
+            IrminChunk::clear(conn)?;
+
+            let base_level = Block::base_level(conn)?;
+            let top_level = Block::top_level(conn)?;
+            let boundary = top_level - 1;
+
+            for chunk_level in [base_level, boundary] {
+                IrminChunk {
+                    level: chunk_level,
+                    timestamp: i64::from(chunk_level.0),
+                }
+                .insert(conn)?;
+            }
+
+            Metadata::insert_history_mode(conn, "rolling:1")?;
+
+            let expected_blocks = (boundary.0 - base_level.0) as usize;
+
+            let dry_run_report = prune_before(conn, true)?;
+
+            assert_eq!(dry_run_report.boundary, Some(boundary));
+            assert_eq!(dry_run_report.blocks, expected_blocks);
+            assert_eq!(dry_run_report.irmin_chunks, 1);
+            assert_eq!(Block::base_level(conn)?, base_level);
+
+            let report = prune_before(conn, false)?;
+
+            assert_eq!(
+                PruneReport {
+                    dry_run: true,
+                    ..report
+                },
+                dry_run_report
+            );
+            assert_eq!(Block::base_level(conn)?, boundary);
+            assert_eq!(Blueprint::base_level(conn)?, boundary);
+            assert_eq!(IrminChunk::nth(conn, 0)?, (boundary, i64::from(boundary.0)));
+
+            let second_report = prune_before(conn, false)?;
+
+            assert_eq!(second_report.total(), 0);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_prune_before_full_and_archive() {
+        let connection = &mut establish_connection().unwrap();
+
+        connection.test_transaction::<_, StoreError, _>(|conn| {
+            IrminChunk::clear(conn)?;
+
+            let base_level = Block::base_level(conn)?;
+            let top_level = Block::top_level(conn)?;
+
+            IrminChunk {
+                level: top_level,
+                timestamp: 0,
+            }
+            .insert(conn)?;
+
+            let archive_report = prune_with_history_mode(conn, HistoryMode::Archive, false)?;
+
+            assert_eq!(archive_report.boundary, None);
+            assert_eq!(archive_report.total(), 0);
+
+            let not_enough_chunks_report =
+                prune_with_history_mode(conn, HistoryMode::Full(2), false)?;
+
+            assert_eq!(not_enough_chunks_report.boundary, None);
+
+            let full_report = prune_with_history_mode(conn, HistoryMode::Full(1), false)?;
+
+            assert_eq!(full_report.boundary, Some(top_level));
+            assert_eq!(full_report.blocks, 0);
+            assert_eq!(Block::base_level(conn)?, base_level);
+            assert_eq!(ContextHashRow::get_earliest(conn)?.0, top_level);
+
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,181 @@
+use crate::dieselsqlite::{
+    error::{StoreError, StoreResult},
+    models::*,
+    types::L2Level,
+};
+use diesel::prelude::*;
+
+/// Number of rows removed (or nullified for upgrades) in each table by [`reset_after`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ResetReport {
+    pub blueprints: usize,
+    pub blocks: usize,
+    pub transactions: usize,
+    pub context_hashes: usize,
+    pub kernel_upgrades_cleared: usize,
+    pub kernel_upgrades_nullified: usize,
+    pub sequencer_upgrades_cleared: usize,
+    pub sequencer_upgrades_nullified: usize,
+    pub delayed_transactions: usize,
+    pub l1_l2_levels_relationships: usize,
+    pub l1_l2_finalized_levels: usize,
+    pub irmin_chunks: usize,
+}
+
+impl ResetReport {
+    pub fn total(&self) -> usize {
+        self.blueprints
+            + self.blocks
+            + self.transactions
+            + self.context_hashes
+            + self.kernel_upgrades_cleared
+            + self.kernel_upgrades_nullified
+            + self.sequencer_upgrades_cleared
+            + self.sequencer_upgrades_nullified
+            + self.delayed_transactions
+            + self.l1_l2_levels_relationships
+            + self.l1_l2_finalized_levels
+            + self.irmin_chunks
+    }
+}
+
+/// Rolls the store back to `level`, as the node does on a reorg: everything
+/// recorded after `level` is removed and upgrades applied after `level` are
+/// marked as unapplied again. All the tables are reset in a single transaction.
+pub fn reset_after(connection: &mut SqliteConnection, level: L2Level) -> StoreResult<ResetReport> {
+    connection.transaction::<_, StoreError, _>(|conn| {
+        let blueprints = Blueprint::clear_after(conn, level)?;
+        let context_hashes = ContextHashRow::clear_after(conn, level)?;
+        let kernel_upgrades_cleared = KernelUpgrade::clear_after(conn, level)?;
+        let kernel_upgrades_nullified = KernelUpgrade::nullify_after(conn, level)?;
+        let sequencer_upgrades_cleared = SequencerUpgrade::clear_after(conn, level)?;
+        let sequencer_upgrades_nullified = SequencerUpgrade::nullify_after(conn, level)?;
+        let delayed_transactions = DelayedTransaction::clear_after(conn, level)?;
+        let l1_l2_levels_relationships = L1L2LevelRelationship::clear_after(conn, level)?;
+        let l1_l2_finalized_levels = L1L2FinalizedLevel::clear_after(conn, level)?;
+        let blocks = Block::clear_after(conn, level)?;
+        let transactions = Transaction::clear_after(conn, level)?;
+        let irmin_chunks = IrminChunk::clear_after(conn, level)?;
+
+        Ok(ResetReport {
+            blueprints,
+            blocks,
+            transactions,
+            context_hashes,
+            kernel_upgrades_cleared,
+            kernel_upgrades_nullified,
+            sequencer_upgrades_cleared,
+            sequencer_upgrades_nullified,
+            delayed_transactions,
+            l1_l2_levels_relationships,
+            l1_l2_finalized_levels,
+            irmin_chunks,
+        })
+    })
+}
+
+#[cfg(test)]
+mod reset_test {
+    use super::*;
+    use crate::dieselsqlite::{
+        establish_connection,
+        types::{self, Address, BlockHash, Bytes, RootHash, TxHash},
+    };
+
+    #[test]
+    fn test_reset_after() {
+        let connection = &mut establish_connection().unwrap();
+
+        connection.test_transaction::<_, StoreError, _>(|conn| {
+            let reset_level = Block::top_level(conn)?;
+            let iter = 3;
+
+            // Anything already above the reset level must not skew the counts.
+            reset_after(conn, reset_level)?;
+            KernelUpgrade::clear_after(conn, L2Level(i32::MIN))?;
+
+            for i in 1..=iter {
+                let inserted_level = reset_level + i;
+                let inserted_hash = [i as u8; 32];
+
+                Blueprint {
+                    id: inserted_level,
+                    payload: Bytes::from("payload".as_bytes()),
+                    timestamp: i64::from(i),
+                }
+                .insert(conn)?;
+                Block {
+                    level: inserted_level,
+                    hash: BlockHash(inserted_hash),
+                    block: Bytes::from("block".as_bytes()),
+                }
+                .insert(conn)?;
+                Transaction {
+                    block_hash: BlockHash(inserted_hash),
+                    block_number: inserted_level,
+                    index_: 0,
+                    hash: TxHash(inserted_hash),
+                    from_: Address([1u8; 20]),
+                    to_: None,
+                    receipt_fields: Bytes::from("receipt_fields".as_bytes()),
+                    object_fields: Bytes::from("object_fields".as_bytes()),
+                }
+                .insert(conn)?;
+                ContextHashRow {
+                    id: inserted_level,
+                    context_hash: types::ContextHash(inserted_hash),
+                }
+                .insert(conn)?;
+                IrminChunk {
+                    level: inserted_level,
+                    timestamp: i64::from(i),
+                }
+                .insert(conn)?;
+            }
+
+            let kernel_upgrade = KernelUpgrade {
+                injected_before: reset_level - 1,
+                root_hash: RootHash([1u8; 33]),
+                activation_timestamp: 0,
+                applied_before: None,
+            };
+            kernel_upgrade.insert(conn)?;
+            KernelUpgrade::record_apply(conn, reset_level + 1)?;
+
+            let sequencer_upgrade = SequencerUpgrade {
+                injected_before: reset_level + 1,
+                sequencer: Bytes::from("sequencer".as_bytes()),
+                pool_address: Address([2u8; 20]),
+                activation_timestamp: 0,
+                applied_before: None,
+            };
+            sequencer_upgrade.insert(conn)?;
+
+            let expected_cleared = iter as usize;
+
+            let report = reset_after(conn, reset_level)?;
+
+            assert_eq!(report.blueprints, expected_cleared);
+            assert_eq!(report.blocks, expected_cleared);
+            assert_eq!(report.transactions, expected_cleared);
+            assert_eq!(report.context_hashes, expected_cleared);
+            assert_eq!(report.irmin_chunks, expected_cleared);
+            assert_eq!(report.kernel_upgrades_cleared, 0);
+            assert_eq!(report.kernel_upgrades_nullified, 1);
+            assert_eq!(report.sequencer_upgrades_cleared, 1);
+            assert_eq!(report.sequencer_upgrades_nullified, 0);
+
+            let (latest_unapplied_injected_before, _, _) =
+                KernelUpgrade::get_latest_unapplied(conn)?;
+
+            assert_eq!(latest_unapplied_injected_before, reset_level - 1);
+            assert_eq!(Block::top_level(conn)?, reset_level);
+
+            let second_report = reset_after(conn, reset_level)?;
+
+            assert_eq!(second_report.total(), 0);
+
+            Ok(())
+        })
+    }
+}
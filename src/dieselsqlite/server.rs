@@ -0,0 +1,1249 @@
+use crate::{
+    dieselsqlite::{
+        accountindex::{ACCOUNT_INDEX_URL_KEY, AccountDirection, AccountTransaction},
+        decoder::{TransactionObject, TransactionReceipt},
+        error::StoreError,
+        jsonrpc::{self, ParamField, Params, RpcError, SERVER_ERROR, UNAVAILABLE, parse_params},
+        logs::{FilteredLog, LogFilter, select_logs},
+        models::*,
+        openrpc::{self, DISCOVER_METHOD},
+        pool::{PoolConfig, StorePool, build_pool},
+        sidecar,
+        store::StoreConfig,
+        topicindex::TOPIC_INDEX_URL_KEY,
+        types::{self, Address, BlockHash, Bytes, L1Level, L2Level, RootHash, TxHash},
+    },
+    rpc_params, rpc_result,
+};
+use actix_web::{App, HttpResponse, HttpServer, Result, post, web};
+use diesel::{ConnectionError, prelude::*, r2d2::PoolError};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::{
+    collections::BTreeMap,
+    env,
+    fmt::{self, Display, Formatter},
+    io,
+    marker::PhantomData,
+    net::ToSocketAddrs,
+};
+
+/// Address of the `server` binary.
+pub const DEFAULT_ADDRESS: (&str, u16) = ("127.0.0.1", 8080);
+
+/// Most logs returned by `select_logs`, beyond which the query fails.
+const MAX_LOGS: usize = 10_000;
+
+/// Most transactions of a page of `select_account_transactions`, larger
+/// limits are lowered to it.
+const MAX_ACCOUNT_TRANSACTIONS: i64 = 1_000;
+
+// Results of the methods, serialized as objects of their fields.
+
+rpc_result! {
+    struct BlueprintResult {
+        payload: Bytes,
+        timestamp: i64,
+    }
+}
+
+rpc_result! {
+    struct BlueprintRangeResult {
+        idandpayloads: Vec<(L2Level, Bytes)>,
+    }
+}
+
+rpc_result! {
+    struct BlockResult {
+        block: Bytes,
+    }
+}
+
+rpc_result! {
+    struct BlockHashResult {
+        hash: BlockHash,
+    }
+}
+
+rpc_result! {
+    struct BlockIdResult {
+        id: L2Level,
+    }
+}
+
+rpc_result! {
+    struct PendingConfirmationResult {
+        hash: BlockHash,
+    }
+}
+
+rpc_result! {
+    struct PendingConfirmationCountResult {
+        count: i64,
+    }
+}
+
+rpc_result! {
+    struct TransactionReceiptResult {
+        receipt: TransactionReceipt,
+    }
+}
+
+rpc_result! {
+    struct TransactionReceiptsResult {
+        receipts: Vec<TransactionReceipt>,
+    }
+}
+
+rpc_result! {
+    struct TransactionObjectResult {
+        object: TransactionObject,
+    }
+}
+
+rpc_result! {
+    struct TransactionObjectsResult {
+        objects: Vec<TransactionObject>,
+    }
+}
+
+rpc_result! {
+    struct ContextHashResult {
+        context_hash: types::ContextHash,
+    }
+}
+
+rpc_result! {
+    struct ContextHashGetResult {
+        id: L2Level,
+        context_hash: types::ContextHash,
+    }
+}
+
+rpc_result! {
+    struct MetadataSmartRollupAddressResult {
+        address: String,
+    }
+}
+
+rpc_result! {
+    struct MetadataHistoryModeResult {
+        history_mode: String,
+    }
+}
+
+rpc_result! {
+    struct ActivationLevelsResult {
+        activation_levels: Vec<L2Level>,
+    }
+}
+
+rpc_result! {
+    struct KernelUpgradeLatestUnappliedResult {
+        injected_before: L2Level,
+        root_hash: RootHash,
+        activation_timestamp: i64,
+    }
+}
+
+rpc_result! {
+    struct KernelUpgradeInjectedResult {
+        root_hash: RootHash,
+        activation_timestamp: i64,
+    }
+}
+
+rpc_result! {
+    struct SequencerUpgradeLatestUnappliedResult {
+        injected_before: L2Level,
+        sequencer: Bytes,
+        pool_address: Address,
+        activation_timestamp: i64,
+    }
+}
+
+rpc_result! {
+    struct SequencerUpgradeInjectedResult {
+        sequencer: Bytes,
+        pool_address: Address,
+        activation_timestamp: i64,
+    }
+}
+
+rpc_result! {
+    struct DelayedTransactionSelectResult {
+        payload: Bytes,
+    }
+}
+
+rpc_result! {
+    struct L1L2LevelRelationshipGetResult {
+        latest_l2_level: L2Level,
+        l1_level: L1Level,
+    }
+}
+
+rpc_result! {
+    struct L1L2FinalizedLevelGetResult {
+        start_l2_level: L2Level,
+        end_l2_level: L2Level,
+    }
+}
+
+rpc_result! {
+    struct L1L2FinalizedLevelResult {
+        level: L2Level,
+    }
+}
+
+rpc_result! {
+    struct L1L2FinalizedL1LevelResult {
+        level: L1Level,
+    }
+}
+
+rpc_result! {
+    struct L1L2FinalizedLevelLastResult {
+        l1_level: L1Level,
+        start_l2_level: L2Level,
+        end_l2_level: L2Level,
+    }
+}
+
+rpc_result! {
+    struct L1L2FinalizedLevelListResult {
+        levels: Vec<FinalizedLevelRow>,
+    }
+}
+
+rpc_result! {
+    struct IrminChunkResult {
+        level: L2Level,
+        timestamp: i64,
+    }
+}
+
+rpc_result! {
+    struct BlockStorageModeResult {
+        legacy: i32,
+    }
+}
+
+rpc_result! {
+    struct CurrentMigrationIdResult {
+        id: i32,
+    }
+}
+
+rpc_result! {
+    struct SchemasResult {
+        sqls: Vec<String>,
+    }
+}
+
+rpc_result! {
+    struct TableExistsResult {
+        table_exists: bool,
+    }
+}
+
+rpc_result! {
+    struct AccountTransactionsResult {
+        transactions: Vec<AccountTransaction>,
+    }
+}
+
+rpc_result! {
+    struct LogsResult {
+        logs: Vec<FilteredLog>,
+    }
+}
+
+/// Pools of the store and of the indexes served by the methods.
+pub struct Pools {
+    pub store: StorePool,
+    pub account_index: Option<StorePool>,
+    pub topic_index: Option<StorePool>,
+}
+
+impl Pools {
+    pub fn new(store: StorePool) -> Pools {
+        Pools {
+            store,
+            account_index: None,
+            topic_index: None,
+        }
+    }
+
+    /// Opens the store read-only, and the indexes whose path is set with
+    /// `ACCOUNT_INDEX_URL` and `TOPIC_INDEX_URL`.
+    pub fn open(store: &StoreConfig, pool_config: &PoolConfig) -> Result<Pools, PoolError> {
+        let index = |key| {
+            env::var(key)
+                .ok()
+                .map(|path| build_pool(&StoreConfig::new(&path).into_read_only(), pool_config))
+                .transpose()
+        };
+        Ok(Pools {
+            store: build_pool(&store.clone().into_read_only(), pool_config)?,
+            account_index: index(ACCOUNT_INDEX_URL_KEY)?,
+            topic_index: index(TOPIC_INDEX_URL_KEY)?,
+        })
+    }
+}
+
+/// Errors of the methods, mapped to the error codes of `jsonrpc`.
+#[derive(Debug)]
+pub enum ServerError {
+    StoreError {
+        error: StoreError,
+    },
+    ConnectionError {
+        error: ConnectionError,
+    },
+    PoolError {
+        error: PoolError,
+    },
+    AccountIndexUnavailable,
+    /// The block the account index stopped at isn't in the store anymore.
+    AccountIndexStale,
+}
+
+impl From<ServerError> for RpcError {
+    fn from(error: ServerError) -> RpcError {
+        match error {
+            ServerError::StoreError { error } => error.into(),
+            ServerError::AccountIndexUnavailable | ServerError::AccountIndexStale => {
+                RpcError::new(UNAVAILABLE, error.to_string())
+            }
+            ServerError::ConnectionError { error: _ } | ServerError::PoolError { error: _ } => {
+                RpcError::new(SERVER_ERROR, error.to_string())
+            }
+        }
+    }
+}
+
+impl Display for ServerError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let string = match self {
+            ServerError::StoreError { error } => format!("Store error:{}", error),
+            ServerError::ConnectionError { error } => {
+                format!("Error connecting to the database:{}", error)
+            }
+            ServerError::PoolError { error } => {
+                format!("No database connection available:{}", error)
+            }
+            ServerError::AccountIndexUnavailable => format!(
+                "No account index, set {} to serve one",
+                ACCOUNT_INDEX_URL_KEY
+            ),
+            ServerError::AccountIndexStale => {
+                "The account index doesn't match the store, update it with accountindex".to_string()
+            }
+        };
+        write!(f, "{}", string)
+    }
+}
+
+impl From<StoreError> for ServerError {
+    fn from(error: StoreError) -> ServerError {
+        ServerError::StoreError { error }
+    }
+}
+
+impl From<ConnectionError> for ServerError {
+    fn from(error: ConnectionError) -> ServerError {
+        ServerError::ConnectionError { error }
+    }
+}
+
+impl From<PoolError> for ServerError {
+    fn from(error: PoolError) -> ServerError {
+        ServerError::PoolError { error }
+    }
+}
+
+rpc_params! {
+    struct NoParams {}
+}
+
+rpc_params! {
+    struct LevelParams {
+        level: L2Level,
+    }
+}
+
+rpc_params! {
+    struct LevelRangeParams {
+        lowerlevel: L2Level,
+        upperlevel: L2Level,
+    }
+}
+
+rpc_params! {
+    struct BlockHashParams {
+        hash: BlockHash,
+    }
+}
+
+rpc_params! {
+    struct TxHashParams {
+        hash: TxHash,
+    }
+}
+
+rpc_params! {
+    struct BlockNumberParams {
+        block_number: L2Level,
+    }
+}
+
+rpc_params! {
+    struct InjectedBeforeParams {
+        injected_before: L2Level,
+    }
+}
+
+rpc_params! {
+    struct InjectedAfterParams {
+        injected_after: L2Level,
+    }
+}
+
+rpc_params! {
+    struct L1LevelParams {
+        l1_level: L1Level,
+    }
+}
+
+rpc_params! {
+    struct L2LevelParams {
+        l2_level: L2Level,
+    }
+}
+
+rpc_params! {
+    struct L1LevelRangeParams {
+        start_l1: L1Level,
+        end_l1: L1Level,
+    }
+}
+
+rpc_params! {
+    struct L2LevelRangeParams {
+        start_l2: L2Level,
+        end_l2: L2Level,
+    }
+}
+
+rpc_params! {
+    struct OffsetParams {
+        offset: i64,
+    }
+}
+
+rpc_params! {
+    struct TableNameParams {
+        table_name: String,
+    }
+}
+
+rpc_params! {
+    struct AccountTransactionsParams {
+        address: Address,
+        direction: AccountDirection,
+        lowerlevel: L2Level,
+        upperlevel: L2Level,
+        offset: i64,
+        limit: i64,
+    }
+}
+
+rpc_params! {
+    struct LogsParams {
+        filter: LogFilter,
+    }
+}
+
+/// Method served by the server.
+pub trait Handler: Send + Sync {
+    /// Answers a call with the given params.
+    fn call(&self, pools: &Pools, params: Value) -> Result<Value, RpcError>;
+
+    /// Params of the method, described in the OpenRPC document.
+    fn params(&self) -> &'static [ParamField] {
+        &[]
+    }
+
+    /// JSON Schema of the result of the method, any value by default.
+    fn result(&self) -> Value {
+        json!({})
+    }
+}
+
+/// Method reading params `P` and answering from the pools.
+struct PoolsHandler<P, R, F> {
+    handler: F,
+    signature: PhantomData<fn(P) -> R>,
+}
+
+impl<P, R, F> Handler for PoolsHandler<P, R, F>
+where
+    P: Params,
+    R: Serialize + openrpc::Schema,
+    F: Fn(&Pools, P) -> Result<R, ServerError> + Send + Sync,
+{
+    fn call(&self, pools: &Pools, params: Value) -> Result<Value, RpcError> {
+        let params = parse_params(params)?;
+        let result = (self.handler)(pools, params)?;
+        serde_json::to_value(result).map_err(RpcError::internal)
+    }
+
+    fn params(&self) -> &'static [ParamField] {
+        P::FIELDS
+    }
+
+    fn result(&self) -> Value {
+        R::schema()
+    }
+}
+
+/// Methods of a server by name. `store_methods` has the methods of the
+/// `server` binary, which other methods can be added to.
+#[derive(Default)]
+pub struct MethodRegistry {
+    methods: BTreeMap<String, Box<dyn Handler>>,
+}
+
+impl MethodRegistry {
+    pub fn new() -> MethodRegistry {
+        MethodRegistry::default()
+    }
+
+    /// Registers a method querying the store with its typed params, declared
+    /// with `rpc_params!`, and its result, declared with `rpc_result!`. A
+    /// method of the same name is replaced.
+    pub fn register<P, R, F>(&mut self, name: &str, handler: F) -> &mut MethodRegistry
+    where
+        P: Params + 'static,
+        R: Serialize + openrpc::Schema + 'static,
+        F: Fn(&mut SqliteConnection, &Pools, P) -> Result<R, ServerError> + Send + Sync + 'static,
+    {
+        self.register_with_pools(name, move |pools: &Pools, params| {
+            let connection = &mut pools.store.get()?;
+            handler(connection, pools, params)
+        })
+    }
+
+    /// Registers a method like `register`, checking out the connections it
+    /// needs from the pools itself, e.g. only once an index is available.
+    pub fn register_with_pools<P, R, F>(&mut self, name: &str, handler: F) -> &mut MethodRegistry
+    where
+        P: Params + 'static,
+        R: Serialize + openrpc::Schema + 'static,
+        F: Fn(&Pools, P) -> Result<R, ServerError> + Send + Sync + 'static,
+    {
+        self.register_handler(
+            name,
+            PoolsHandler {
+                handler,
+                signature: PhantomData,
+            },
+        )
+    }
+
+    /// Registers a method answering from the raw params.
+    pub fn register_handler(
+        &mut self,
+        name: &str,
+        handler: impl Handler + 'static,
+    ) -> &mut MethodRegistry {
+        self.methods.insert(name.to_string(), Box::new(handler));
+        self
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.methods.keys().map(String::as_str)
+    }
+
+    /// OpenRPC document describing the params and result of each method.
+    pub fn discover(&self) -> Value {
+        let methods = self
+            .methods
+            .iter()
+            .map(|(name, handler)| {
+                openrpc::method_description(name, handler.params(), handler.result())
+            })
+            .collect();
+        openrpc::document(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"), methods)
+    }
+
+    /// Answers a call, `rpc.discover` with the OpenRPC document unless a
+    /// method of that name is registered.
+    pub fn call(&self, pools: &Pools, method: &str, params: Value) -> Result<Value, RpcError> {
+        match self.methods.get(method) {
+            Some(handler) => handler.call(pools, params),
+            None if method == DISCOVER_METHOD => {
+                parse_params::<NoParams>(params)?;
+                Ok(self.discover())
+            }
+            None => Err(RpcError::method_not_found(method)),
+        }
+    }
+
+    /// Answers a JSON-RPC 2.0 body, see `jsonrpc::handle`.
+    pub fn answer(&self, pools: &Pools, body: &[u8]) -> Option<Value> {
+        jsonrpc::handle(body, |method, params| self.call(pools, method, params))
+    }
+}
+
+/// Methods of the `server` binary.
+pub fn store_methods() -> MethodRegistry {
+    let mut registry = MethodRegistry::new();
+    registry
+        .register("select_blueprint", |connection, _, params: LevelParams| {
+            let (payload, timestamp) = Blueprint::select(connection, params.level)?;
+            Ok(BlueprintResult { payload, timestamp })
+        })
+        .register(
+            "select_blueprint_range",
+            |connection, _, params: LevelRangeParams| {
+                let idandpayloads =
+                    Blueprint::select_range(connection, params.lowerlevel, params.upperlevel)?;
+                Ok(BlueprintRangeResult { idandpayloads })
+            },
+        )
+        .register(
+            "select_block_with_level",
+            |connection, _, params: LevelParams| {
+                let block = Block::select_with_level(connection, params.level)?;
+                Ok(BlockResult { block })
+            },
+        )
+        .register(
+            "select_block_with_hash",
+            |connection, _, params: BlockHashParams| {
+                let block = Block::select_with_hash(connection, &params.hash)?;
+                Ok(BlockResult { block })
+            },
+        )
+        .register(
+            "select_block_hash_of_number",
+            |connection, _, params: LevelParams| {
+                let hash = Block::select_hash_of_number(connection, params.level)?;
+                Ok(BlockHashResult { hash })
+            },
+        )
+        .register(
+            "select_block_number_of_hash",
+            |connection, _, params: BlockHashParams| {
+                let id = Block::select_number_of_hash(connection, &params.hash)?;
+                Ok(BlockIdResult { id })
+            },
+        )
+        .register(
+            "select_pending_confirmation_with_level",
+            |connection, _, params: LevelParams| {
+                let hash = PendingConfirmation::select_with_level(connection, params.level)?;
+                Ok(PendingConfirmationResult { hash })
+            },
+        )
+        .register(
+            "count_pending_confirmations",
+            |connection, _, _: NoParams| {
+                let count = PendingConfirmation::count(connection)?;
+                Ok(PendingConfirmationCountResult { count })
+            },
+        )
+        .register(
+            "select_transaction_receipt",
+            |connection, _, params: TxHashParams| {
+                let receipt = Transaction::select_receipt(connection, &params.hash)?;
+                Ok(TransactionReceiptResult { receipt })
+            },
+        )
+        .register(
+            "select_transaction_receipts_from_block_number",
+            |connection, _, params: BlockNumberParams| {
+                let receipts = Transaction::select_receipts_from_block_number(
+                    connection,
+                    params.block_number,
+                )?;
+                Ok(TransactionReceiptsResult { receipts })
+            },
+        )
+        .register(
+            "select_transaction_object",
+            |connection, _, params: TxHashParams| {
+                let object = Transaction::select_object(connection, &params.hash)?;
+                Ok(TransactionObjectResult { object })
+            },
+        )
+        .register(
+            "select_transaction_objects_from_block_number",
+            |connection, _, params: BlockNumberParams| {
+                let objects =
+                    Transaction::select_objects_from_block_number(connection, params.block_number)?;
+                Ok(TransactionObjectsResult { objects })
+            },
+        )
+        .register(
+            "select_context_hash",
+            |connection, _, params: LevelParams| {
+                let context_hash = ContextHashRow::select(connection, params.level)?;
+                Ok(ContextHashResult { context_hash })
+            },
+        )
+        .register("get_latest_context_hash", |connection, _, _: NoParams| {
+            let (id, context_hash) = ContextHashRow::get_latest(connection)?;
+            Ok(ContextHashGetResult { id, context_hash })
+        })
+        .register("get_earliest_context_hash", |connection, _, _: NoParams| {
+            let (id, context_hash) = ContextHashRow::get_earliest(connection)?;
+            Ok(ContextHashGetResult { id, context_hash })
+        })
+        .register("get_smart_rollup_address", |connection, _, _: NoParams| {
+            let address = Metadata::get_smart_rollup_address(connection)?;
+            Ok(MetadataSmartRollupAddressResult { address })
+        })
+        .register("get_history_mode", |connection, _, _: NoParams| {
+            let history_mode = Metadata::get_history_mode(connection)?;
+            Ok(MetadataHistoryModeResult { history_mode })
+        })
+        .register(
+            "kernel_upgrade_activation_levels",
+            |connection, _, _: NoParams| {
+                let activation_levels = KernelUpgrade::activation_levels(connection)?;
+                Ok(ActivationLevelsResult { activation_levels })
+            },
+        )
+        .register(
+            "get_latest_unapplied_kernel_upgrade",
+            |connection, _, _: NoParams| {
+                let (injected_before, root_hash, activation_timestamp) =
+                    KernelUpgrade::get_latest_unapplied(connection)?;
+                Ok(KernelUpgradeLatestUnappliedResult {
+                    injected_before,
+                    root_hash,
+                    activation_timestamp,
+                })
+            },
+        )
+        .register(
+            "find_kernel_upgrade_injected_before",
+            |connection, _, params: InjectedBeforeParams| {
+                let (root_hash, activation_timestamp) =
+                    KernelUpgrade::find_injected_before(connection, params.injected_before)?;
+                Ok(KernelUpgradeInjectedResult {
+                    root_hash,
+                    activation_timestamp,
+                })
+            },
+        )
+        .register(
+            "find_latest_kernel_upgrade_injected_after",
+            |connection, _, params: InjectedAfterParams| {
+                let (root_hash, activation_timestamp) =
+                    KernelUpgrade::find_latest_injected_after(connection, params.injected_after)?;
+                Ok(KernelUpgradeInjectedResult {
+                    root_hash,
+                    activation_timestamp,
+                })
+            },
+        )
+        .register(
+            "sequencer_upgrade_activation_levels",
+            |connection, _, _: NoParams| {
+                let activation_levels = SequencerUpgrade::activation_levels(connection)?;
+                Ok(ActivationLevelsResult { activation_levels })
+            },
+        )
+        .register(
+            "get_latest_unapplied_sequencer_upgrade",
+            |connection, _, _: NoParams| {
+                let (injected_before, sequencer, pool_address, activation_timestamp) =
+                    SequencerUpgrade::get_latest_unapplied(connection)?;
+                Ok(SequencerUpgradeLatestUnappliedResult {
+                    injected_before,
+                    sequencer,
+                    pool_address,
+                    activation_timestamp,
+                })
+            },
+        )
+        .register(
+            "find_sequencer_upgrade_injected_before",
+            |connection, _, params: InjectedBeforeParams| {
+                let (sequencer, pool_address, activation_timestamp) =
+                    SequencerUpgrade::find_injected_before(connection, params.injected_before)?;
+                Ok(SequencerUpgradeInjectedResult {
+                    sequencer,
+                    pool_address,
+                    activation_timestamp,
+                })
+            },
+        )
+        .register(
+            "find_latest_sequencer_upgrade_injected_after",
+            |connection, _, params: InjectedAfterParams| {
+                let (sequencer, pool_address, activation_timestamp) =
+                    SequencerUpgrade::find_latest_injected_after(
+                        connection,
+                        params.injected_after,
+                    )?;
+                Ok(SequencerUpgradeInjectedResult {
+                    sequencer,
+                    pool_address,
+                    activation_timestamp,
+                })
+            },
+        )
+        .register(
+            "select_delayed_transaction_at_level",
+            |connection, _, params: InjectedBeforeParams| {
+                let payload =
+                    DelayedTransaction::select_at_level(connection, params.injected_before)?;
+                Ok(DelayedTransactionSelectResult { payload })
+            },
+        )
+        .register(
+            "select_delayed_transaction_at_hash",
+            |connection, _, params: TxHashParams| {
+                let payload = DelayedTransaction::select_at_hash(connection, &params.hash)?;
+                Ok(DelayedTransactionSelectResult { payload })
+            },
+        )
+        .register(
+            "get_l1_l2_level_relationship",
+            |connection, _, _: NoParams| {
+                let (latest_l2_level, l1_level) = L1L2LevelRelationship::get(connection)?;
+                Ok(L1L2LevelRelationshipGetResult {
+                    latest_l2_level,
+                    l1_level,
+                })
+            },
+        )
+        .register(
+            "get_l1_l2_finalized_level",
+            |connection, _, params: L1LevelParams| {
+                let (start_l2_level, end_l2_level) =
+                    L1L2FinalizedLevel::get(connection, params.l1_level)?;
+                Ok(L1L2FinalizedLevelGetResult {
+                    start_l2_level,
+                    end_l2_level,
+                })
+            },
+        )
+        .register("last_finalized_l2_level", |connection, _, _: NoParams| {
+            let level = L1L2FinalizedLevel::last_l2_level(connection)?;
+            Ok(L1L2FinalizedLevelResult { level })
+        })
+        .register(
+            "last_l1_l2_finalized_level",
+            |connection, _, _: NoParams| {
+                let (l1_level, start_l2_level, end_l2_level) =
+                    L1L2FinalizedLevel::last(connection)?;
+                Ok(L1L2FinalizedLevelLastResult {
+                    l1_level,
+                    start_l2_level,
+                    end_l2_level,
+                })
+            },
+        )
+        .register(
+            "find_finalized_l1_level",
+            |connection, _, params: L2LevelParams| {
+                let level = L1L2FinalizedLevel::find_l1_level(connection, params.l2_level)?;
+                Ok(L1L2FinalizedL1LevelResult { level })
+            },
+        )
+        .register(
+            "list_l1_l1_finalized_levels_by_l2_levels",
+            |connection, _, params: L2LevelRangeParams| {
+                let levels = L1L2FinalizedLevel::list_by_l2_levels(
+                    connection,
+                    params.start_l2,
+                    params.end_l2,
+                )?;
+                Ok(L1L2FinalizedLevelListResult { levels })
+            },
+        )
+        .register(
+            "list_l1_l1_finalized_levels_by_l1_levels",
+            |connection, _, params: L1LevelRangeParams| {
+                let levels = L1L2FinalizedLevel::list_by_l1_levels(
+                    connection,
+                    params.start_l1,
+                    params.end_l1,
+                )?;
+                Ok(L1L2FinalizedLevelListResult { levels })
+            },
+        )
+        .register("nth_irmin_chunk", |connection, _, params: OffsetParams| {
+            let (level, timestamp) = IrminChunk::nth(connection, params.offset)?;
+            Ok(IrminChunkResult { level, timestamp })
+        })
+        .register("latest_irmin_chunk", |connection, _, _: NoParams| {
+            let (level, timestamp) = IrminChunk::latest(connection)?;
+            Ok(IrminChunkResult { level, timestamp })
+        })
+        .register("block_storage_mode", |connection, _, _: NoParams| {
+            let legacy = BlockStorageMode::legacy(connection)?;
+            Ok(BlockStorageModeResult { legacy })
+        })
+        .register("current_migration", |connection, _, _: NoParams| {
+            let id = Migration::current_migration(connection)?;
+            Ok(CurrentMigrationIdResult { id })
+        })
+        .register("get_all", |connection, _, _: NoParams| {
+            let sqls = Schema::get_all(connection)?;
+            Ok(SchemasResult { sqls })
+        })
+        .register("table_exists", |connection, _, params: TableNameParams| {
+            let table_exists = Schema::table_exists(connection, &params.table_name)?;
+            Ok(TableExistsResult { table_exists })
+        })
+        .register_with_pools(
+            "select_account_transactions",
+            |pools, params: AccountTransactionsParams| {
+                let index = &mut pools
+                    .account_index
+                    .as_ref()
+                    .ok_or(ServerError::AccountIndexUnavailable)?
+                    .get()?;
+                let connection = &mut pools.store.get()?;
+                let indexed_level = sidecar::valid_indexed_level(index, connection)?
+                    .ok_or(ServerError::AccountIndexStale)?;
+                let transactions = AccountTransaction::select(
+                    index,
+                    &params.address,
+                    params.direction,
+                    params.lowerlevel,
+                    params.upperlevel.min(indexed_level),
+                    params.offset,
+                    params.limit.clamp(0, MAX_ACCOUNT_TRANSACTIONS),
+                )?;
+                Ok(AccountTransactionsResult { transactions })
+            },
+        )
+        .register("select_logs", |connection, pools, params: LogsParams| {
+            let mut topic_index = pools
+                .topic_index
+                .as_ref()
+                .map(|pool| pool.get())
+                .transpose()?;
+            let logs = select_logs(
+                connection,
+                topic_index.as_deref_mut(),
+                &params.filter,
+                MAX_LOGS,
+            )?;
+            Ok(LogsResult { logs })
+        });
+    registry
+}
+
+#[post("/")]
+async fn answer_query(
+    pools: web::Data<Pools>,
+    methods: web::Data<MethodRegistry>,
+    body: web::Bytes,
+) -> Result<HttpResponse> {
+    let response = web::block(move || methods.answer(&pools, &body)).await?;
+
+    Ok(match response {
+        Some(response) => HttpResponse::Ok().json(response),
+        None => HttpResponse::NoContent().finish(),
+    })
+}
+
+/// Serves `methods` over JSON-RPC 2.0 at `address`, running the queries on
+/// actix's blocking thread pool.
+pub async fn run(
+    methods: MethodRegistry,
+    pools: Pools,
+    address: impl ToSocketAddrs,
+) -> io::Result<()> {
+    let pools = web::Data::new(pools);
+    let methods = web::Data::new(methods);
+
+    HttpServer::new(move || {
+        App::new()
+            .app_data(pools.clone())
+            .app_data(methods.clone())
+            .service(answer_query)
+    })
+    .bind(address)?
+    .run()
+    .await
+}
+
+#[cfg(test)]
+mod server_test {
+    use super::*;
+    use crate::dieselsqlite::{
+        accountindex::{create_account_index, update_account_index},
+        bootstrap::create_store,
+        fixture::{FixtureConfig, TempStoreFile, generate},
+        jsonrpc::{INVALID_PARAMS, METHOD_NOT_FOUND, NOT_FOUND},
+        schema::blocks,
+    };
+    use serde_json::json;
+    use std::time::Duration;
+
+    rpc_params! {
+        struct CountParams {
+            lowerlevel: L2Level,
+            upperlevel: L2Level,
+        }
+    }
+
+    /// Pools of a fixture store created at `path`, and its first level.
+    fn fixture_pools(path: &str) -> (Pools, L2Level) {
+        let config = FixtureConfig {
+            levels: 10,
+            transactions_per_block: 2,
+            ..FixtureConfig::default()
+        };
+        generate(&mut create_store(path).unwrap(), &config).unwrap();
+        let pool = build_pool(
+            &StoreConfig::new(path).into_read_only(),
+            &PoolConfig::default(),
+        );
+        (Pools::new(pool.unwrap()), config.start_level)
+    }
+
+    #[test]
+    fn test_method_registry() {
+        let store_file = TempStoreFile::new("server");
+        let path = store_file.path();
+        let (pools, start_level) = fixture_pools(path);
+
+        let mut methods = store_methods();
+        methods.register(
+            "count_transactions",
+            |connection, _, params: CountParams| {
+                let rows = Transaction::select_block_hashes_range(
+                    connection,
+                    params.lowerlevel,
+                    params.upperlevel,
+                )?;
+                Ok(rows.len())
+            },
+        );
+
+        assert!(methods.names().any(|name| name == "select_blueprint"));
+        assert_eq!(
+            methods.call(
+                &pools,
+                "count_transactions",
+                json!({"lowerlevel": start_level, "upperlevel": start_level + 4})
+            ),
+            Ok(json!(10))
+        );
+
+        let hash = methods
+            .call(&pools, "select_block_hash_of_number", json!([start_level]))
+            .unwrap();
+
+        assert_eq!(
+            methods.call(&pools, "select_block_number_of_hash", hash),
+            Ok(json!({ "id": start_level }))
+        );
+
+        let responses = methods
+            .answer(
+                &pools,
+                json!([
+                    {"jsonrpc": "2.0", "id": 1, "method": "select_block_hash_of_number", "params": [1]},
+                    {"jsonrpc": "2.0", "id": 2, "method": "count_transactions", "params": [start_level]},
+                    {"jsonrpc": "2.0", "id": 3, "method": "select_accounts"},
+                    {"jsonrpc": "2.0", "id": 4, "method": "select_account_transactions",
+                     "params": [Address([1u8; 20]), "any", 0, 1, 0, 10]},
+                ])
+                .to_string()
+                .as_bytes(),
+            )
+            .unwrap();
+        let codes = responses
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|response| response["error"]["code"].clone())
+            .collect::<Vec<Value>>();
+
+        assert_eq!(
+            codes,
+            vec![
+                json!(NOT_FOUND),
+                json!(INVALID_PARAMS),
+                json!(METHOD_NOT_FOUND),
+                json!(UNAVAILABLE)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_register_with_pools() {
+        let store_file = TempStoreFile::new("pools");
+        let path = store_file.path();
+        create_store(path).unwrap();
+        let pool_config = PoolConfig::default()
+            .max_size(1)
+            .connection_timeout(Duration::from_millis(100));
+        let pools =
+            Pools::new(build_pool(&StoreConfig::new(path).into_read_only(), &pool_config).unwrap());
+        let mut methods = store_methods();
+        methods.register_with_pools("has_topic_index", |pools, _: NoParams| {
+            Ok(pools.topic_index.is_some())
+        });
+        let held = pools.store.get().unwrap();
+
+        assert_eq!(
+            methods.call(&pools, "has_topic_index", json!([])),
+            Ok(json!(false))
+        );
+        assert_eq!(
+            methods
+                .call(
+                    &pools,
+                    "select_account_transactions",
+                    json!([Address([1u8; 20]), "any", 0, 1, 0, 10])
+                )
+                .unwrap_err()
+                .code,
+            UNAVAILABLE
+        );
+        assert_eq!(
+            methods
+                .call(&pools, "count_pending_confirmations", json!([]))
+                .unwrap_err()
+                .code,
+            SERVER_ERROR
+        );
+
+        drop(held);
+    }
+
+    #[test]
+    fn test_select_account_transactions() {
+        let store_file = TempStoreFile::new("account");
+        let path = store_file.path();
+        let index_file = TempStoreFile::new("account-index");
+        let index_path = index_file.path();
+        let (mut pools, start_level) = fixture_pools(path);
+        let store = &mut StoreConfig::new(path).establish().unwrap();
+        update_account_index(
+            &mut create_account_index(index_path).unwrap(),
+            store,
+            Some(start_level + 4),
+        )
+        .unwrap();
+        pools.account_index = Some(
+            build_pool(
+                &StoreConfig::new(index_path).into_read_only(),
+                &PoolConfig::default(),
+            )
+            .unwrap(),
+        );
+        let methods = store_methods();
+        let sender =
+            Transaction::select_receipts_from_block_number(store, start_level).unwrap()[0].from;
+        let select = |limit| {
+            methods.call(
+                &pools,
+                "select_account_transactions",
+                json!([sender, "sent", start_level, start_level + 9, 0, limit]),
+            )
+        };
+
+        assert_eq!(
+            select(100).unwrap()["transactions"][0]["block_number"],
+            json!(start_level)
+        );
+        assert_eq!(select(-1), Ok(json!({ "transactions": [] })));
+
+        diesel::update(blocks::table.find(start_level + 4))
+            .set(blocks::hash.eq(BlockHash([1u8; 32])))
+            .execute(store)
+            .unwrap();
+
+        assert_eq!(select(100).unwrap_err().code, UNAVAILABLE);
+    }
+
+    #[test]
+    fn test_discover() {
+        let store_file = TempStoreFile::new("discover");
+        let path = store_file.path();
+        let (pools, start_level) = fixture_pools(path);
+        let methods = store_methods();
+        let document = methods.call(&pools, DISCOVER_METHOD, json!([])).unwrap();
+        let descriptions = document["methods"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|method| (method["name"].as_str().unwrap(), method))
+            .collect::<BTreeMap<&str, &Value>>();
+
+        assert_eq!(document["openrpc"], json!(openrpc::OPENRPC_VERSION));
+        assert_eq!(
+            descriptions.keys().copied().collect::<Vec<&str>>(),
+            methods.names().collect::<Vec<&str>>()
+        );
+        assert_eq!(
+            descriptions["select_blueprint_range"]["params"],
+            json!([
+                {"name": "lowerlevel", "required": true, "schema": {"type": "integer"}},
+                {"name": "upperlevel", "required": true, "schema": {"type": "integer"}},
+            ])
+        );
+
+        let hash = methods
+            .call(&pools, "select_block_hash_of_number", json!([start_level]))
+            .unwrap();
+        let transaction_hash = methods
+            .call(
+                &pools,
+                "select_transaction_receipts_from_block_number",
+                json!([start_level]),
+            )
+            .unwrap()["receipts"][0]["hash"]
+            .clone();
+        let calls = [
+            ("select_blueprint", json!([start_level])),
+            (
+                "select_blueprint_range",
+                json!([start_level, start_level + 2]),
+            ),
+            ("select_block_with_level", json!([start_level])),
+            ("select_block_number_of_hash", hash),
+            ("select_transaction_receipt", json!([transaction_hash])),
+            ("select_transaction_object", json!([transaction_hash])),
+            (
+                "select_transaction_objects_from_block_number",
+                json!([start_level]),
+            ),
+            ("count_pending_confirmations", json!([])),
+            ("get_history_mode", json!([])),
+            ("kernel_upgrade_activation_levels", json!([])),
+            (
+                "list_l1_l1_finalized_levels_by_l2_levels",
+                json!([start_level, start_level + 10]),
+            ),
+            ("table_exists", json!(["blocks"])),
+            (
+                "select_logs",
+                json!([{"from_block": start_level, "to_block": start_level + 10}]),
+            ),
+        ];
+        for (method, params) in calls {
+            let result = methods.call(&pools, method, params).unwrap();
+            assert!(
+                openrpc::conforms(&descriptions[method]["result"]["schema"], &result),
+                "{} answered {}",
+                method,
+                result
+            );
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use crate::dieselsqlite::{
+    bootstrap::BootstrapError,
+    error::{StoreError, StoreResult},
+    level_batches,
+    models::Block,
+    types::{BlockHash, L2Level},
+};
+use diesel::{connection::SimpleConnection, prelude::*};
+
+/// Number of levels indexed by a single transaction on the index.
+const BATCH_SIZE: i32 = 1000;
+/// Rows inserted by a single statement, to stay below SQLite's limit on bound parameters.
+pub(crate) const INSERT_CHUNK_SIZE: usize = 1000;
+
+/// Table of the last level covered by an index, and its block hash, shared by
+/// the indexes kept in their own database.
+pub const INDEX_STATE_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS index_state (
+        id INTEGER PRIMARY KEY CHECK (id = 0),
+        indexed_level INTEGER NOT NULL,
+        indexed_hash BLOB NOT NULL
+    );";
+
+diesel::table! {
+    index_state (id) {
+        id -> Integer,
+        indexed_level -> Integer,
+        indexed_hash -> Binary,
+    }
+}
+
+/// Index kept in its own database, so the node's store is only ever read,
+/// and updated from the store by batches of levels.
+pub trait SidecarIndex {
+    /// Row of the index.
+    type Row;
+
+    /// Rows of the levels between `lowerlevel` and `upperlevel`, and the
+    /// number of items of the store they index.
+    fn load(
+        store: &mut SqliteConnection,
+        lowerlevel: L2Level,
+        upperlevel: L2Level,
+    ) -> StoreResult<(Vec<Self::Row>, usize)>;
+
+    /// Inserts `rows`, in the transaction of the batch.
+    fn insert(index: &mut SqliteConnection, rows: &[Self::Row]) -> StoreResult<()>;
+
+    /// Deletes all the rows of the index.
+    fn clear(index: &mut SqliteConnection) -> StoreResult<()>;
+}
+
+/// Outcome of `update_index`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexUpdate {
+    /// The index didn't match the store anymore and was built again.
+    pub rebuilt: bool,
+    /// Items of the store (transactions, logs) indexed by the update.
+    pub indexed: usize,
+    /// Last level covered by the index.
+    pub indexed_level: Option<L2Level>,
+}
+
+/// Opens (creating it if needed) the index at `database_url`, with the tables
+/// of `schema`.
+pub fn create_index(database_url: &str, schema: &str) -> Result<SqliteConnection, BootstrapError> {
+    let mut connection = SqliteConnection::establish(database_url)?;
+    connection.batch_execute(INDEX_STATE_SCHEMA)?;
+    connection.batch_execute(schema)?;
+    Ok(connection)
+}
+
+/// Last level covered by the index, and its block hash.
+pub fn indexed_level(index: &mut SqliteConnection) -> StoreResult<Option<(L2Level, BlockHash)>> {
+    use self::index_state::dsl::*;
+
+    let state = index_state
+        .find(0)
+        .select((indexed_level, indexed_hash))
+        .get_result(index)
+        .optional()?;
+    Ok(state)
+}
+
+/// Last level covered by the index, if the index still matches the store there.
+pub fn valid_indexed_level(
+    index: &mut SqliteConnection,
+    store: &mut SqliteConnection,
+) -> StoreResult<Option<L2Level>> {
+    match indexed_level(index)? {
+        Some((level, hash)) => match Block::select_hash_of_number(store, level) {
+            Ok(stored) if stored == hash => Ok(Some(level)),
+            Ok(_) | Err(StoreError::NotFound) => Ok(None),
+            Err(error) => Err(error),
+        },
+        None => Ok(None),
+    }
+}
+
+fn clear_index<I: SidecarIndex>(index: &mut SqliteConnection) -> StoreResult<()> {
+    index.transaction(|conn| {
+        I::clear(conn)?;
+        diesel::delete(index_state::table).execute(conn)?;
+        Ok(())
+    })
+}
+
+/// Indexes `store` from the last indexed level up to `upper_level` (the top
+/// of the store by default), by batches of levels each committed with the
+/// level reached. The index is built again when the block it stopped at isn't
+/// in the store anymore, e.g. after a reset, and built from scratch when it
+/// has no state, dropping the rows left without one.
+pub fn update_index<I: SidecarIndex>(
+    index: &mut SqliteConnection,
+    store: &mut SqliteConnection,
+    upper_level: Option<L2Level>,
+) -> StoreResult<IndexUpdate> {
+    let state = indexed_level(index)?;
+    let mut update = IndexUpdate {
+        indexed_level: state.map(|(level, _)| level),
+        ..IndexUpdate::default()
+    };
+    let top_level = match Block::top_level(store) {
+        Ok(top_level) => top_level,
+        Err(StoreError::NotFound) => return Ok(update),
+        Err(error) => return Err(error),
+    };
+    let upper_level = upper_level.map_or(top_level, |level| level.min(top_level));
+
+    let lower_level = match (state, valid_indexed_level(index, store)?) {
+        (_, Some(level)) => level + 1,
+        (Some(_), None) => {
+            clear_index::<I>(index)?;
+            update.rebuilt = true;
+            update.indexed_level = None;
+            Block::base_level(store)?
+        }
+        (None, None) => {
+            clear_index::<I>(index)?;
+            Block::base_level(store)?
+        }
+    };
+
+    for (batch_start, batch_end) in level_batches(lower_level, upper_level, BATCH_SIZE) {
+        let (hashes, (rows, indexed)) = store.transaction(|conn| {
+            let hashes = Block::select_hashes_range(conn, batch_start, batch_end)?;
+            let rows = I::load(conn, batch_start, batch_end)?;
+            Ok::<_, StoreError>((hashes, rows))
+        })?;
+        let Some(&(last_level, last_hash)) = hashes.last() else {
+            continue;
+        };
+
+        index.transaction(|conn| {
+            I::insert(conn, &rows)?;
+            diesel::replace_into(index_state::table)
+                .values((
+                    index_state::id.eq(0),
+                    index_state::indexed_level.eq(last_level),
+                    index_state::indexed_hash.eq(last_hash),
+                ))
+                .execute(conn)?;
+            Ok::<_, StoreError>(())
+        })?;
+        update.indexed += indexed;
+        update.indexed_level = Some(last_level);
+    }
+    Ok(update)
+}
+
+#[cfg(test)]
+mod sidecar_test {
+    use super::*;
+    use crate::dieselsqlite::{
+        accountindex::{AccountIndex, create_account_index},
+        fixture::fixture_store,
+        schema::blocks,
+    };
+
+    #[test]
+    fn test_update_index_after_reset() {
+        let (mut store, config) = fixture_store(10, 2);
+        let store = &mut store;
+        let top_level = config.top_level();
+        let index = &mut create_account_index(":memory:").unwrap();
+        update_index::<AccountIndex>(index, store, Some(top_level - 1)).unwrap();
+
+        assert_eq!(
+            valid_indexed_level(index, store).unwrap(),
+            Some(top_level - 1)
+        );
+
+        diesel::update(blocks::table.find(top_level - 1))
+            .set(blocks::hash.eq(BlockHash([1u8; 32])))
+            .execute(store)
+            .unwrap();
+
+        assert_eq!(valid_indexed_level(index, store).unwrap(), None);
+
+        let update = update_index::<AccountIndex>(index, store, None).unwrap();
+
+        assert_eq!(
+            update,
+            IndexUpdate {
+                rebuilt: true,
+                indexed: 20,
+                indexed_level: Some(top_level),
+            }
+        );
+        assert_eq!(valid_indexed_level(index, store).unwrap(), Some(top_level));
+    }
+
+    #[test]
+    fn test_update_index_without_state() {
+        let (mut store, config) = fixture_store(10, 2);
+        let store = &mut store;
+        let top_level = config.top_level();
+        let index = &mut create_account_index(":memory:").unwrap();
+        let update = update_index::<AccountIndex>(index, store, None).unwrap();
+
+        assert_eq!(update.indexed, 20);
+        assert_eq!(valid_indexed_level(index, store).unwrap(), Some(top_level));
+
+        diesel::delete(index_state::table).execute(index).unwrap();
+        let update = update_index::<AccountIndex>(index, store, None).unwrap();
+
+        assert_eq!(
+            update,
+            IndexUpdate {
+                rebuilt: false,
+                indexed: 20,
+                indexed_level: Some(top_level),
+            }
+        );
+    }
+}
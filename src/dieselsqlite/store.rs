@@ -0,0 +1,412 @@
+use crate::dieselsqlite::{
+    DATABASE_URL_KEY,
+    connection::{ConnectionBuilder, DEFAULT_BUSY_TIMEOUT, JournalMode, Synchronous, TempStore},
+};
+use diesel::{connection::SimpleConnection, prelude::*};
+use dotenvy::dotenv;
+use serde::Deserialize;
+use std::{
+    env,
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::Path,
+    time::Duration,
+};
+
+/// Environment variable giving the path of a TOML store configuration.
+pub const STORE_CONFIG_KEY: &str = "STORE_CONFIG";
+
+#[derive(Debug)]
+pub enum StoreConfigError {
+    MissingPath,
+    InvalidFile { path: String, error: io::Error },
+    InvalidToml { error: toml::de::Error },
+}
+
+impl Display for StoreConfigError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let string = match self {
+            StoreConfigError::MissingPath => format!(
+                "Store path wasn't provided, set it in the configuration or with {}",
+                DATABASE_URL_KEY
+            ),
+            StoreConfigError::InvalidFile { path, error } => {
+                format!("Can't read store configuration {}:{}", path, error)
+            }
+            StoreConfigError::InvalidToml { error } => {
+                format!("Invalid store configuration:{}", error)
+            }
+        };
+        write!(f, "{}", string)
+    }
+}
+
+impl std::error::Error for StoreConfigError {}
+
+impl From<toml::de::Error> for StoreConfigError {
+    fn from(error: toml::de::Error) -> StoreConfigError {
+        StoreConfigError::InvalidToml { error }
+    }
+}
+
+impl From<StoreConfigError> for ConnectionError {
+    fn from(error: StoreConfigError) -> ConnectionError {
+        ConnectionError::InvalidConnectionUrl(error.to_string())
+    }
+}
+
+/// Store configuration as written in a TOML file. Every field but `path` is
+/// optional, and `StoreConfig::from_env` takes the path from `DATABASE_URL`
+/// when the file doesn't provide one.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StoreConfigFile {
+    path: Option<String>,
+    read_only: Option<bool>,
+    immutable: Option<bool>,
+    busy_timeout_ms: Option<u64>,
+    journal_mode: Option<JournalMode>,
+    synchronous: Option<Synchronous>,
+    cache_size: Option<i64>,
+    mmap_size: Option<i64>,
+    temp_store: Option<TempStore>,
+}
+
+fn read_config_file(path: impl AsRef<Path>) -> Result<StoreConfigFile, StoreConfigError> {
+    let path = path.as_ref();
+    let toml = fs::read_to_string(path).map_err(|error| StoreConfigError::InvalidFile {
+        path: path.display().to_string(),
+        error,
+    })?;
+    Ok(toml::from_str(&toml)?)
+}
+
+/// Path and open flags (see `ConnectionBuilder`) and PRAGMAs used to open
+/// the store. PRAGMAs left to `None` keep SQLite's (or the database's)
+/// current value. Don't set `journal_mode` on a read-only store, changing it
+/// requires writing to the database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreConfig {
+    connection: ConnectionBuilder,
+    journal_mode: Option<JournalMode>,
+    synchronous: Option<Synchronous>,
+    cache_size: Option<i64>,
+    mmap_size: Option<i64>,
+    temp_store: Option<TempStore>,
+}
+
+impl StoreConfig {
+    pub fn new(path: &str) -> StoreConfig {
+        StoreConfig {
+            connection: ConnectionBuilder::new(path),
+            journal_mode: None,
+            synchronous: None,
+            cache_size: None,
+            mmap_size: None,
+            temp_store: None,
+        }
+    }
+
+    /// Reads the TOML file given by `STORE_CONFIG` if set, taking the path
+    /// from `DATABASE_URL` when the file doesn't provide one. This is the only
+    /// constructor reading the environment (and `.env`).
+    pub fn from_env() -> Result<StoreConfig, StoreConfigError> {
+        dotenv().ok();
+        let file = match env::var(STORE_CONFIG_KEY) {
+            Ok(path) => read_config_file(&path)?,
+            Err(_) => StoreConfigFile::default(),
+        };
+        let path = file
+            .path
+            .clone()
+            .or_else(|| env::var(DATABASE_URL_KEY).ok());
+        StoreConfig::from_config_file(file, path)
+    }
+
+    pub fn from_toml_file(path: impl AsRef<Path>) -> Result<StoreConfig, StoreConfigError> {
+        let file = read_config_file(path)?;
+        let path = file.path.clone();
+        StoreConfig::from_config_file(file, path)
+    }
+
+    pub fn from_toml(toml: &str) -> Result<StoreConfig, StoreConfigError> {
+        let file: StoreConfigFile = toml::from_str(toml)?;
+        let path = file.path.clone();
+        StoreConfig::from_config_file(file, path)
+    }
+
+    fn from_config_file(
+        file: StoreConfigFile,
+        path: Option<String>,
+    ) -> Result<StoreConfig, StoreConfigError> {
+        let path = path.ok_or(StoreConfigError::MissingPath)?;
+        let mut connection = ConnectionBuilder::new(&path)
+            .read_only(file.read_only.unwrap_or(false))
+            .immutable(file.immutable.unwrap_or(false));
+        if let Some(busy_timeout_ms) = file.busy_timeout_ms {
+            connection = connection.busy_timeout(Duration::from_millis(busy_timeout_ms));
+        }
+        Ok(StoreConfig {
+            connection,
+            journal_mode: file.journal_mode,
+            synchronous: file.synchronous,
+            cache_size: file.cache_size,
+            mmap_size: file.mmap_size,
+            temp_store: file.temp_store,
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        self.connection.database_url()
+    }
+
+    /// Read-only configuration for a store the node keeps writing to, with
+    /// `DEFAULT_BUSY_TIMEOUT` unless a busy timeout is already set. The
+    /// journal mode is left to the node: setting it requires writing.
+    pub fn into_read_only(mut self) -> StoreConfig {
+        let busy_timeout = self
+            .connection
+            .get_busy_timeout()
+            .unwrap_or(DEFAULT_BUSY_TIMEOUT);
+        self.journal_mode = None;
+        self.read_only(true).busy_timeout(busy_timeout)
+    }
+
+    pub fn read_only(mut self, read_only: bool) -> StoreConfig {
+        self.connection = self.connection.read_only(read_only);
+        self
+    }
+
+    pub fn immutable(mut self, immutable: bool) -> StoreConfig {
+        self.connection = self.connection.immutable(immutable);
+        self
+    }
+
+    pub fn busy_timeout(mut self, busy_timeout: Duration) -> StoreConfig {
+        self.connection = self.connection.busy_timeout(busy_timeout);
+        self
+    }
+
+    pub fn journal_mode(mut self, journal_mode: JournalMode) -> StoreConfig {
+        self.journal_mode = Some(journal_mode);
+        self
+    }
+
+    pub fn synchronous(mut self, synchronous: Synchronous) -> StoreConfig {
+        self.synchronous = Some(synchronous);
+        self
+    }
+
+    /// Negative values are in KiB, positive ones in pages.
+    pub fn cache_size(mut self, cache_size: i64) -> StoreConfig {
+        self.cache_size = Some(cache_size);
+        self
+    }
+
+    /// In bytes, 0 disables memory-mapped I/O.
+    pub fn mmap_size(mut self, mmap_size: i64) -> StoreConfig {
+        self.mmap_size = Some(mmap_size);
+        self
+    }
+
+    pub fn temp_store(mut self, temp_store: TempStore) -> StoreConfig {
+        self.temp_store = Some(temp_store);
+        self
+    }
+
+    /// URI given to SQLite, carrying the open flags as query parameters.
+    pub fn uri(&self) -> String {
+        self.connection.uri()
+    }
+
+    /// PRAGMAs run on every new connection.
+    pub fn pragmas(&self) -> String {
+        let mut pragmas = String::new();
+        if let Some(busy_timeout) = self.connection.get_busy_timeout() {
+            pragmas += &format!("PRAGMA busy_timeout = {};", busy_timeout.as_millis());
+        }
+        pragmas + &self.store_pragmas()
+    }
+
+    /// PRAGMAs of the store on top of the ones of its `ConnectionBuilder`.
+    fn store_pragmas(&self) -> String {
+        let mut pragmas = String::new();
+        if let Some(journal_mode) = self.journal_mode {
+            pragmas += &format!("PRAGMA journal_mode = {};", journal_mode);
+        }
+        if let Some(synchronous) = self.synchronous {
+            pragmas += &format!("PRAGMA synchronous = {};", synchronous);
+        }
+        if let Some(cache_size) = self.cache_size {
+            pragmas += &format!("PRAGMA cache_size = {};", cache_size);
+        }
+        if let Some(mmap_size) = self.mmap_size {
+            pragmas += &format!("PRAGMA mmap_size = {};", mmap_size);
+        }
+        if let Some(temp_store) = self.temp_store {
+            pragmas += &format!("PRAGMA temp_store = {};", temp_store);
+        }
+        pragmas
+    }
+
+    /// Opens a connection with the `ConnectionBuilder` and applies the
+    /// PRAGMAs of the store.
+    pub fn establish(&self) -> ConnectionResult<SqliteConnection> {
+        let mut connection = self.connection.establish()?;
+        connection
+            .batch_execute(&self.store_pragmas())
+            .map_err(ConnectionError::CouldntSetupConfiguration)?;
+        Ok(connection)
+    }
+}
+
+/// Store opened from a `StoreConfig`.
+pub struct Store {
+    config: StoreConfig,
+    connection: SqliteConnection,
+}
+
+impl Store {
+    pub fn open(config: StoreConfig) -> ConnectionResult<Store> {
+        let connection = config.establish()?;
+        Ok(Store { config, connection })
+    }
+
+    pub fn config(&self) -> &StoreConfig {
+        &self.config
+    }
+
+    pub fn connection(&mut self) -> &mut SqliteConnection {
+        &mut self.connection
+    }
+
+    pub fn into_connection(self) -> SqliteConnection {
+        self.connection
+    }
+}
+
+#[cfg(test)]
+mod store_test {
+    use super::*;
+    use crate::dieselsqlite::connection::journal_mode;
+    use diesel::sql_types::BigInt;
+    use std::{fs, process};
+
+    #[derive(QueryableByName)]
+    struct PragmaValue {
+        #[diesel(sql_type = BigInt)]
+        value: i64,
+    }
+
+    fn pragma(connection: &mut SqliteConnection, pragma: &str, column: &str) -> i64 {
+        diesel::sql_query(format!("SELECT {column} AS value FROM pragma_{pragma}()"))
+            .get_result::<PragmaValue>(connection)
+            .unwrap()
+            .value
+    }
+
+    #[test]
+    fn test_store_config_from_toml() {
+        let config = StoreConfig::from_toml(
+            r#"
+            path = "/data/store.sqlite"
+            read_only = true
+            busy_timeout_ms = 1000
+            synchronous = "normal"
+            cache_size = -2000
+            mmap_size = 268435456
+            temp_store = "memory"
+            "#,
+        )
+        .unwrap();
+
+        let expected_config = StoreConfig::new("/data/store.sqlite")
+            .read_only(true)
+            .busy_timeout(Duration::from_secs(1))
+            .synchronous(Synchronous::Normal)
+            .cache_size(-2000)
+            .mmap_size(268435456)
+            .temp_store(TempStore::Memory);
+
+        assert_eq!(config, expected_config);
+
+        let unknown_field = StoreConfig::from_toml("path = \"store.sqlite\"\ncache = 1");
+
+        assert!(matches!(
+            unknown_field,
+            Err(StoreConfigError::InvalidToml { .. })
+        ));
+
+        // Unlike `from_env`, it doesn't read `DATABASE_URL`, set for the tests.
+        let missing_path = StoreConfig::from_toml("read_only = true");
+
+        assert!(matches!(missing_path, Err(StoreConfigError::MissingPath)));
+
+        let invalid_value =
+            StoreConfig::from_toml("path = \"store.sqlite\"\njournal_mode = \"fast\"");
+
+        assert!(matches!(
+            invalid_value,
+            Err(StoreConfigError::InvalidToml { .. })
+        ));
+    }
+
+    #[test]
+    fn test_store_config_from_env_and_file() {
+        // `DATABASE_URL` is set for the tests, and `STORE_CONFIG` isn't.
+        if env::var(STORE_CONFIG_KEY).is_err()
+            && let Ok(database_url) = env::var(DATABASE_URL_KEY)
+        {
+            assert_eq!(StoreConfig::from_env().unwrap().path(), database_url);
+        }
+
+        let path = env::temp_dir().join(format!("evmnodetooling-store-{}.toml", process::id()));
+        fs::write(&path, "path = \"/data/store.sqlite\"\nmmap_size = 0\n").unwrap();
+        let config = StoreConfig::from_toml_file(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            config.unwrap(),
+            StoreConfig::new("/data/store.sqlite").mmap_size(0)
+        );
+    }
+
+    #[test]
+    fn test_store_config_into_read_only() {
+        let config = StoreConfig::new("/data/store.sqlite")
+            .journal_mode(JournalMode::Wal)
+            .synchronous(Synchronous::Normal)
+            .into_read_only();
+
+        assert_eq!(
+            config,
+            StoreConfig::new("/data/store.sqlite")
+                .read_only(true)
+                .busy_timeout(DEFAULT_BUSY_TIMEOUT)
+                .synchronous(Synchronous::Normal)
+        );
+        assert!(!config.pragmas().contains("journal_mode"));
+    }
+
+    #[test]
+    fn test_store_open_applies_pragmas() {
+        let config = StoreConfig::new(":memory:")
+            .busy_timeout(Duration::from_millis(1500))
+            .journal_mode(JournalMode::Memory)
+            .synchronous(Synchronous::Off)
+            .cache_size(-4096)
+            .mmap_size(0)
+            .temp_store(TempStore::Memory);
+        let mut store = Store::open(config.clone()).unwrap();
+
+        assert_eq!(store.config(), &config);
+
+        let connection = store.connection();
+
+        assert_eq!(pragma(connection, "busy_timeout", "timeout"), 1500);
+        assert_eq!(journal_mode(connection).unwrap(), JournalMode::Memory);
+        assert_eq!(pragma(connection, "synchronous", "synchronous"), 0);
+        assert_eq!(pragma(connection, "cache_size", "cache_size"), -4096);
+        assert_eq!(pragma(connection, "temp_store", "temp_store"), 2);
+    }
+}
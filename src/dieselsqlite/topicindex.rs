@@ -0,0 +1,198 @@
+use crate::dieselsqlite::{
+    bootstrap::BootstrapError,
+    error::StoreResult,
+    models::Transaction,
+    sidecar::{INSERT_CHUNK_SIZE, IndexUpdate, SidecarIndex, create_index, update_index},
+    types::{H256, L2Level},
+};
+use diesel::prelude::*;
+use std::collections::BTreeSet;
+
+/// Environment variable giving the path of the topic index used by the server.
+pub const TOPIC_INDEX_URL_KEY: &str = "TOPIC_INDEX_URL";
+
+/// Table of the topic index: the blocks having a log with a given topic at a
+/// given position. The index lives in its own database so the node's store is
+/// only ever read.
+pub const TOPIC_INDEX_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS log_topics (
+        topic BLOB NOT NULL,
+        position INTEGER NOT NULL,
+        block_number INTEGER NOT NULL,
+        PRIMARY KEY (topic, position, block_number)
+    ) WITHOUT ROWID;";
+
+diesel::table! {
+    log_topics (topic, position, block_number) {
+        topic -> Binary,
+        position -> Integer,
+        block_number -> Integer,
+    }
+}
+
+/// Opens (creating it if needed) the topic index at `database_url`.
+pub fn create_topic_index(database_url: &str) -> Result<SqliteConnection, BootstrapError> {
+    create_index(database_url, TOPIC_INDEX_SCHEMA)
+}
+
+/// Levels between `lowerlevel` and `upperlevel` having, for each position of
+/// `topics` that isn't a wildcard, a log with one of the topics at that
+/// position. The levels must be covered by the index.
+pub fn candidate_levels(
+    index: &mut SqliteConnection,
+    topics: &[Option<Vec<H256>>],
+    lowerlevel: L2Level,
+    upperlevel: L2Level,
+) -> StoreResult<BTreeSet<L2Level>> {
+    use self::log_topics::dsl::*;
+
+    let mut candidates: Option<BTreeSet<L2Level>> = None;
+    for (topic_position, topic_set) in topics.iter().enumerate() {
+        let Some(topic_set) = topic_set.as_ref().filter(|topic_set| !topic_set.is_empty()) else {
+            continue;
+        };
+        let levels = log_topics
+            .filter(topic.eq_any(topic_set))
+            .filter(position.eq(topic_position as i32))
+            .filter(block_number.between(lowerlevel, upperlevel))
+            .select(block_number)
+            .distinct()
+            .load::<L2Level>(index)?
+            .into_iter()
+            .collect::<BTreeSet<L2Level>>();
+        candidates = Some(match candidates {
+            Some(candidates) => candidates.intersection(&levels).copied().collect(),
+            None => levels,
+        });
+    }
+    Ok(candidates.unwrap_or_default())
+}
+
+/// The topic index, see `SidecarIndex`. A row is a topic, its position in
+/// the log and the level of the block.
+pub struct TopicIndex;
+
+impl SidecarIndex for TopicIndex {
+    type Row = (H256, i32, L2Level);
+
+    fn load(
+        store: &mut SqliteConnection,
+        lowerlevel: L2Level,
+        upperlevel: L2Level,
+    ) -> StoreResult<(Vec<(H256, i32, L2Level)>, usize)> {
+        let receipts = Transaction::select_receipts_range(store, lowerlevel, upperlevel)?;
+        let mut logs = 0;
+        let mut rows = BTreeSet::new();
+        for receipt in &receipts {
+            logs += receipt.fields.logs.len();
+            for log in &receipt.fields.logs {
+                for (position, topic) in log.topics.iter().enumerate() {
+                    rows.insert((*topic, position as i32, receipt.block_number));
+                }
+            }
+        }
+        Ok((rows.into_iter().collect(), logs))
+    }
+
+    fn insert(index: &mut SqliteConnection, rows: &[(H256, i32, L2Level)]) -> StoreResult<()> {
+        for chunk in rows.chunks(INSERT_CHUNK_SIZE) {
+            let values = chunk
+                .iter()
+                .map(|(topic, position, block_number)| {
+                    (
+                        log_topics::topic.eq(topic),
+                        log_topics::position.eq(position),
+                        log_topics::block_number.eq(block_number),
+                    )
+                })
+                .collect::<Vec<_>>();
+            diesel::insert_into(log_topics::table)
+                .values(values)
+                .execute(index)?;
+        }
+        Ok(())
+    }
+
+    fn clear(index: &mut SqliteConnection) -> StoreResult<()> {
+        diesel::delete(log_topics::table).execute(index)?;
+        Ok(())
+    }
+}
+
+/// Indexes the topics of the logs of `store`, see `update_index`.
+pub fn update_topic_index(
+    index: &mut SqliteConnection,
+    store: &mut SqliteConnection,
+    upper_level: Option<L2Level>,
+) -> StoreResult<IndexUpdate> {
+    update_index::<TopicIndex>(index, store, upper_level)
+}
+
+#[cfg(test)]
+mod topic_index_test {
+    use super::*;
+    use crate::dieselsqlite::{fixture::fixture_store, sidecar::valid_indexed_level};
+
+    #[test]
+    fn test_topic_index() {
+        let (mut store, config) = fixture_store(20, 4);
+        let store = &mut store;
+        let start_level = config.start_level;
+        let top_level = config.top_level();
+        let receipts = Transaction::select_receipts_range(store, start_level, top_level).unwrap();
+        let (level, log) = receipts
+            .iter()
+            .flat_map(|receipt| {
+                receipt
+                    .fields
+                    .logs
+                    .iter()
+                    .map(|log| (receipt.block_number, log))
+            })
+            .find(|(_, log)| log.topics.len() >= 2)
+            .unwrap();
+        let log_count = receipts
+            .iter()
+            .map(|receipt| receipt.fields.logs.len())
+            .sum::<usize>();
+
+        let index = &mut create_topic_index(":memory:").unwrap();
+
+        assert_eq!(valid_indexed_level(index, store).unwrap(), None);
+
+        let update = update_topic_index(index, store, None).unwrap();
+
+        assert_eq!(
+            update,
+            IndexUpdate {
+                rebuilt: false,
+                indexed: log_count,
+                indexed_level: Some(top_level),
+            }
+        );
+        assert_eq!(
+            candidate_levels(
+                index,
+                &[Some(vec![log.topics[0]]), None, Some(Vec::new())],
+                start_level,
+                top_level
+            )
+            .unwrap(),
+            BTreeSet::from([level])
+        );
+        assert!(
+            candidate_levels(
+                index,
+                &[None, Some(vec![log.topics[0]])],
+                start_level,
+                top_level
+            )
+            .unwrap()
+            .is_empty()
+        );
+        assert!(
+            candidate_levels(index, &[Some(vec![log.topics[0]])], level + 1, top_level)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,507 @@
+use diesel::{
+    deserialize::{self, FromSql, FromSqlRow},
+    expression::AsExpression,
+    serialize::{self, IsNull, Output, ToSql},
+    sql_types::{Binary, Integer},
+    sqlite::{Sqlite, SqliteValue},
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+use std::{
+    fmt::{self, Display, Formatter},
+    num::{ParseIntError, TryFromIntError},
+    ops::{Add, AddAssign, Sub},
+    str::FromStr,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InvalidBytes {
+    InvalidHex {
+        error: hex::FromHexError,
+    },
+    InvalidLength {
+        type_name: &'static str,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl Display for InvalidBytes {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let string = match self {
+            InvalidBytes::InvalidHex { error } => format!("Invalid hexadecimal:{}", error),
+            InvalidBytes::InvalidLength {
+                type_name,
+                expected,
+                found,
+            } => format!(
+                "Invalid length for {}:expected {} bytes, found {}",
+                type_name, expected, found
+            ),
+        };
+        write!(f, "{}", string)
+    }
+}
+
+impl std::error::Error for InvalidBytes {}
+
+impl From<hex::FromHexError> for InvalidBytes {
+    fn from(error: hex::FromHexError) -> InvalidBytes {
+        InvalidBytes::InvalidHex { error }
+    }
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>, InvalidBytes> {
+    let s = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    Ok(hex::decode(s)?)
+}
+
+/// `0x`-hex text, binary blob, RLP and serde implementations shared by the byte types.
+macro_rules! impl_bytes {
+    ($name:ident) => {
+        impl $name {
+            pub fn as_bytes(&self) -> &[u8] {
+                &self.0
+            }
+
+            pub fn to_vec(&self) -> Vec<u8> {
+                self.0.to_vec()
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+                write!(f, "0x{}", hex::encode(&self.0))
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = InvalidBytes;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $name::from_vec(decode_hex(s)?)
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let s = String::deserialize(deserializer)?;
+                s.parse().map_err(de::Error::custom)
+            }
+        }
+
+        impl rlp::Encodable for $name {
+            fn rlp_append(&self, stream: &mut rlp::RlpStream) {
+                stream.encoder().encode_value(self.as_bytes());
+            }
+        }
+
+        impl ToSql<Binary, Sqlite> for $name {
+            fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> serialize::Result {
+                out.set_value(self.as_bytes());
+                Ok(IsNull::No)
+            }
+        }
+
+        impl FromSql<Binary, Sqlite> for $name {
+            fn from_sql(value: SqliteValue<'_, '_, '_>) -> deserialize::Result<Self> {
+                let bytes = <Vec<u8> as FromSql<Binary, Sqlite>>::from_sql(value)?;
+                Ok($name::from_vec(bytes)?)
+            }
+        }
+    };
+}
+
+/// Declares a byte array type of a known length.
+macro_rules! fixed_bytes {
+    ($(#[$meta:meta])* $name:ident, $length:expr) => {
+        $(#[$meta])*
+        #[derive(
+            Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, AsExpression, FromSqlRow,
+        )]
+        #[diesel(sql_type = Binary)]
+        pub struct $name(pub [u8; $length]);
+
+        impl $name {
+            pub const LENGTH: usize = $length;
+
+            fn from_vec(bytes: Vec<u8>) -> Result<$name, InvalidBytes> {
+                $name::try_from(bytes)
+            }
+        }
+
+        impl From<[u8; $length]> for $name {
+            fn from(bytes: [u8; $length]) -> $name {
+                $name(bytes)
+            }
+        }
+
+        impl TryFrom<&[u8]> for $name {
+            type Error = InvalidBytes;
+
+            fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+                $name::try_from(bytes.to_vec())
+            }
+        }
+
+        impl TryFrom<Vec<u8>> for $name {
+            type Error = InvalidBytes;
+
+            fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+                let found = bytes.len();
+                let bytes = bytes
+                    .try_into()
+                    .map_err(|_| InvalidBytes::InvalidLength {
+                        type_name: stringify!($name),
+                        expected: $length,
+                        found,
+                    })?;
+                Ok($name(bytes))
+            }
+        }
+
+        impl_bytes!($name);
+    };
+}
+
+fixed_bytes!(
+    /// Hash of an L2 block.
+    BlockHash,
+    32
+);
+fixed_bytes!(
+    /// Hash of an L2 transaction.
+    TxHash,
+    32
+);
+fixed_bytes!(
+    /// Ethereum address.
+    Address,
+    20
+);
+fixed_bytes!(
+    /// Hash of the kernel's durable storage (context) after a level.
+    ContextHash,
+    32
+);
+fixed_bytes!(
+    /// Preimage hash of a kernel, announced by a kernel upgrade.
+    RootHash,
+    33
+);
+fixed_bytes!(
+    /// 32 bytes hash with no more specific meaning, e.g. a trie root.
+    H256,
+    32
+);
+fixed_bytes!(
+    /// Logs bloom filter of a block or a receipt.
+    Bloom,
+    256
+);
+fixed_bytes!(
+    /// Signature of the sequencer: the 64 bytes of a Tezos signature, whatever
+    /// the curve of the key (`r` and `s` for ECDSA, without recovery id).
+    Signature,
+    64
+);
+
+impl Bloom {
+    /// Adds the bits of `other`, like a block's bloom accrues its receipts'.
+    pub fn accrue_bloom(&mut self, other: &Bloom) {
+        for (byte, other_byte) in self.0.iter_mut().zip(other.0) {
+            *byte |= other_byte;
+        }
+    }
+}
+
+/// Bytes of no fixed length: payloads, RLP encoded fields and keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Binary)]
+pub struct Bytes(pub Vec<u8>);
+
+impl From<Vec<u8>> for Bytes {
+    fn from(bytes: Vec<u8>) -> Bytes {
+        Bytes(bytes)
+    }
+}
+
+impl From<&[u8]> for Bytes {
+    fn from(bytes: &[u8]) -> Bytes {
+        Bytes(bytes.to_vec())
+    }
+}
+
+impl Bytes {
+    fn from_vec(bytes: Vec<u8>) -> Result<Bytes, InvalidBytes> {
+        Ok(Bytes(bytes))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl_bytes!(Bytes);
+
+/// Declares a level of one of the chains. Levels of different chains can't be compared or
+/// mixed; the conversions from and to `i32` are explicit.
+macro_rules! level {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(
+            Debug,
+            Clone,
+            Copy,
+            Default,
+            PartialEq,
+            Eq,
+            Hash,
+            PartialOrd,
+            Ord,
+            AsExpression,
+            FromSqlRow,
+            Serialize,
+            Deserialize,
+        )]
+        #[diesel(sql_type = Integer)]
+        #[serde(transparent)]
+        pub struct $name(pub i32);
+
+        impl From<i32> for $name {
+            fn from(level: i32) -> $name {
+                $name(level)
+            }
+        }
+
+        impl From<$name> for i32 {
+            fn from(level: $name) -> i32 {
+                level.0
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ParseIntError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                s.parse().map($name)
+            }
+        }
+
+        impl $name {
+            /// `self + offset`, `None` when it overflows.
+            pub fn checked_add(self, offset: i32) -> Option<$name> {
+                self.0.checked_add(offset).map($name)
+            }
+
+            /// `self - offset`, `None` when it overflows.
+            pub fn checked_sub(self, offset: i32) -> Option<$name> {
+                self.0.checked_sub(offset).map($name)
+            }
+        }
+
+        impl Add<i32> for $name {
+            type Output = $name;
+
+            fn add(self, offset: i32) -> $name {
+                $name(self.0 + offset)
+            }
+        }
+
+        impl AddAssign<i32> for $name {
+            fn add_assign(&mut self, offset: i32) {
+                self.0 += offset;
+            }
+        }
+
+        impl Sub<i32> for $name {
+            type Output = $name;
+
+            fn sub(self, offset: i32) -> $name {
+                $name(self.0 - offset)
+            }
+        }
+
+        impl ToSql<Integer, Sqlite> for $name {
+            fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> serialize::Result {
+                <i32 as ToSql<Integer, Sqlite>>::to_sql(&self.0, out)
+            }
+        }
+
+        impl FromSql<Integer, Sqlite> for $name {
+            fn from_sql(value: SqliteValue<'_, '_, '_>) -> deserialize::Result<Self> {
+                Ok($name(<i32 as FromSql<Integer, Sqlite>>::from_sql(value)?))
+            }
+        }
+    };
+}
+
+level!(
+    /// Level of the L1 (Tezos) chain.
+    L1Level
+);
+level!(
+    /// Level of the L2 (EVM) chain, i.e. a block number.
+    ///
+    /// ```compile_fail
+    /// use evmnodetooling::dieselsqlite::types::{L1Level, L2Level};
+    ///
+    /// assert!(L2Level(1) < L1Level(2));
+    /// ```
+    L2Level
+);
+
+/// Number encoded as a `0x`-hex string without leading zeros, like the
+/// quantities of the Ethereum JSON-RPC API, and read with or without them.
+/// Amounts are served as quantities:
+/// JSON numbers can't hold them above 2^64, and JavaScript clients lose
+/// precision above 2^53.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Quantity(pub u128);
+
+impl Display for Quantity {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+impl Serialize for Quantity {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Quantity {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let digits = s
+            .strip_prefix("0x")
+            .filter(|digits| !digits.is_empty())
+            .ok_or_else(|| de::Error::custom(format!("Invalid quantity {}", s)))?;
+        u128::from_str_radix(digits, 16)
+            .map(Quantity)
+            .map_err(|error| de::Error::custom(format!("Invalid quantity {}:{}", s, error)))
+    }
+}
+
+impl From<u64> for Quantity {
+    fn from(value: u64) -> Quantity {
+        Quantity(value.into())
+    }
+}
+
+impl TryFrom<i32> for Quantity {
+    type Error = TryFromIntError;
+
+    fn try_from(value: i32) -> Result<Quantity, TryFromIntError> {
+        u128::try_from(value).map(Quantity)
+    }
+}
+
+impl TryFrom<L2Level> for Quantity {
+    type Error = TryFromIntError;
+
+    fn try_from(level: L2Level) -> Result<Quantity, TryFromIntError> {
+        Quantity::try_from(level.0)
+    }
+}
+
+impl rlp::Encodable for Quantity {
+    fn rlp_append(&self, stream: &mut rlp::RlpStream) {
+        self.0.rlp_append(stream);
+    }
+}
+
+impl From<Quantity> for u128 {
+    fn from(quantity: Quantity) -> u128 {
+        quantity.0
+    }
+}
+
+#[cfg(test)]
+mod types_test {
+    use super::*;
+
+    #[test]
+    fn test_hex_serde() {
+        let hash = BlockHash([0xab; 32]);
+        let json = serde_json::to_string(&hash).unwrap();
+
+        assert_eq!(json, format!("\"0x{}\"", "ab".repeat(32)));
+        assert_eq!(serde_json::from_str::<BlockHash>(&json).unwrap(), hash);
+
+        let address: Address = "0x00000000000000000000000000000000000000ff"
+            .parse()
+            .unwrap();
+
+        assert_eq!(address.0[19], 0xff);
+        assert_eq!(
+            "00000000000000000000000000000000000000ff"
+                .parse::<Address>()
+                .unwrap(),
+            address
+        );
+
+        let bytes: Bytes = serde_json::from_str("\"0x\"").unwrap();
+
+        assert!(bytes.is_empty());
+        assert_eq!(Bytes(vec![1, 2]).to_string(), "0x0102");
+    }
+
+    #[test]
+    fn test_invalid_bytes() {
+        assert_eq!(
+            "0x0102".parse::<TxHash>(),
+            Err(InvalidBytes::InvalidLength {
+                type_name: "TxHash",
+                expected: 32,
+                found: 2
+            })
+        );
+        assert!(matches!(
+            "0xzz".parse::<Bytes>(),
+            Err(InvalidBytes::InvalidHex { .. })
+        ));
+        assert!(serde_json::from_str::<RootHash>("[1, 2, 3]").is_err());
+    }
+
+    #[test]
+    fn test_levels() {
+        let level = L2Level(10);
+
+        assert_eq!(level + 1, L2Level(11));
+        assert_eq!(i32::from(level - 1), 9);
+        assert_eq!(serde_json::to_string(&L1Level(5)).unwrap(), "5");
+        assert_eq!(serde_json::from_str::<L2Level>("7").unwrap(), L2Level(7));
+        assert_eq!("12".parse::<L2Level>(), Ok(L2Level(12)));
+        assert!("twelve".parse::<L2Level>().is_err());
+        assert_eq!(L2Level(i32::MIN).checked_sub(1), None);
+        assert_eq!(level.checked_add(2), Some(L2Level(12)));
+        assert_eq!(Quantity::try_from(L2Level(12)), Ok(Quantity(12)));
+        assert!(Quantity::try_from(L2Level(-1)).is_err());
+    }
+}
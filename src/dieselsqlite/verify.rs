@@ -0,0 +1,1040 @@
+use crate::dieselsqlite::{
+    decoder::{
+        DecodeError, EthBlock, ObjectFields, ReceiptFields, SequencerBlueprint, keccak256,
+        ordered_trie_root,
+    },
+    error::{StoreError, StoreResult},
+    level_batches,
+    models::{Block, Blueprint, SequencerUpgrade, Transaction},
+    types::{Address, BlockHash, Bloom, Bytes, H256, L2Level, Signature, TxHash},
+};
+use blake2::{Blake2b, Digest, digest::consts::U32};
+use diesel::prelude::*;
+use k256::ecdsa::signature::{Verifier, hazmat::PrehashVerifier};
+use std::fmt::{self, Display, Formatter};
+
+const ED25519_TAG: u8 = 0;
+const SECP256K1_TAG: u8 = 1;
+const P256_TAG: u8 = 2;
+/// Number of levels loaded at once by the verifiers.
+const BATCH_SIZE: i32 = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyError {
+    Empty,
+    UnsupportedScheme { tag: u8 },
+    InvalidKey { tag: u8 },
+}
+
+impl Display for KeyError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let string = match self {
+            KeyError::Empty => "Empty sequencer key".to_string(),
+            KeyError::UnsupportedScheme { tag } => {
+                format!("Unsupported sequencer key scheme:{}", tag)
+            }
+            KeyError::InvalidKey { tag } => format!("Invalid sequencer key of scheme {}", tag),
+        };
+        write!(f, "{}", string)
+    }
+}
+
+impl std::error::Error for KeyError {}
+
+/// Public key of a sequencer, as stored in `sequencer_upgrades.sequencer`:
+/// the Tezos binary encoding, a scheme tag (0 for Ed25519, 1 for secp256k1,
+/// 2 for P-256) followed by the key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SequencerKey {
+    Ed25519(ed25519_dalek::VerifyingKey),
+    Secp256k1(k256::ecdsa::VerifyingKey),
+    P256(p256::ecdsa::VerifyingKey),
+}
+
+impl SequencerKey {
+    pub fn decode(bytes: &[u8]) -> Result<SequencerKey, KeyError> {
+        let (tag, key) = bytes.split_first().ok_or(KeyError::Empty)?;
+        let invalid = || KeyError::InvalidKey { tag: *tag };
+        match *tag {
+            ED25519_TAG => {
+                let key = key.try_into().map_err(|_| invalid())?;
+                let key = ed25519_dalek::VerifyingKey::from_bytes(key).map_err(|_| invalid())?;
+                Ok(SequencerKey::Ed25519(key))
+            }
+            SECP256K1_TAG => {
+                let key = k256::ecdsa::VerifyingKey::from_sec1_bytes(key).map_err(|_| invalid())?;
+                Ok(SequencerKey::Secp256k1(key))
+            }
+            P256_TAG => {
+                let key = p256::ecdsa::VerifyingKey::from_sec1_bytes(key).map_err(|_| invalid())?;
+                Ok(SequencerKey::P256(key))
+            }
+            tag => Err(KeyError::UnsupportedScheme { tag }),
+        }
+    }
+
+    pub fn encode(&self) -> Bytes {
+        let mut bytes = Vec::new();
+        match self {
+            SequencerKey::Ed25519(key) => {
+                bytes.push(ED25519_TAG);
+                bytes.extend_from_slice(key.as_bytes());
+            }
+            SequencerKey::Secp256k1(key) => {
+                bytes.push(SECP256K1_TAG);
+                bytes.extend_from_slice(&key.to_sec1_bytes());
+            }
+            SequencerKey::P256(key) => {
+                bytes.push(P256_TAG);
+                bytes.extend_from_slice(key.to_encoded_point(true).as_bytes());
+            }
+        }
+        Bytes(bytes)
+    }
+
+    /// Checks a signature of `message`, made like Tezos signatures over its
+    /// Blake2b 256 bits digest.
+    pub fn verify(&self, message: &[u8], signature: &Signature) -> bool {
+        let digest = blake2b_256(message);
+        match self {
+            SequencerKey::Ed25519(key) => {
+                let signature = ed25519_dalek::Signature::from_bytes(&signature.0);
+                key.verify(&digest, &signature).is_ok()
+            }
+            SequencerKey::Secp256k1(key) => {
+                match k256::ecdsa::Signature::from_slice(&signature.0) {
+                    Ok(signature) => key.verify_prehash(&digest, &signature).is_ok(),
+                    Err(_) => false,
+                }
+            }
+            SequencerKey::P256(key) => match p256::ecdsa::Signature::from_slice(&signature.0) {
+                Ok(signature) => key.verify_prehash(&digest, &signature).is_ok(),
+                Err(_) => false,
+            },
+        }
+    }
+}
+
+pub(crate) fn blake2b_256(message: &[u8]) -> [u8; 32] {
+    Blake2b::<U32>::digest(message).into()
+}
+
+/// Why a blueprint isn't signed by the expected sequencer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SignatureIssue {
+    Undecodable { error: DecodeError },
+    InvalidSequencerKey { error: KeyError },
+    InvalidSignature { chunk_index: u16 },
+}
+
+impl Display for SignatureIssue {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let string = match self {
+            SignatureIssue::Undecodable { error } => format!("Undecodable payload:{}", error),
+            SignatureIssue::InvalidSequencerKey { error } => error.to_string(),
+            SignatureIssue::InvalidSignature { chunk_index } => {
+                format!("Chunk {} not signed by the sequencer", chunk_index)
+            }
+        };
+        write!(f, "{}", string)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidBlueprint {
+    pub level: L2Level,
+    pub issue: SignatureIssue,
+}
+
+/// Outcome of `verify_blueprints`. Blueprints of levels where no sequencer
+/// key is known are counted as unchecked.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SignatureReport {
+    pub verified: usize,
+    pub unchecked: usize,
+    pub invalid: Vec<InvalidBlueprint>,
+}
+
+impl SignatureReport {
+    pub fn is_valid(&self) -> bool {
+        self.invalid.is_empty()
+    }
+}
+
+/// Sequencer keys in force, from the applied sequencer upgrades.
+struct SequencerKeys {
+    initial: Option<SequencerKey>,
+    upgrades: Vec<(L2Level, Result<SequencerKey, KeyError>)>,
+}
+
+impl SequencerKeys {
+    fn at(&self, level: L2Level) -> Option<Result<&SequencerKey, &KeyError>> {
+        let upgrades = self
+            .upgrades
+            .partition_point(|(applied_before, _)| *applied_before <= level);
+        match upgrades.checked_sub(1) {
+            Some(index) => Some(self.upgrades[index].1.as_ref()),
+            None => self.initial.as_ref().map(Ok),
+        }
+    }
+}
+
+fn verify_blueprint(key: &SequencerKey, payload: &Bytes) -> Option<SignatureIssue> {
+    let blueprint = match SequencerBlueprint::decode(payload.as_bytes()) {
+        Ok(blueprint) => blueprint,
+        Err(error) => return Some(SignatureIssue::Undecodable { error }),
+    };
+    blueprint
+        .chunks
+        .iter()
+        .find(|chunk| !key.verify(chunk.unsigned().as_bytes(), &chunk.signature))
+        .map(|chunk| SignatureIssue::InvalidSignature {
+            chunk_index: chunk.chunk_index,
+        })
+}
+
+/// Checks that the chunks of the blueprints from `lowerlevel` to
+/// `upperlevel` are signed by the sequencer in force at their level: the
+/// sequencer of the last upgrade applied before it, or `initial_sequencer`
+/// before the first applied upgrade.
+pub fn verify_blueprints(
+    connection: &mut SqliteConnection,
+    initial_sequencer: Option<SequencerKey>,
+    lowerlevel: L2Level,
+    upperlevel: L2Level,
+) -> StoreResult<SignatureReport> {
+    let keys = SequencerKeys {
+        initial: initial_sequencer,
+        upgrades: SequencerUpgrade::applied_sequencers(connection)?
+            .into_iter()
+            .map(|(level, sequencer)| (level, SequencerKey::decode(sequencer.as_bytes())))
+            .collect(),
+    };
+
+    let mut report = SignatureReport::default();
+    for (batch_start, batch_end) in level_batches(lowerlevel, upperlevel, BATCH_SIZE) {
+        for (level, payload) in Blueprint::select_range(connection, batch_start, batch_end)? {
+            let issue = match keys.at(level) {
+                None => {
+                    report.unchecked += 1;
+                    continue;
+                }
+                Some(Err(error)) => Some(SignatureIssue::InvalidSequencerKey {
+                    error: error.clone(),
+                }),
+                Some(Ok(key)) => verify_blueprint(key, &payload),
+            };
+            match issue {
+                Some(issue) => report.invalid.push(InvalidBlueprint { level, issue }),
+                None => report.verified += 1,
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Whether a verification stops at the first issue or reports all of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyMode {
+    StopAtFirst,
+    ReportAll,
+}
+
+/// Why a stored block isn't intact.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockHashIssue {
+    Undecodable {
+        level: L2Level,
+        error: DecodeError,
+    },
+    HashMismatch {
+        level: L2Level,
+        stored: BlockHash,
+        computed: BlockHash,
+    },
+    /// The hash embedded in the block's blob isn't the hash of its row.
+    EmbeddedHashMismatch {
+        level: L2Level,
+        stored: BlockHash,
+        embedded: BlockHash,
+    },
+    ParentHashMismatch {
+        level: L2Level,
+        parent_hash: BlockHash,
+        previous_hash: BlockHash,
+    },
+}
+
+impl Display for BlockHashIssue {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let string = match self {
+            BlockHashIssue::Undecodable { level, error } => {
+                format!("Level {}:undecodable block:{}", level, error)
+            }
+            BlockHashIssue::HashMismatch {
+                level,
+                stored,
+                computed,
+            } => format!(
+                "Level {}:stored hash {}, computed {}",
+                level, stored, computed
+            ),
+            BlockHashIssue::EmbeddedHashMismatch {
+                level,
+                stored,
+                embedded,
+            } => format!(
+                "Level {}:stored hash {}, hash in the block {}",
+                level, stored, embedded
+            ),
+            BlockHashIssue::ParentHashMismatch {
+                level,
+                parent_hash,
+                previous_hash,
+            } => format!(
+                "Level {}:parent hash {}, previous level hash {}",
+                level, parent_hash, previous_hash
+            ),
+        };
+        write!(f, "{}", string)
+    }
+}
+
+/// Outcome of `verify_block_hashes`: the number of intact blocks and the
+/// issues, in level order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlockHashReport {
+    pub verified: usize,
+    pub issues: Vec<BlockHashIssue>,
+}
+
+impl BlockHashReport {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+fn verify_block(
+    level: L2Level,
+    stored: BlockHash,
+    block: &Bytes,
+    previous: Option<(L2Level, BlockHash)>,
+) -> Vec<BlockHashIssue> {
+    let block = match EthBlock::decode(block.as_bytes()) {
+        Ok(block) => block,
+        Err(error) => return vec![BlockHashIssue::Undecodable { level, error }],
+    };
+    let mut issues = Vec::new();
+    let computed = block.compute_hash();
+    if computed != stored {
+        issues.push(BlockHashIssue::HashMismatch {
+            level,
+            stored,
+            computed,
+        });
+    }
+    if block.hash != stored {
+        issues.push(BlockHashIssue::EmbeddedHashMismatch {
+            level,
+            stored,
+            embedded: block.hash,
+        });
+    }
+    if let Some((previous_level, previous_hash)) = previous
+        && level.checked_sub(1) == Some(previous_level)
+        && previous_hash != block.parent_hash
+    {
+        issues.push(BlockHashIssue::ParentHashMismatch {
+            level,
+            parent_hash: block.parent_hash,
+            previous_hash,
+        });
+    }
+    issues
+}
+
+/// Recomputes the hash of the blocks from `lowerlevel` to `upperlevel` and
+/// checks it against `blocks.hash`, and checks that their parent hash is the
+/// hash stored at the previous level. Blocks are loaded by batches.
+pub fn verify_block_hashes(
+    connection: &mut SqliteConnection,
+    lowerlevel: L2Level,
+    upperlevel: L2Level,
+    mode: VerifyMode,
+) -> StoreResult<BlockHashReport> {
+    let mut previous = match lowerlevel.checked_sub(1) {
+        Some(previous_level) => match Block::select_hash_of_number(connection, previous_level) {
+            Ok(hash) => Some((previous_level, hash)),
+            Err(StoreError::NotFound) => None,
+            Err(error) => return Err(error),
+        },
+        None => None,
+    };
+
+    let mut report = BlockHashReport::default();
+    for (batch_start, batch_end) in level_batches(lowerlevel, upperlevel, BATCH_SIZE) {
+        for (level, stored, block) in Block::select_range(connection, batch_start, batch_end)? {
+            let issues = verify_block(level, stored, &block, previous);
+            previous = Some((level, stored));
+            if issues.is_empty() {
+                report.verified += 1;
+                continue;
+            }
+            report.issues.extend(issues);
+            if mode == VerifyMode::StopAtFirst {
+                return Ok(report);
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Why the transactions stored for a block don't match its header.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockRootIssue {
+    Undecodable {
+        level: L2Level,
+        error: DecodeError,
+    },
+    UndecodableTransaction {
+        level: L2Level,
+        transaction_hash: TxHash,
+        error: DecodeError,
+    },
+    TransactionHashesMismatch {
+        level: L2Level,
+    },
+    ReceiptLogsBloomMismatch {
+        level: L2Level,
+        transaction_hash: TxHash,
+    },
+    /// The Keccak hash of a legacy transaction, encoded back, isn't its hash.
+    LegacyTransactionHashMismatch {
+        level: L2Level,
+        transaction_hash: TxHash,
+        computed: TxHash,
+    },
+    TransactionsRootMismatch {
+        level: L2Level,
+        header: H256,
+        computed: H256,
+    },
+    ReceiptsRootMismatch {
+        level: L2Level,
+        header: H256,
+        computed: H256,
+    },
+    LogsBloomMismatch {
+        level: L2Level,
+    },
+}
+
+impl Display for BlockRootIssue {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let string = match self {
+            BlockRootIssue::Undecodable { level, error } => {
+                format!("Level {}:undecodable block:{}", level, error)
+            }
+            BlockRootIssue::UndecodableTransaction {
+                level,
+                transaction_hash,
+                error,
+            } => format!(
+                "Level {}:undecodable transaction {}:{}",
+                level, transaction_hash, error
+            ),
+            BlockRootIssue::TransactionHashesMismatch { level } => format!(
+                "Level {}:stored transactions aren't the transactions of the block",
+                level
+            ),
+            BlockRootIssue::ReceiptLogsBloomMismatch {
+                level,
+                transaction_hash,
+            } => format!(
+                "Level {}:logs bloom of transaction {} doesn't match its logs",
+                level, transaction_hash
+            ),
+            BlockRootIssue::LegacyTransactionHashMismatch {
+                level,
+                transaction_hash,
+                computed,
+            } => format!(
+                "Level {}:transaction {} hashes to {}",
+                level, transaction_hash, computed
+            ),
+            BlockRootIssue::TransactionsRootMismatch {
+                level,
+                header,
+                computed,
+            } => format!(
+                "Level {}:transactions root {}, computed {}",
+                level, header, computed
+            ),
+            BlockRootIssue::ReceiptsRootMismatch {
+                level,
+                header,
+                computed,
+            } => format!(
+                "Level {}:receipts root {}, computed {}",
+                level, header, computed
+            ),
+            BlockRootIssue::LogsBloomMismatch { level } => {
+                format!("Level {}:logs bloom doesn't match the receipts", level)
+            }
+        };
+        write!(f, "{}", string)
+    }
+}
+
+/// Outcome of `verify_block_roots`: the number of blocks matching their
+/// transactions, the levels whose transactions root couldn't be checked
+/// because of typed transactions, and the issues, in level order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlockRootReport {
+    pub verified: usize,
+    pub unchecked_transactions_roots: Vec<L2Level>,
+    pub issues: Vec<BlockRootIssue>,
+}
+
+impl BlockRootReport {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Whether no block matched its roots, while their transactions matched
+    /// otherwise: more likely the node not building its tries the way they
+    /// are rebuilt here than every block being corrupted.
+    pub fn roots_never_matched(&self) -> bool {
+        self.verified == 0
+            && !self.issues.is_empty()
+            && self.issues.iter().all(|issue| {
+                matches!(
+                    issue,
+                    BlockRootIssue::TransactionsRootMismatch { .. }
+                        | BlockRootIssue::ReceiptsRootMismatch { .. }
+                )
+            })
+    }
+}
+
+/// Hash, to, receipt and object fields of a stored transaction.
+type TransactionFields = (TxHash, Option<Address>, Bytes, Bytes);
+
+fn verify_roots(
+    level: L2Level,
+    block: &Bytes,
+    transactions: &[TransactionFields],
+    report: &mut BlockRootReport,
+) {
+    let block = match EthBlock::decode(block.as_bytes()) {
+        Ok(block) => block,
+        Err(error) => {
+            return report
+                .issues
+                .push(BlockRootIssue::Undecodable { level, error });
+        }
+    };
+    if transactions
+        .iter()
+        .map(|(transaction_hash, ..)| *transaction_hash)
+        .ne(block.transactions.iter().copied())
+    {
+        report
+            .issues
+            .push(BlockRootIssue::TransactionHashesMismatch { level });
+    }
+
+    let mut transaction_envelopes = Some(Vec::new());
+    let mut receipt_envelopes = Vec::new();
+    let mut logs_bloom = Bloom([0u8; 256]);
+    for (transaction_hash, to, receipt_fields, object_fields) in transactions {
+        let decoded = ReceiptFields::decode(receipt_fields.as_bytes())
+            .and_then(|receipt| Ok((receipt, ObjectFields::decode(object_fields.as_bytes())?)));
+        let (receipt, object) = match decoded {
+            Ok(decoded) => decoded,
+            Err(error) => {
+                return report.issues.push(BlockRootIssue::UndecodableTransaction {
+                    level,
+                    transaction_hash: *transaction_hash,
+                    error,
+                });
+            }
+        };
+        let computed_bloom = receipt.compute_logs_bloom();
+        if computed_bloom != receipt.logs_bloom {
+            report
+                .issues
+                .push(BlockRootIssue::ReceiptLogsBloomMismatch {
+                    level,
+                    transaction_hash: *transaction_hash,
+                });
+        }
+        logs_bloom.accrue_bloom(&computed_bloom);
+        if receipt.is_legacy() {
+            let envelope = object.encode_legacy(*to);
+            let computed = TxHash(keccak256(envelope.as_bytes()));
+            if computed != *transaction_hash {
+                report
+                    .issues
+                    .push(BlockRootIssue::LegacyTransactionHashMismatch {
+                        level,
+                        transaction_hash: *transaction_hash,
+                        computed,
+                    });
+            }
+            if let Some(envelopes) = &mut transaction_envelopes {
+                envelopes.push(envelope);
+            }
+        } else {
+            transaction_envelopes = None;
+        }
+        receipt_envelopes.push(receipt.encode_envelope());
+    }
+
+    match transaction_envelopes {
+        Some(envelopes) => {
+            let computed = ordered_trie_root(&envelopes);
+            if computed != block.transactions_root {
+                report
+                    .issues
+                    .push(BlockRootIssue::TransactionsRootMismatch {
+                        level,
+                        header: block.transactions_root,
+                        computed,
+                    });
+            }
+        }
+        None => report.unchecked_transactions_roots.push(level),
+    }
+    let computed = ordered_trie_root(&receipt_envelopes);
+    if computed != block.receipts_root {
+        report.issues.push(BlockRootIssue::ReceiptsRootMismatch {
+            level,
+            header: block.receipts_root,
+            computed,
+        });
+    }
+    if logs_bloom != block.logs_bloom {
+        report
+            .issues
+            .push(BlockRootIssue::LogsBloomMismatch { level });
+    }
+}
+
+/// Rebuilds the transactions and receipts tries of the blocks from
+/// `lowerlevel` to `upperlevel` from their stored transactions, in index
+/// order, and checks their roots and the logs blooms against the headers.
+/// Legacy transactions are encoded back and must hash to their stored hash.
+/// Typed transactions can't be: the node doesn't store their chain id, fee
+/// caps and access list. The transactions root of the blocks holding some
+/// is left unchecked, and their levels are reported.
+///
+/// The tries are rebuilt like Ethereum's: Merkle Patricia tries of the
+/// EIP-2718 envelopes keyed by the RLP of their index. That the kernel
+/// builds its roots this way hasn't been checked against blocks written by
+/// the node; `tests/test_node_store.rs` does, given a copy of a store. The
+/// fixture builds its blocks the same way, so the unit tests only show that
+/// the checks agree with it. See `BlockRootReport::roots_never_matched`.
+pub fn verify_block_roots(
+    connection: &mut SqliteConnection,
+    lowerlevel: L2Level,
+    upperlevel: L2Level,
+    mode: VerifyMode,
+) -> StoreResult<BlockRootReport> {
+    let mut report = BlockRootReport::default();
+    for (batch_start, batch_end) in level_batches(lowerlevel, upperlevel, BATCH_SIZE) {
+        let mut transactions =
+            Transaction::select_fields_range(connection, batch_start, batch_end)?
+                .into_iter()
+                .peekable();
+        for (level, _, block) in Block::select_range(connection, batch_start, batch_end)? {
+            let mut block_transactions = Vec::new();
+            while let Some((block_number, transaction_hash, to, receipt_fields, object_fields)) =
+                transactions.next_if(|(block_number, ..)| *block_number <= level)
+            {
+                if block_number == level {
+                    block_transactions.push((transaction_hash, to, receipt_fields, object_fields));
+                }
+            }
+
+            let issues = report.issues.len();
+            verify_roots(level, &block, &block_transactions, &mut report);
+            if report.issues.len() == issues {
+                report.verified += 1;
+            } else if mode == VerifyMode::StopAtFirst {
+                return Ok(report);
+            }
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod verify_test {
+    use super::*;
+    use crate::dieselsqlite::{
+        bootstrap::create_store,
+        fixture::{FixtureConfig, generate},
+        schema::{blocks, transactions},
+        types::Quantity,
+    };
+    use ed25519_dalek::Signer;
+    use k256::ecdsa::signature::hazmat::PrehashSigner;
+
+    #[test]
+    fn test_sequencer_key() {
+        let secret = ed25519_dalek::SigningKey::from_bytes(&[1u8; 32]);
+        let key = SequencerKey::Ed25519(secret.verifying_key());
+        let signature = Signature(secret.sign(&blake2b_256(b"message")).to_bytes());
+
+        assert_eq!(key.encode().len(), 33);
+        assert_eq!(
+            SequencerKey::decode(key.encode().as_bytes()),
+            Ok(key.clone())
+        );
+        assert!(key.verify(b"message", &signature));
+        assert!(!key.verify(b"other message", &signature));
+
+        let secret = k256::ecdsa::SigningKey::from_slice(&[2u8; 32]).unwrap();
+        let key = SequencerKey::Secp256k1(*secret.verifying_key());
+        let (signature, _) = secret
+            .sign_prehash_recoverable(&blake2b_256(b"message"))
+            .unwrap();
+        let signature = Signature(signature.to_bytes().into());
+
+        assert_eq!(key.encode().len(), 34);
+        assert_eq!(
+            SequencerKey::decode(key.encode().as_bytes()),
+            Ok(key.clone())
+        );
+        assert!(key.verify(b"message", &signature));
+        assert!(!key.verify(b"other message", &signature));
+
+        let secret = p256::ecdsa::SigningKey::from_slice(&[3u8; 32]).unwrap();
+        let key = SequencerKey::P256(*secret.verifying_key());
+        let signature: p256::ecdsa::Signature =
+            secret.sign_prehash(&blake2b_256(b"message")).unwrap();
+        let signature = Signature(signature.to_bytes().into());
+
+        assert_eq!(key.encode().len(), 34);
+        assert_eq!(
+            SequencerKey::decode(key.encode().as_bytes()),
+            Ok(key.clone())
+        );
+        assert!(key.verify(b"message", &signature));
+        assert!(!key.verify(b"other message", &signature));
+
+        assert_eq!(SequencerKey::decode(&[]), Err(KeyError::Empty));
+        assert_eq!(
+            SequencerKey::decode(&[3u8; 33]),
+            Err(KeyError::UnsupportedScheme { tag: 3 })
+        );
+        assert_eq!(
+            SequencerKey::decode(&[2u8; 33]),
+            Err(KeyError::InvalidKey { tag: 2 })
+        );
+        assert_eq!(
+            SequencerKey::decode(&[0u8; 20]),
+            Err(KeyError::InvalidKey { tag: 0 })
+        );
+    }
+
+    #[test]
+    fn test_verify_blueprints() {
+        let connection = &mut create_store(":memory:").unwrap();
+        let config = FixtureConfig {
+            levels: 30,
+            transactions_per_block: 2,
+            sequencer_upgrade_interval: Some(10),
+            ..FixtureConfig::default()
+        };
+        generate(connection, &config).unwrap();
+        let start_level = config.start_level;
+        let top_level = config.top_level();
+
+        let report = verify_blueprints(
+            connection,
+            Some(config.sequencer_key()),
+            start_level,
+            top_level,
+        )
+        .unwrap();
+
+        assert_eq!(
+            report,
+            SignatureReport {
+                verified: 30,
+                unchecked: 0,
+                invalid: Vec::new(),
+            }
+        );
+
+        let report = verify_blueprints(connection, None, start_level, top_level).unwrap();
+
+        assert_eq!(report.verified, 19);
+        assert_eq!(report.unchecked, 11);
+
+        let other_sequencer = SequencerKey::Ed25519(
+            ed25519_dalek::SigningKey::from_bytes(&[9u8; 32]).verifying_key(),
+        );
+        let report =
+            verify_blueprints(connection, Some(other_sequencer), start_level, top_level).unwrap();
+
+        assert_eq!(report.verified, 19);
+        assert_eq!(report.invalid.len(), 11);
+        assert_eq!(
+            report.invalid[0],
+            InvalidBlueprint {
+                level: start_level,
+                issue: SignatureIssue::InvalidSignature { chunk_index: 0 },
+            }
+        );
+    }
+
+    #[test]
+    fn test_verify_block_hashes() {
+        let connection = &mut create_store(":memory:").unwrap();
+        let config = FixtureConfig {
+            levels: 20,
+            transactions_per_block: 2,
+            ..FixtureConfig::default()
+        };
+        generate(connection, &config).unwrap();
+        let start_level = config.start_level;
+        let top_level = config.top_level();
+
+        let report =
+            verify_block_hashes(connection, start_level, top_level, VerifyMode::ReportAll).unwrap();
+
+        assert_eq!(
+            report,
+            BlockHashReport {
+                verified: 20,
+                issues: Vec::new(),
+            }
+        );
+        assert_eq!(
+            verify_block_hashes(
+                connection,
+                L2Level(i32::MIN),
+                L2Level(i32::MIN),
+                VerifyMode::ReportAll
+            )
+            .unwrap(),
+            BlockHashReport::default()
+        );
+
+        let tampered_level = start_level + 5;
+        let stored = Block::select_hash_of_number(connection, tampered_level).unwrap();
+        let computed = Block::select_decoded_with_level(connection, tampered_level)
+            .unwrap()
+            .compute_hash();
+        diesel::update(blocks::table.find(tampered_level))
+            .set(blocks::hash.eq(BlockHash([1u8; 32])))
+            .execute(connection)
+            .unwrap();
+        Block {
+            level: top_level + 1,
+            hash: BlockHash([2u8; 32]),
+            block: Bytes::from("block".as_bytes()),
+        }
+        .insert(connection)
+        .unwrap();
+
+        let report = verify_block_hashes(
+            connection,
+            start_level,
+            top_level + 1,
+            VerifyMode::ReportAll,
+        )
+        .unwrap();
+
+        assert_eq!(report.verified, 18);
+        assert_eq!(
+            report.issues[..3],
+            [
+                BlockHashIssue::HashMismatch {
+                    level: tampered_level,
+                    stored: BlockHash([1u8; 32]),
+                    computed,
+                },
+                BlockHashIssue::EmbeddedHashMismatch {
+                    level: tampered_level,
+                    stored: BlockHash([1u8; 32]),
+                    embedded: stored,
+                },
+                BlockHashIssue::ParentHashMismatch {
+                    level: tampered_level + 1,
+                    parent_hash: stored,
+                    previous_hash: BlockHash([1u8; 32]),
+                },
+            ]
+        );
+        assert!(matches!(
+            report.issues[3],
+            BlockHashIssue::Undecodable { level, .. } if level == top_level + 1
+        ));
+
+        let report = verify_block_hashes(
+            connection,
+            start_level,
+            top_level + 1,
+            VerifyMode::StopAtFirst,
+        )
+        .unwrap();
+
+        assert_eq!(report.verified, 5);
+        assert_eq!(report.issues.len(), 2);
+
+        let report = verify_block_hashes(
+            connection,
+            tampered_level + 1,
+            top_level,
+            VerifyMode::StopAtFirst,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            report.issues[..],
+            [BlockHashIssue::ParentHashMismatch { .. }]
+        ));
+
+        let embedded_level = start_level + 10;
+        let block = Block::select_decoded_with_level(connection, embedded_level).unwrap();
+        let stored = block.hash;
+        let block = EthBlock {
+            hash: BlockHash([3u8; 32]),
+            ..block
+        };
+        diesel::update(blocks::table.find(embedded_level))
+            .set(blocks::block.eq(block.encode()))
+            .execute(connection)
+            .unwrap();
+        let report = verify_block_hashes(
+            connection,
+            embedded_level,
+            embedded_level,
+            VerifyMode::ReportAll,
+        )
+        .unwrap();
+
+        assert_eq!(
+            report.issues,
+            vec![BlockHashIssue::EmbeddedHashMismatch {
+                level: embedded_level,
+                stored,
+                embedded: BlockHash([3u8; 32]),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_verify_block_roots() {
+        let connection = &mut create_store(":memory:").unwrap();
+        let config = FixtureConfig {
+            levels: 20,
+            transactions_per_block: 3,
+            ..FixtureConfig::default()
+        };
+        generate(connection, &config).unwrap();
+        let start_level = config.start_level;
+        let top_level = config.top_level();
+
+        let report =
+            verify_block_roots(connection, start_level, top_level, VerifyMode::ReportAll).unwrap();
+
+        assert_eq!(
+            report,
+            BlockRootReport {
+                verified: 20,
+                unchecked_transactions_roots: Vec::new(),
+                issues: Vec::new(),
+            }
+        );
+
+        let receipt = &Transaction::select_receipts_from_block_number(connection, start_level + 2)
+            .unwrap()[0];
+        let fields = ReceiptFields {
+            status: 0,
+            ..receipt.fields.clone()
+        };
+        diesel::update(transactions::table.filter(transactions::hash.eq(receipt.hash)))
+            .set(transactions::receipt_fields.eq(fields.encode()))
+            .execute(connection)
+            .unwrap();
+        let object =
+            &Transaction::select_objects_from_block_number(connection, start_level + 4).unwrap()[1];
+        let fields = ObjectFields {
+            nonce: Quantity(object.fields.nonce.0 + 1),
+            ..object.fields.clone()
+        };
+        diesel::update(transactions::table.filter(transactions::hash.eq(object.hash)))
+            .set(transactions::object_fields.eq(fields.encode()))
+            .execute(connection)
+            .unwrap();
+        let receipt = &Transaction::select_receipts_from_block_number(connection, start_level + 6)
+            .unwrap()[2];
+        let fields = ReceiptFields {
+            transaction_type: 2,
+            ..receipt.fields.clone()
+        };
+        diesel::update(transactions::table.filter(transactions::hash.eq(receipt.hash)))
+            .set(transactions::receipt_fields.eq(fields.encode()))
+            .execute(connection)
+            .unwrap();
+
+        let report =
+            verify_block_roots(connection, start_level, top_level, VerifyMode::ReportAll).unwrap();
+
+        assert_eq!(report.verified, 17);
+        assert!(!report.roots_never_matched());
+        assert_eq!(report.unchecked_transactions_roots, vec![start_level + 6]);
+
+        let receipts_report = verify_block_roots(
+            connection,
+            start_level + 2,
+            start_level + 2,
+            VerifyMode::ReportAll,
+        )
+        .unwrap();
+
+        assert!(receipts_report.roots_never_matched());
+        assert!(matches!(
+            report.issues[..],
+            [
+                BlockRootIssue::ReceiptsRootMismatch { level: receipts_level, .. },
+                BlockRootIssue::LegacyTransactionHashMismatch {
+                    level: hash_level,
+                    transaction_hash,
+                    ..
+                },
+                BlockRootIssue::TransactionsRootMismatch { level: transactions_level, .. },
+                BlockRootIssue::ReceiptsRootMismatch { level: typed_level, .. },
+            ] if receipts_level == start_level + 2
+                && hash_level == start_level + 4
+                && transaction_hash == object.hash
+                && transactions_level == start_level + 4
+                && typed_level == start_level + 6
+        ));
+
+        diesel::delete(transactions::table.filter(transactions::block_number.eq(start_level + 1)))
+            .execute(connection)
+            .unwrap();
+
+        let report =
+            verify_block_roots(connection, start_level, top_level, VerifyMode::StopAtFirst)
+                .unwrap();
+
+        assert!(!report.roots_never_matched());
+        assert_eq!(report.verified, 1);
+        assert!(matches!(
+            report.issues[..],
+            [
+                BlockRootIssue::TransactionHashesMismatch { .. },
+                BlockRootIssue::TransactionsRootMismatch { .. },
+                BlockRootIssue::ReceiptsRootMismatch { .. },
+                ..
+            ]
+        ));
+    }
+}
@@ -1,11 +1,17 @@
-use diesel::{Connection, result::Error};
-use evmnodetooling::dieselsqlite::{establish_connection, models::*};
+use diesel::Connection;
+use evmnodetooling::dieselsqlite::{
+    applyblueprint::*,
+    decoder::{TransactionObject, TransactionReceipt},
+    establish_connection,
+    models::*,
+    types::{BlockHash, TxHash},
+};
 
 #[test]
 fn test_apply_blueprint_iterations() {
     let connection = &mut establish_connection().unwrap();
 
-    connection.test_transaction::<_, Error, _>(|conn| {
+    connection.test_transaction::<_, ApplyBlueprintError, _>(|conn| {
         let iter = 10;
         let select_index = Blueprint::base_level(conn)?;
         let clear_index = Blueprint::top_level(conn)?;
@@ -21,7 +27,7 @@ fn test_apply_blueprint_iterations() {
 
         let transactions_len = transaction_receipts.len();
 
-        let context_hash_vector = ContextHash::select(conn, select_index)?;
+        let context_hash_vector = ContextHashRow::select(conn, select_index)?;
 
         for _ in 0..iter {
             let insert_index = Blueprint::top_level(conn)? + 1;
@@ -33,54 +39,49 @@ fn test_apply_blueprint_iterations() {
             };
 
             rand::fill(&mut bytes);
-            let hash = Vec::from(bytes);
+            let hash = BlockHash(bytes);
 
             let transactions_hash = (0..transactions_len)
                 .map(|_| {
                     rand::fill(&mut bytes);
-                    Vec::from(bytes)
+                    TxHash(bytes)
                 })
-                .collect::<Vec<Vec<u8>>>();
+                .collect::<Vec<TxHash>>();
 
             let block = Block {
                 level: insert_index,
-                hash: hash.clone(),
+                hash,
                 block: block_vector.clone(),
             };
 
             let transactions = transaction_receipts
                 .clone()
                 .into_iter()
-                .zip(transactions_hash.clone().into_iter())
-                .zip(transaction_objects.clone().into_iter())
-                .map(
-                    |(
-                        ((block_hash, index_, _, from_, to_, receipt_fields), hash),
-                        (_, _, _, _, object_fields),
-                    )| Transaction {
-                        block_hash,
-                        block_number: insert_index,
-                        index_,
-                        hash,
-                        from_,
-                        to_,
-                        receipt_fields,
-                        object_fields,
-                    },
-                )
+                .zip(transactions_hash.clone())
+                .zip(transaction_objects.clone())
+                .map(|((receipt, transaction_hash), object)| Transaction {
+                    block_hash: hash,
+                    block_number: insert_index,
+                    hash: transaction_hash,
+                    ..Transaction::from_decoded(&receipt, &object)
+                })
                 .collect::<Vec<Transaction>>();
 
-            let context_hash = ContextHash {
+            let context_hash = ContextHashRow {
                 id: insert_index,
-                context_hash: context_hash_vector.clone(),
+                context_hash: context_hash_vector,
+            };
+
+            let input = ApplyBlueprint {
+                blueprint,
+                block,
+                transactions,
+                context_hash,
             };
+            let applied = apply_blueprint(conn, input)?;
 
-            let _ = PendingConfirmation::select_with_level(conn, blueprint.id);
-            blueprint.insert(conn)?;
-            block.insert(conn)?;
-            Transaction::batch_insert(conn, &transactions)?;
-            context_hash.insert(conn)?;
-            let _history_mode = Metadata::get_history_mode(conn)?;
+            assert_eq!(applied.level, insert_index);
+            assert_eq!(applied.inserted_transactions, transactions_len);
 
             let (insertedpayload, insertedtimestamp) = Blueprint::select(conn, insert_index)?;
             let insertedhash = Block::select_hash_of_number(conn, insert_index)?;
@@ -94,24 +95,28 @@ fn test_apply_blueprint_iterations() {
             let transaction_receipts = transaction_receipts
                 .clone()
                 .into_iter()
-                .zip(transactions_hash.clone().into_iter())
-                .map(
-                    |((block_hash, index_, _, from_, to_, receipt_fields), hash)| {
-                        (block_hash, index_, hash, from_, to_, receipt_fields)
-                    },
-                )
-                .collect::<Vec<(Vec<u8>, i32, Vec<u8>, Vec<u8>, Option<Vec<u8>>, Vec<u8>)>>();
+                .zip(transactions_hash.clone())
+                .map(|(receipt, transaction_hash)| TransactionReceipt {
+                    block_hash: hash,
+                    block_number: insert_index,
+                    hash: transaction_hash,
+                    ..receipt
+                })
+                .collect::<Vec<TransactionReceipt>>();
 
             let transaction_objects = transaction_objects
                 .clone()
                 .into_iter()
-                .zip(transactions_hash.clone().into_iter())
-                .map(|((index_, _, from_, to_, object_fields), hash)| {
-                    (index_, hash, from_, to_, object_fields)
+                .zip(transactions_hash.clone())
+                .map(|(object, transaction_hash)| TransactionObject {
+                    block_hash: hash,
+                    block_number: insert_index,
+                    hash: transaction_hash,
+                    ..object
                 })
-                .collect::<Vec<(i32, Vec<u8>, Vec<u8>, Option<Vec<u8>>, Vec<u8>)>>();
+                .collect::<Vec<TransactionObject>>();
 
-            let inserted_context_hash = ContextHash::select(conn, insert_index)?;
+            let inserted_context_hash = ContextHashRow::select(conn, insert_index)?;
 
             assert_eq!(payload, insertedpayload);
             assert_eq!(timestamp, insertedtimestamp);
@@ -124,7 +129,7 @@ fn test_apply_blueprint_iterations() {
 
         Blueprint::clear_after(conn, clear_index)?;
         Block::clear_after(conn, clear_index)?;
-        ContextHash::clear_after(conn, clear_index)?;
+        ContextHashRow::clear_after(conn, clear_index)?;
         Transaction::clear_after(conn, clear_index)?;
 
         Ok(())
@@ -0,0 +1,185 @@
+//! Checks of the crate against a copy of a store written by octez-evm-node.
+//! They are ignored by default: run them with
+//! `NODE_STORE_URL=/path/to/store.sqlite cargo test --test test_node_store -- --ignored`.
+
+use diesel::prelude::*;
+use evmnodetooling::dieselsqlite::{
+    bootstrap::{MIGRATIONS, create_store, create_store_like},
+    decoder::{EthBlock, ObjectFields, ReceiptFields, SequencerBlueprint},
+    models::{Block, Blueprint, Schema, SchemaColumn, Transaction},
+    schema::migrations,
+    store::{Store, StoreConfig},
+    types::{L2Level, Quantity, TxHash},
+    verify::{VerifyMode, verify_block_roots},
+};
+use std::env;
+
+const NODE_STORE_URL_KEY: &str = "NODE_STORE_URL";
+/// Number of levels read at once from the node's store.
+const BATCH_SIZE: i32 = 1000;
+
+fn node_store() -> SqliteConnection {
+    let path = env::var(NODE_STORE_URL_KEY)
+        .unwrap_or_else(|_| panic!("Provide {NODE_STORE_URL_KEY}, a copy of a node's store"));
+    Store::open(StoreConfig::new(&path).into_read_only())
+        .unwrap()
+        .into_connection()
+}
+
+#[test]
+#[ignore = "needs NODE_STORE_URL, a copy of a node's store.sqlite"]
+fn test_bootstrap_matches_node_store() {
+    let node = &mut node_store();
+    let created = &mut create_store(":memory:").unwrap();
+
+    let node_migrations = migrations::table
+        .select(migrations::name)
+        .order_by(migrations::id)
+        .load::<Option<String>>(node)
+        .unwrap();
+    let expected_migrations = MIGRATIONS
+        .iter()
+        .map(|(name, _)| Some(name.to_string()))
+        .collect::<Vec<Option<String>>>();
+
+    assert_eq!(node_migrations, expected_migrations);
+
+    let tables = Schema::table_names(created).unwrap();
+
+    assert_eq!(Schema::table_names(node).unwrap(), tables);
+
+    for table in tables {
+        assert_eq!(
+            SchemaColumn::of_table(node, &table).unwrap(),
+            SchemaColumn::of_table(created, &table).unwrap(),
+            "{table}"
+        );
+    }
+
+    let like = &mut create_store_like(":memory:", node).unwrap();
+
+    assert_eq!(
+        Schema::create_statements(like).unwrap(),
+        Schema::create_statements(node).unwrap()
+    );
+}
+
+/// Consecutive ranges of `BATCH_SIZE` levels covering the blocks of the store.
+fn batches(node: &mut SqliteConnection) -> Vec<(L2Level, L2Level)> {
+    let base_level = Block::base_level(node).unwrap();
+    let top_level = Block::top_level(node).unwrap();
+    (base_level.0..=top_level.0)
+        .step_by(BATCH_SIZE as usize)
+        .map(|start| {
+            (
+                L2Level(start),
+                L2Level(start + BATCH_SIZE - 1).min(top_level),
+            )
+        })
+        .collect()
+}
+
+/// Decodes every block, receipt and object written by the node. The decoded
+/// fields must match the columns of their row and the other tables, the
+/// blocks must hash to their stored hash, and the receipt and object fields
+/// must encode back to the stored blobs.
+#[test]
+#[ignore = "needs NODE_STORE_URL, a copy of a node's store.sqlite"]
+fn test_decode_node_store() {
+    let node = &mut node_store();
+
+    for (lowerlevel, upperlevel) in batches(node) {
+        let blocks = Block::select_range(node, lowerlevel, upperlevel).unwrap();
+        let fields = Transaction::select_fields_range(node, lowerlevel, upperlevel).unwrap();
+        let receipts = Transaction::select_receipts_range(node, lowerlevel, upperlevel).unwrap();
+
+        for (level, hash, block) in blocks {
+            let block = EthBlock::decode(block.as_bytes()).unwrap();
+            let block_receipts = receipts
+                .iter()
+                .filter(|receipt| receipt.block_number == level)
+                .collect::<Vec<_>>();
+
+            assert_eq!(block.number, level.0 as u64, "level {level}");
+            assert_eq!(block.hash, hash, "level {level}");
+            assert_eq!(block.compute_hash(), hash, "level {level}");
+            assert_eq!(
+                block.transactions,
+                block_receipts
+                    .iter()
+                    .map(|receipt| receipt.hash)
+                    .collect::<Vec<TxHash>>(),
+                "level {level}"
+            );
+            assert_eq!(
+                Quantity::from(block.gas_used),
+                block_receipts
+                    .last()
+                    .map_or(Quantity(0), |receipt| receipt.fields.cumulative_gas_used),
+                "level {level}"
+            );
+            for receipt in block_receipts {
+                assert_eq!(receipt.block_hash, hash, "transaction {}", receipt.hash);
+                assert_eq!(
+                    receipt.fields.logs_bloom,
+                    receipt.fields.compute_logs_bloom(),
+                    "transaction {}",
+                    receipt.hash
+                );
+            }
+        }
+        for (_, hash, _, receipt_fields, object_fields) in fields {
+            let receipt = ReceiptFields::decode(receipt_fields.as_bytes()).unwrap();
+            let object = ObjectFields::decode(object_fields.as_bytes()).unwrap();
+
+            assert_eq!(receipt.encode(), receipt_fields, "transaction {hash}");
+            assert_eq!(object.encode(), object_fields, "transaction {hash}");
+        }
+    }
+}
+
+/// Decodes every blueprint kept by the node, whatever the framing of its
+/// chunks, and compares it with the block executed from it.
+#[test]
+#[ignore = "needs NODE_STORE_URL, a copy of a node's store.sqlite"]
+fn test_decode_node_blueprints() {
+    let node = &mut node_store();
+
+    for (lowerlevel, upperlevel) in batches(node) {
+        let blocks = Block::select_range(node, lowerlevel, upperlevel).unwrap();
+
+        for (level, payload) in Blueprint::select_range(node, lowerlevel, upperlevel).unwrap() {
+            let blueprint = SequencerBlueprint::decode(payload.as_bytes())
+                .unwrap_or_else(|error| panic!("level {level}:{error}"));
+
+            assert_eq!(blueprint.number, level.0 as u64, "level {level}");
+            if let Some((_, _, block)) = blocks.iter().find(|(number, _, _)| *number == level) {
+                let block = EthBlock::decode(block.as_bytes()).unwrap();
+
+                assert!(
+                    blueprint.mismatches(&block).is_empty(),
+                    "level {level}:{:?}",
+                    blueprint.mismatches(&block)
+                );
+            }
+        }
+    }
+}
+
+/// Rebuilds the tries of the node's blocks. The levels whose transactions root
+/// can't be checked are printed, run with `--nocapture` to see them.
+#[test]
+#[ignore = "needs NODE_STORE_URL, a copy of a node's store.sqlite"]
+fn test_verify_node_block_roots() {
+    let node = &mut node_store();
+    let base_level = Block::base_level(node).unwrap();
+    let top_level = Block::top_level(node).unwrap();
+
+    let report = verify_block_roots(node, base_level, top_level, VerifyMode::ReportAll).unwrap();
+
+    println!(
+        "Unchecked transactions roots:{:?}",
+        report.unchecked_transactions_roots
+    );
+    assert!(report.is_valid(), "{:?}", report.issues);
+}